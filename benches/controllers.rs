@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use souls::testing::TestWorld;
+
+const ARENA_SIZE: f32 = 100.0;
+
+fn setup_world(controller_count: usize) -> TestWorld {
+    let mut world = TestWorld::new();
+
+    world.spawn_box(
+        Vec3::new(0.0, -0.05, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(ARENA_SIZE, 0.1, ARENA_SIZE),
+    );
+
+    for (translation, size) in [
+        (
+            Vec3::new(ARENA_SIZE / 2.0, 2.5, 0.0),
+            Vec3::new(1.0, 5.0, ARENA_SIZE),
+        ),
+        (
+            Vec3::new(-ARENA_SIZE / 2.0, 2.5, 0.0),
+            Vec3::new(1.0, 5.0, ARENA_SIZE),
+        ),
+        (
+            Vec3::new(0.0, 2.5, ARENA_SIZE / 2.0),
+            Vec3::new(ARENA_SIZE, 5.0, 1.0),
+        ),
+        (
+            Vec3::new(0.0, 2.5, -ARENA_SIZE / 2.0),
+            Vec3::new(ARENA_SIZE, 5.0, 1.0),
+        ),
+    ] {
+        world.spawn_box(translation, Quat::IDENTITY, size);
+    }
+
+    // Lay the characters out on a grid and send each in a different direction, so that over the
+    // course of a benchmark most of them end up sliding along the arena walls.
+    let columns = (controller_count as f32).sqrt().ceil() as usize;
+    let spacing = (ARENA_SIZE - 4.0) / columns as f32;
+
+    for i in 0..controller_count {
+        let x = (i % columns) as f32 * spacing - ARENA_SIZE / 2.0 + 2.0;
+        let z = (i / columns) as f32 * spacing - ARENA_SIZE / 2.0 + 2.0;
+        let angle = i as f32 * 2.4;
+
+        world.spawn_character(
+            Vec3::new(x, 1.0, z),
+            Vec3::new(angle.cos(), 0.0, angle.sin()) * 15.0,
+        );
+    }
+
+    world.prepare();
+
+    world
+}
+
+fn step_controllers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step_controllers");
+
+    for controller_count in [1, 10, 100, 500, 1000] {
+        let mut world = setup_world(controller_count);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(controller_count),
+            &controller_count,
+            |b, _| b.iter(|| world.step(1)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step_controllers);
+criterion_main!(benches);
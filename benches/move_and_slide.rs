@@ -0,0 +1,123 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+use souls::{
+    character_controller::{move_and_slide, SlideContext, SolverFallback, MAX_SLOPE_ANGLE},
+    testing::{character_collider, TestWorld},
+};
+
+/// The displacement of one step at the demo's player speed.
+const STEP_DISTANCE: f32 = 15.0 / 64.0;
+
+struct Case {
+    name: &'static str,
+    translation: Vec3,
+    direction: Vec3,
+}
+
+const CASES: [Case; 4] = [
+    Case {
+        name: "open_ground",
+        translation: Vec3::new(0.0, 1.01, 0.0),
+        direction: Vec3::X,
+    },
+    Case {
+        name: "wall_slide",
+        translation: Vec3::new(9.49, 1.01, 0.0),
+        direction: Vec3::new(1.0, 0.0, 1.0),
+    },
+    Case {
+        name: "acute_corner",
+        translation: Vec3::new(-18.0, 1.01, 18.0),
+        direction: Vec3::new(-1.0, 0.0, 1.0),
+    },
+    Case {
+        name: "stair_climb",
+        translation: Vec3::new(0.0, 1.01, -9.49),
+        direction: Vec3::NEG_Z,
+    },
+];
+
+fn setup_world() -> TestWorld {
+    let mut world = TestWorld::new();
+
+    // Ground
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(60.0, 1.0, 60.0),
+    );
+
+    // Wall to slide along
+    world.spawn_box(
+        Vec3::new(10.5, 2.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 5.0, 20.0),
+    );
+
+    // Two walls meeting at a 30 degree corner
+    world.spawn_box(
+        Vec3::new(-20.0, 2.5, 20.0),
+        Quat::from_rotation_y(15.0_f32.to_radians()),
+        Vec3::new(10.0, 5.0, 1.0),
+    );
+    world.spawn_box(
+        Vec3::new(-20.0, 2.5, 20.0),
+        Quat::from_rotation_y(-15.0_f32.to_radians()),
+        Vec3::new(1.0, 5.0, 10.0),
+    );
+
+    // Stairs with 0.25 tall, 0.5 deep steps
+    for i in 0..8 {
+        let height = 0.25 * (i + 1) as f32;
+
+        world.spawn_box(
+            Vec3::new(0.0, height / 2.0, -10.25 - 0.5 * i as f32),
+            Quat::IDENTITY,
+            Vec3::new(4.0, height, 0.5),
+        );
+    }
+
+    world.sync_colliders();
+
+    world
+}
+
+fn move_and_slide_cases(c: &mut Criterion) {
+    let mut world = setup_world();
+    let spatial_query = world.spatial_query();
+
+    let collider = character_collider();
+    let filter = SpatialQueryFilter::default();
+    let context = SlideContext {
+        spatial_query: &spatial_query,
+        collider: &collider,
+        rotation: Quat::IDENTITY,
+        filter: &filter,
+        up: Dir3::Y,
+        max_slope_angle: MAX_SLOPE_ANGLE,
+        fallback: SolverFallback::Halt,
+        walkable_overrides: None,
+    };
+
+    let mut group = c.benchmark_group("move_and_slide");
+
+    for case in CASES {
+        group.bench_function(case.name, |b| {
+            b.iter(|| {
+                move_and_slide(
+                    &context,
+                    case.translation,
+                    case.direction.normalize() * STEP_DISTANCE,
+                    None,
+                    false,
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, move_and_slide_cases);
+criterion_main!(benches);
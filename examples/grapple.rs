@@ -0,0 +1,263 @@
+//! A grappling hook built on the character controller's external velocity.
+//!
+//! Right click fires the hook along the camera's view, and releasing it lets go. While attached,
+//! the rope pulls the character towards the anchor with a damped spring every fixed step, through
+//! [`CharacterController::apply_impulse`]. Gravity and the slide solver are left alone, so the
+//! character swings under the anchor and slides along whatever it swings into.
+//!
+//! `cargo run --example grapple --features example-tools`
+
+use avian3d::{
+    math::{AdjustPrecision, AsF32},
+    prelude::*,
+};
+use bevy::{color::palettes, prelude::*};
+use bevy_atmosphere::prelude::*;
+use bevy_debug_text_overlay::{screen_print, OverlayPlugin};
+use souls::prelude::*;
+
+const PLAYER_SPEED: f32 = 10.0;
+const JUMP_SPEED: f32 = 6.0;
+const GRAPPLE_RANGE: f32 = 60.0;
+/// How hard the rope pulls per meter it's stretched past its length.
+const ROPE_STIFFNESS: f32 = 40.0;
+/// How much of the speed along the rope is lost per second while it's stretched.
+const ROPE_DAMPING: f32 = 4.0;
+/// How quickly the rope reels in, in meters per second, so swings gain height.
+const REEL_SPEED: f32 = 2.0;
+
+/// Where the player's hook is attached, if anywhere.
+#[derive(Component, Default)]
+struct Grapple {
+    anchor: Option<Vec3>,
+    length: f32,
+}
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            AtmospherePlugin,
+            PhysicsPlugins::new(CustomUpdate),
+            SchedulePlugin,
+            CameraPlugin,
+            CharacterControllerPlugin,
+            OverlayPlugin {
+                font_size: 24.0,
+                ..default()
+            },
+        ))
+        .init_resource::<AtmosphereModel>()
+        .add_systems(Startup, (setup_level, setup_player))
+        .add_systems(Update, (fire_grapple, draw_rope))
+        .add_systems(
+            CustomPreUpdate,
+            (set_velocity, pull_rope)
+                .chain()
+                .after(CameraRotationSet)
+                .before(CharacterControllerSet),
+        )
+        .run()
+}
+
+fn setup_level(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -1.0, 0.4, 0.0)),
+        ..default()
+    });
+
+    let floor_size = Vec3::new(200.0, 1.0, 200.0);
+    let floor_extents = floor_size.adjust_precision();
+
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(floor_extents.x, floor_extents.y, floor_extents.z),
+        PbrBundle {
+            mesh: meshes.add(Cuboid::from_size(floor_size)),
+            material: materials.add(Color::Srgba(palettes::css::DARK_GRAY)),
+            transform: Transform::from_xyz(0.0, -0.5, 0.0),
+            ..default()
+        },
+    ));
+
+    // Two rows of towers to swing between, with beams across the tops to grapple onto.
+    let tower_size = Vec3::new(3.0, 20.0, 3.0);
+    let beam_size = Vec3::new(3.0, 1.0, 24.0);
+    let tower_extents = tower_size.adjust_precision();
+    let beam_extents = beam_size.adjust_precision();
+    let tower_mesh = meshes.add(Cuboid::from_size(tower_size));
+    let beam_mesh = meshes.add(Cuboid::from_size(beam_size));
+    let material = materials.add(Color::Srgba(palettes::css::STEEL_BLUE));
+
+    for i in 0..6 {
+        let x = i as f32 * 25.0 - 60.0;
+
+        for z in [-12.0, 12.0] {
+            commands.spawn((
+                RigidBody::Static,
+                Collider::cuboid(tower_extents.x, tower_extents.y, tower_extents.z),
+                PbrBundle {
+                    mesh: tower_mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(x, tower_size.y / 2.0, z),
+                    ..default()
+                },
+            ));
+        }
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(beam_extents.x, beam_extents.y, beam_extents.z),
+            PbrBundle {
+                mesh: beam_mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(x, tower_size.y + beam_size.y / 2.0, 0.0),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn setup_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        CharacterController::default(),
+        CharacterControllerConfig {
+            air_control: 1.0,
+            ..default()
+        },
+        Grapple::default(),
+        RigidBody::Kinematic,
+        Collider::cylinder(0.5, 2.0),
+        PbrBundle {
+            mesh: meshes.add(Cylinder::new(0.5, 2.0)),
+            material: materials.add(Color::Srgba(palettes::css::RED)),
+            transform: Transform::from_xyz(-70.0, 1.0, 0.0),
+            ..default()
+        },
+    ));
+}
+
+fn fire_grapple(
+    mouse: Res<ButtonInput<MouseButton>>,
+    camera: Query<&Transform, With<Camera>>,
+    mut player: Query<(Entity, &Position, &mut Grapple)>,
+    spatial_query: SpatialQuery,
+) {
+    let (entity, position, mut grapple) = player.single_mut();
+
+    if mouse.just_released(MouseButton::Right) {
+        grapple.anchor = None;
+    }
+
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let camera = camera.single();
+
+    let Some(hit) = spatial_query.cast_ray(
+        camera.translation.adjust_precision(),
+        camera.forward(),
+        GRAPPLE_RANGE.adjust_precision(),
+        true,
+        SpatialQueryFilter::from_excluded_entities([entity]),
+    ) else {
+        return;
+    };
+
+    let anchor = camera.translation + camera.forward() * hit.time_of_impact.f32();
+
+    grapple.anchor = Some(anchor);
+    grapple.length = anchor.distance(position.0.f32());
+}
+
+fn set_velocity(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    camera_rotation: Res<CameraRotation>,
+    mut query: Query<&mut CharacterController>,
+) {
+    let mut direction = Vec3::ZERO;
+
+    if keyboard_input.pressed(KeyCode::KeyW) {
+        direction.z -= 1.0;
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyA) {
+        direction.x -= 1.0;
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyS) {
+        direction.z += 1.0;
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyD) {
+        direction.x += 1.0;
+    }
+
+    let move_direction = camera_rotation.heading() * direction;
+
+    for mut character_controller in &mut query {
+        character_controller.velocity = move_direction.normalize_or_zero() * PLAYER_SPEED;
+
+        if keyboard_input.just_pressed(KeyCode::Space) {
+            character_controller.jump(JUMP_SPEED);
+        }
+    }
+}
+
+/// Pulls the player back towards the anchor whenever it's further away than the rope is long, so
+/// the rope acts like a stiff spring that can't push.
+fn pull_rope(
+    time: Res<Time>,
+    mut player: Query<(&mut CharacterController, &Position, &mut Grapple)>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    for (mut character_controller, position, mut grapple) in &mut player {
+        let Some(anchor) = grapple.anchor else {
+            continue;
+        };
+
+        grapple.length = (grapple.length - REEL_SPEED * delta_seconds).max(1.0);
+
+        let offset = anchor - position.0.f32();
+        let distance = offset.length();
+        let stretch = distance - grapple.length;
+
+        screen_print!("rope stretch: {:.2}", stretch);
+
+        if stretch <= 0.0 {
+            continue;
+        }
+
+        let direction = offset / distance;
+        let velocity = character_controller.move_velocity
+            + character_controller.external_velocity
+            + *character_controller.up_direction * character_controller.vertical_velocity;
+        let speed_along_rope = velocity.dot(direction);
+
+        let acceleration = direction * (stretch * ROPE_STIFFNESS - speed_along_rope * ROPE_DAMPING);
+
+        character_controller.apply_impulse(acceleration * delta_seconds);
+    }
+}
+
+fn draw_rope(player: Query<(&Transform, &Grapple)>, mut gizmos: Gizmos) {
+    for (transform, grapple) in &player {
+        if let Some(anchor) = grapple.anchor {
+            gizmos.line(transform.translation, anchor, Color::WHITE);
+        }
+    }
+}
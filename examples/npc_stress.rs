@@ -0,0 +1,225 @@
+//! Spawns hundreds of wandering character controllers to track the performance of the solver.
+//!
+//! The number of characters can be passed as the first argument:
+//! `cargo run --release --example npc_stress -- 1000`
+
+use avian3d::{math::AdjustPrecision, prelude::*};
+use bevy::{
+    color::palettes,
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_debug_text_overlay::{screen_print, OverlayPlugin};
+use souls::{
+    character_controller::{
+        CharacterController, CharacterControllerPlugin, CharacterControllerSet,
+    },
+    schedule::{CustomPreUpdate, CustomUpdate, SchedulePlugin},
+};
+
+const DEFAULT_NPC_COUNT: usize = 500;
+const ARENA_SIZE: f32 = 120.0;
+const NPC_SPEED: f32 = 6.0;
+const PILLAR_COUNT: u32 = 40;
+
+#[derive(Resource)]
+struct NpcCount(usize);
+
+/// Walks in a straight line and picks a new heading every so often.
+#[derive(Component)]
+struct Wanderer {
+    heading: f32,
+    seconds_until_turn: f32,
+    rng: u32,
+}
+
+impl Wanderer {
+    fn new(seed: u32) -> Self {
+        let mut wanderer = Self {
+            heading: 0.0,
+            seconds_until_turn: 0.0,
+            rng: seed.max(1),
+        };
+        wanderer.turn();
+        wanderer
+    }
+
+    /// xorshift32, so the example doesn't need a rand dependency.
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng as f32 / u32::MAX as f32
+    }
+
+    fn turn(&mut self) {
+        self.heading = self.next_random() * std::f32::consts::TAU;
+        self.seconds_until_turn = 1.0 + self.next_random() * 4.0;
+    }
+}
+
+fn main() -> AppExit {
+    let npc_count = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_NPC_COUNT);
+
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            FrameTimeDiagnosticsPlugin,
+            PhysicsPlugins::new(CustomUpdate),
+            SchedulePlugin,
+            CharacterControllerPlugin,
+            OverlayPlugin {
+                font_size: 24.0,
+                ..default()
+            },
+        ))
+        .insert_resource(NpcCount(npc_count))
+        .add_systems(Startup, (setup_level, setup_npcs))
+        .add_systems(CustomPreUpdate, wander.before(CharacterControllerSet))
+        .add_systems(Update, print_stats)
+        .run()
+}
+
+fn setup_level(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, ARENA_SIZE * 0.6, ARENA_SIZE * 0.7)
+            .looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_rotation(Quat::from_rotation_x(-1.0)),
+        ..default()
+    });
+
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(
+            ARENA_SIZE.adjust_precision(),
+            0.1,
+            ARENA_SIZE.adjust_precision(),
+        ),
+        PbrBundle {
+            mesh: meshes.add(Cuboid::from_size(Vec3::new(ARENA_SIZE, 0.1, ARENA_SIZE))),
+            transform: Transform::from_xyz(0.0, -0.05, 0.0),
+            material: materials.add(Color::Srgba(palettes::css::DARK_GRAY)),
+            ..default()
+        },
+    ));
+
+    let wall_material = materials.add(Color::Srgba(palettes::css::BLACK));
+
+    for (translation, size) in [
+        (
+            Vec3::new(ARENA_SIZE / 2.0, 2.5, 0.0),
+            Vec3::new(1.0, 5.0, ARENA_SIZE),
+        ),
+        (
+            Vec3::new(-ARENA_SIZE / 2.0, 2.5, 0.0),
+            Vec3::new(1.0, 5.0, ARENA_SIZE),
+        ),
+        (
+            Vec3::new(0.0, 2.5, ARENA_SIZE / 2.0),
+            Vec3::new(ARENA_SIZE, 5.0, 1.0),
+        ),
+        (
+            Vec3::new(0.0, 2.5, -ARENA_SIZE / 2.0),
+            Vec3::new(ARENA_SIZE, 5.0, 1.0),
+        ),
+    ] {
+        let extents = size.adjust_precision();
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(size)),
+                transform: Transform::from_translation(translation),
+                material: wall_material.clone(),
+                ..default()
+            },
+        ));
+    }
+
+    // Scatter pillars on a sunflower spiral so the NPCs have something to slide around.
+    let pillar_mesh = meshes.add(Cuboid::from_size(Vec3::new(3.0, 5.0, 3.0)));
+
+    for i in 0..PILLAR_COUNT {
+        let radius = (i as f32 / PILLAR_COUNT as f32).sqrt() * ARENA_SIZE * 0.45;
+        let angle = i as f32 * 2.4;
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(3.0, 5.0, 3.0),
+            PbrBundle {
+                mesh: pillar_mesh.clone(),
+                transform: Transform::from_xyz(radius * angle.cos(), 2.5, radius * angle.sin())
+                    .with_rotation(Quat::from_rotation_y(angle)),
+                material: wall_material.clone(),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn setup_npcs(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    npc_count: Res<NpcCount>,
+) {
+    let mesh = meshes.add(Cylinder::new(0.5, 2.0));
+    let material = materials.add(Color::Srgba(palettes::css::RED));
+
+    let columns = (npc_count.0 as f32).sqrt().ceil() as usize;
+    let spacing = (ARENA_SIZE - 10.0) / columns as f32;
+
+    for i in 0..npc_count.0 {
+        let x = (i % columns) as f32 * spacing - ARENA_SIZE / 2.0 + 5.0;
+        let z = (i / columns) as f32 * spacing - ARENA_SIZE / 2.0 + 5.0;
+
+        commands.spawn((
+            CharacterController::default(),
+            Wanderer::new(i as u32 + 1),
+            RigidBody::Kinematic,
+            Collider::cylinder(0.5, 2.0),
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(x, 1.0, z),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn wander(time: Res<Time>, mut query: Query<(&mut Wanderer, &mut CharacterController)>) {
+    for (mut wanderer, mut character_controller) in &mut query {
+        wanderer.seconds_until_turn -= time.delta_seconds();
+
+        if wanderer.seconds_until_turn <= 0.0 {
+            wanderer.turn();
+        }
+
+        character_controller.velocity =
+            Vec3::new(wanderer.heading.cos(), 0.0, wanderer.heading.sin()) * NPC_SPEED;
+    }
+}
+
+fn print_stats(npc_count: Res<NpcCount>, diagnostics: Res<DiagnosticsStore>) {
+    screen_print!("NPCs: {}", npc_count.0);
+
+    if let Some(frame_time) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+    {
+        screen_print!("frame time: {:.2} ms", frame_time);
+    }
+}
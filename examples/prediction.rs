@@ -0,0 +1,322 @@
+//! A listen server and a client in one process, connected by an in-memory transport with
+//! simulated latency. The client predicts its character with the controller and reconciles with
+//! the server's state as it arrives, see [`souls::replication`].
+//!
+//! The latency can be passed in milliseconds as the first argument:
+//! `cargo run --example prediction --features replicon -- 200`
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use avian3d::{
+    math::{AdjustPrecision, AsF32},
+    prelude::*,
+};
+use bevy::{color::palettes, prelude::*, scene::ScenePlugin};
+use bevy_debug_text_overlay::{screen_print, OverlayPlugin};
+use bevy_replicon::prelude::*;
+use souls::{
+    character_controller::{
+        CharacterController, CharacterControllerPlugin, CharacterControllerSet,
+    },
+    replication::{
+        CharacterInput, CharacterReplicationPlugin, ControlledBy, PredictedCharacter,
+        ServerCharacterState, ServerInputBuffer,
+    },
+    schedule::{CustomPreUpdate, CustomUpdate, SchedulePlugin},
+    testing::character_collider,
+};
+
+const DEFAULT_LATENCY_MS: u64 = 100;
+const CLIENT_ID: u64 = 1;
+const PLAYER_SPEED: f32 = 10.0;
+
+/// The server's app, updated from the client's.
+struct ServerApp(App);
+
+/// Messages in flight in both directions, each with the time it's delivered.
+#[derive(Resource)]
+struct Transport {
+    latency: Duration,
+    to_client: VecDeque<(Instant, u8, Vec<u8>)>,
+    to_server: VecDeque<(Instant, u8, Vec<u8>)>,
+}
+
+fn main() -> AppExit {
+    let latency = Duration::from_millis(
+        std::env::args()
+            .nth(1)
+            .and_then(|arg| arg.parse().ok())
+            .unwrap_or(DEFAULT_LATENCY_MS),
+    );
+
+    let mut server = App::new();
+
+    server
+        .add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+            bevy::gizmos::GizmoPlugin,
+            PhysicsPlugins::new(CustomUpdate),
+            SchedulePlugin,
+            CharacterControllerPlugin,
+            RepliconPlugins,
+            CharacterReplicationPlugin,
+        ))
+        .init_asset::<Mesh>()
+        .add_systems(Startup, setup_server_level)
+        .add_systems(Update, spawn_client_characters);
+
+    server.finish();
+    server.cleanup();
+
+    server
+        .world_mut()
+        .resource_mut::<RepliconServer>()
+        .set_running(true);
+    server.world_mut().send_event(ServerEvent::ClientConnected {
+        client_id: ClientId::new(CLIENT_ID),
+    });
+
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            PhysicsPlugins::new(CustomUpdate),
+            SchedulePlugin,
+            CharacterControllerPlugin,
+            RepliconPlugins,
+            CharacterReplicationPlugin,
+            OverlayPlugin {
+                font_size: 24.0,
+                ..default()
+            },
+        ))
+        .insert_non_send_resource(ServerApp(server))
+        .insert_resource(Transport {
+            latency,
+            to_client: VecDeque::new(),
+            to_server: VecDeque::new(),
+        })
+        .add_systems(Startup, (connect_client, setup_client_level))
+        .add_systems(First, update_server)
+        .add_systems(
+            Update,
+            (set_up_predicted_characters, draw_server_characters),
+        )
+        .add_systems(CustomPreUpdate, send_input.before(CharacterControllerSet))
+        .run()
+}
+
+fn connect_client(mut client: ResMut<RepliconClient>) {
+    client.set_status(RepliconClientStatus::Connected {
+        client_id: Some(ClientId::new(CLIENT_ID)),
+    });
+}
+
+/// Updates the server and moves messages between it and the client, delivering the ones that
+/// have been in flight for longer than the latency.
+fn update_server(world: &mut World) {
+    let now = Instant::now();
+
+    world.resource_scope(|world, mut transport: Mut<Transport>| {
+        let latency = transport.latency;
+
+        for (channel_id, message) in world.resource_mut::<RepliconClient>().drain_sent() {
+            transport
+                .to_server
+                .push_back((now + latency, channel_id, message.to_vec()));
+        }
+
+        let mut server = world.non_send_resource_mut::<ServerApp>();
+
+        while transport
+            .to_server
+            .front()
+            .is_some_and(|(delivery, ..)| *delivery <= now)
+        {
+            let (_, channel_id, message) = transport.to_server.pop_front().unwrap();
+
+            server
+                .0
+                .world_mut()
+                .resource_mut::<RepliconServer>()
+                .insert_received(ClientId::new(CLIENT_ID), channel_id, message);
+        }
+
+        server.0.update();
+
+        for (_, channel_id, message) in server
+            .0
+            .world_mut()
+            .resource_mut::<RepliconServer>()
+            .drain_sent()
+        {
+            transport
+                .to_client
+                .push_back((now + latency, channel_id, message.to_vec()));
+        }
+
+        let mut client = world.resource_mut::<RepliconClient>();
+
+        while transport
+            .to_client
+            .front()
+            .is_some_and(|(delivery, ..)| *delivery <= now)
+        {
+            let (_, channel_id, message) = transport.to_client.pop_front().unwrap();
+            client.insert_received(channel_id, message);
+        }
+    });
+}
+
+/// The level's blocks as transforms and full extents, spawned on both ends.
+fn level_blocks() -> Vec<(Transform, Vec3)> {
+    let mut blocks = vec![(
+        Transform::from_xyz(0.0, -0.5, 0.0),
+        Vec3::new(40.0, 1.0, 40.0),
+    )];
+
+    for i in 0..5 {
+        let angle = i as f32 * 1.3;
+
+        blocks.push((
+            Transform::from_xyz(8.0 * angle.cos(), 1.5, 8.0 * angle.sin())
+                .with_rotation(Quat::from_rotation_y(angle)),
+            Vec3::new(2.0, 3.0, 2.0),
+        ));
+    }
+
+    blocks
+}
+
+fn setup_server_level(mut commands: Commands) {
+    for (transform, size) in level_blocks() {
+        let extents = size.adjust_precision();
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            TransformBundle::from_transform(transform),
+        ));
+    }
+}
+
+fn setup_client_level(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 25.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_rotation(Quat::from_rotation_x(-1.0)),
+        ..default()
+    });
+
+    let material = materials.add(Color::Srgba(palettes::css::DARK_GRAY));
+
+    for (transform, size) in level_blocks() {
+        let extents = size.adjust_precision();
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(size)),
+                material: material.clone(),
+                transform,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn spawn_client_characters(mut commands: Commands, mut server_events: EventReader<ServerEvent>) {
+    for event in server_events.read() {
+        if let ServerEvent::ClientConnected { client_id } = event {
+            commands.spawn((
+                Replicated,
+                ControlledBy(*client_id),
+                ServerInputBuffer::default(),
+                CharacterController::default(),
+                RigidBody::Kinematic,
+                character_collider(),
+                TransformBundle::from_transform(Transform::from_xyz(0.0, 1.05, 0.0)),
+            ));
+        }
+    }
+}
+
+/// Gives replicated characters everything they need to be simulated on the client.
+fn set_up_predicted_characters(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    characters: Query<(Entity, &ServerCharacterState), Added<ControlledBy>>,
+) {
+    for (entity, server_state) in &characters {
+        commands.entity(entity).insert((
+            PredictedCharacter::default(),
+            CharacterController::default(),
+            RigidBody::Kinematic,
+            character_collider(),
+            PbrBundle {
+                mesh: meshes.add(Cylinder::new(0.5, 2.0)),
+                material: materials.add(Color::Srgba(palettes::css::RED)),
+                transform: Transform::from_translation(server_state.snapshot.translation.f32()),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn send_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: Query<(&mut PredictedCharacter, &mut CharacterController)>,
+    mut inputs: EventWriter<CharacterInput>,
+) {
+    let mut direction = Vec3::ZERO;
+
+    for (key, key_direction) in [
+        (KeyCode::KeyW, Vec3::NEG_Z),
+        (KeyCode::KeyA, Vec3::NEG_X),
+        (KeyCode::KeyS, Vec3::Z),
+        (KeyCode::KeyD, Vec3::X),
+    ] {
+        if keyboard_input.pressed(key) {
+            direction += key_direction;
+        }
+    }
+
+    for (mut predicted, mut character_controller) in &mut characters {
+        inputs.send(predicted.predict(
+            &mut character_controller,
+            direction.normalize_or_zero() * PLAYER_SPEED,
+        ));
+    }
+}
+
+/// Draws where the server last said each character was.
+fn draw_server_characters(
+    characters: Query<(&ServerCharacterState, &PredictedCharacter)>,
+    mut gizmos: Gizmos,
+) {
+    for (server_state, predicted) in &characters {
+        gizmos.sphere(
+            server_state.snapshot.translation.f32(),
+            Quat::IDENTITY,
+            0.5,
+            Color::WHITE,
+        );
+
+        screen_print!("unacknowledged inputs: {}", predicted.pending_inputs.len());
+    }
+}
@@ -0,0 +1,81 @@
+//! Edge-triggered character actions, written from `Update` and handled in the custom schedule.
+//! Events written in frames where no fixed step runs are kept until the next step, and every
+//! action is handled in exactly one step, however many steps a frame runs.
+
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet, ControllerDisabled},
+    schedule::{CustomFirst, CustomPreUpdate},
+};
+
+pub struct CharacterActionPlugin;
+
+impl Plugin for CharacterActionPlugin {
+    fn build(&self, app: &mut App) {
+        // Not `add_event`, which would drop events that no step has read after two frames.
+        app.init_resource::<Events<CharacterAction>>()
+            .init_resource::<StepActions>()
+            .add_systems(CustomFirst, drain_character_actions)
+            .add_systems(
+                CustomPreUpdate,
+                apply_character_actions.before(CharacterControllerSet),
+            );
+    }
+}
+
+/// Send with an `EventWriter<CharacterAction>` from input systems.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct CharacterAction {
+    pub character: Entity,
+    pub kind: ActionKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActionKind {
+    /// See [`CharacterController::jump`].
+    Jump { speed: f32 },
+    /// Adds `velocity` with [`CharacterController::apply_impulse`].
+    Dash { velocity: Vec3 },
+    /// Not handled by the controller, for games to read from [`StepActions`].
+    Interact,
+}
+
+/// The actions handled in the current fixed step. Systems in the custom schedule read actions
+/// from here rather than from `Events<CharacterAction>`.
+#[derive(Resource, Default)]
+pub struct StepActions(pub Vec<CharacterAction>);
+
+impl StepActions {
+    pub fn for_character(&self, character: Entity) -> impl Iterator<Item = &ActionKind> {
+        self.0
+            .iter()
+            .filter(move |action| action.character == character)
+            .map(|action| &action.kind)
+    }
+}
+
+fn drain_character_actions(
+    mut events: ResMut<Events<CharacterAction>>,
+    mut step_actions: ResMut<StepActions>,
+) {
+    step_actions.0.clear();
+    step_actions.0.extend(events.drain());
+}
+
+fn apply_character_actions(
+    step_actions: Res<StepActions>,
+    mut characters: Query<&mut CharacterController, Without<ControllerDisabled>>,
+) {
+    for action in &step_actions.0 {
+        let Ok(mut character_controller) = characters.get_mut(action.character) else {
+            continue;
+        };
+
+        match action.kind {
+            ActionKind::Jump { speed } => character_controller.jump(speed),
+            ActionKind::Dash { velocity } => character_controller.apply_impulse(velocity),
+            ActionKind::Interact => {}
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Classifies what each character is doing, for animation graphs to bind to.
+
+use avian3d::math::AsF32;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet},
+    schedule::CustomPostUpdate,
+};
+
+/// Horizontal speeds at or above this are walking.
+pub const WALK_SPEED: f32 = 0.1;
+/// Horizontal speeds at or above this are running.
+pub const RUN_SPEED: f32 = 8.0;
+/// How long the character stays in [`AnimationState::Land`] if it doesn't start moving.
+pub const LAND_SECONDS: f32 = 0.2;
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            update_animation_states.after(CharacterControllerSet),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Walk,
+    Run,
+    Jump,
+    Fall,
+    Land,
+}
+
+/// Updated after every step from the character's [`CharacterController`].
+#[derive(Component, Default, Debug)]
+pub struct CharacterAnimationState {
+    pub state: AnimationState,
+    /// How long the character has been in `state`, in seconds.
+    pub elapsed: f32,
+    /// How fast the character moved horizontally in the last step, for blending between walking
+    /// and running.
+    pub speed: f32,
+}
+
+fn update_animation_states(
+    mut characters: Query<(&CharacterController, &mut CharacterAnimationState)>,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    if delta_seconds == 0.0 {
+        return;
+    }
+
+    for (character_controller, mut animation) in &mut characters {
+        let speed = character_controller
+            .last_move
+            .displacement()
+            .f32()
+            .with_y(0.0)
+            .length()
+            / delta_seconds;
+
+        let moving_state = if speed >= RUN_SPEED {
+            AnimationState::Run
+        } else if speed >= WALK_SPEED {
+            AnimationState::Walk
+        } else {
+            AnimationState::Idle
+        };
+
+        let state = match animation.state {
+            _ if !character_controller.is_grounded() => {
+                if character_controller.vertical_velocity > 0.0 {
+                    AnimationState::Jump
+                } else {
+                    AnimationState::Fall
+                }
+            }
+            AnimationState::Jump | AnimationState::Fall => AnimationState::Land,
+            AnimationState::Land
+                if animation.elapsed < LAND_SECONDS && moving_state == AnimationState::Idle =>
+            {
+                AnimationState::Land
+            }
+            _ => moving_state,
+        };
+
+        animation.speed = speed;
+
+        if state == animation.state {
+            animation.elapsed += delta_seconds;
+        } else {
+            animation.state = state;
+            animation.elapsed = 0.0;
+        }
+    }
+}
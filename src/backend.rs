@@ -0,0 +1,159 @@
+//! The spatial queries the solver makes, so that it isn't tied to avian's query pipeline.
+//!
+//! [`AvianSpatialQuery`] implements [`SpatialQueryBackend`] and is what the
+//! [`CharacterControllerPlugin`](crate::character_controller::CharacterControllerPlugin) uses.
+//! Another physics engine, like bevy_rapier, can back the solver by implementing the trait and
+//! filling in avian's hit types, which are plain data. Both engines use parry for their shapes,
+//! which [`Collider::shape`] exposes.
+
+use avian3d::{
+    math::{Quaternion, Scalar, Vector},
+    prelude::*,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::character_controller::CharacterController;
+
+pub trait SpatialQueryBackend {
+    /// Sweeps `shape` from `origin` along `direction` and returns the first hit, ignoring shapes
+    /// that `shape` already overlaps at `origin`.
+    fn cast_shape(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+        filter: &SpatialQueryFilter,
+    ) -> Option<ShapeHitData>;
+
+    /// Casts a ray and returns the first hit. A ray starting inside a shape hits it immediately.
+    fn cast_ray(
+        &self,
+        origin: Vector,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+        filter: &SpatialQueryFilter,
+    ) -> Option<RayHitData>;
+
+    /// Returns whether the AABB of any collider that `filter` lets through intersects `aabb`.
+    fn intersects_aabb(&self, aabb: ColliderAabb, filter: &SpatialQueryFilter) -> bool;
+
+    /// Returns every collider that `shape` overlaps at `origin`.
+    fn shape_intersections(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<Entity>;
+
+    /// Returns the contact between `shape` at `origin` and the collider `entity`, with `shape` as
+    /// the first collider, or `None` if they don't touch or `entity` isn't a collider the backend
+    /// can find a contact with.
+    fn contact(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        entity: Entity,
+    ) -> Option<ContactData>;
+}
+
+/// avian's spatial queries. The [`SpatialQueryPipeline`] doesn't keep the shapes of colliders, so
+/// contacts are found from their components instead. Characters are left out of contacts, since
+/// their positions are being solved.
+#[derive(SystemParam)]
+pub struct AvianSpatialQuery<'w, 's> {
+    pub pipeline: Res<'w, SpatialQueryPipeline>,
+    colliders: Query<
+        'w,
+        's,
+        (&'static Collider, &'static Position, &'static Rotation),
+        Without<CharacterController>,
+    >,
+    layers: Query<'w, 's, &'static CollisionLayers>,
+}
+
+impl SpatialQueryBackend for AvianSpatialQuery<'_, '_> {
+    fn cast_shape(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+        filter: &SpatialQueryFilter,
+    ) -> Option<ShapeHitData> {
+        // avian takes the filter by value, so it's cloned per query.
+        self.pipeline.cast_shape(
+            shape,
+            origin,
+            rotation,
+            direction,
+            max_time_of_impact,
+            true,
+            filter.clone(),
+        )
+    }
+
+    fn cast_ray(
+        &self,
+        origin: Vector,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+        filter: &SpatialQueryFilter,
+    ) -> Option<RayHitData> {
+        self.pipeline
+            .cast_ray(origin, direction, max_time_of_impact, true, filter.clone())
+    }
+
+    fn intersects_aabb(&self, aabb: ColliderAabb, filter: &SpatialQueryFilter) -> bool {
+        let mut found = false;
+
+        self.pipeline
+            .aabb_intersections_with_aabb_callback(aabb, |entity| {
+                // Like the casts, colliders without layers are on every layer.
+                let layers = self.layers.get(entity).copied().unwrap_or_default();
+
+                found = !filter.excluded_entities.contains(&entity)
+                    && (layers.memberships & filter.mask) != LayerMask::NONE;
+                !found
+            });
+
+        found
+    }
+
+    fn shape_intersections(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<Entity> {
+        self.pipeline
+            .shape_intersections(shape, origin, rotation, filter.clone())
+    }
+
+    fn contact(
+        &self,
+        shape: &Collider,
+        origin: Vector,
+        rotation: Quaternion,
+        entity: Entity,
+    ) -> Option<ContactData> {
+        let (collider, position, collider_rotation) = self.colliders.get(entity).ok()?;
+
+        contact_query::contact(
+            shape,
+            origin,
+            rotation,
+            collider,
+            *position,
+            *collider_rotation,
+            0.0,
+        )
+        .ok()
+        .flatten()
+    }
+}
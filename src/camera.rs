@@ -1,100 +1,588 @@
 use std::f32::consts::PI;
 
 use bevy::{
-    input::mouse::MouseMotion,
+    input::mouse::{MouseMotion, MouseWheel},
     prelude::*,
     window::{CursorGrabMode, PrimaryWindow},
 };
+#[cfg(feature = "example-tools")]
 use bevy_atmosphere::plugin::AtmosphereCamera;
 
-use crate::character_controller::CharacterController;
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet, Landed},
+    pause::{GamePauseState, PausePlugin},
+    schedule::{CustomPostUpdate, CustomPreUpdate},
+};
 
 const CAMERA_DISTANCE: f32 = 10.0;
+/// How far above the player's center the [`CameraMode::FirstPerson`] camera is.
+const EYE_HEIGHT: f32 = 0.7;
 const SENSITIVITY: f32 = 0.005;
-const PITCH_MIN: f32 = -PI / 2.0;
-const PITCH_MAX: f32 = PI / 2.0;
+/// Toggles the [`CameraMode::FirstPerson`] camera.
+const FIRST_PERSON_KEY: KeyCode = KeyCode::KeyF;
+/// Toggles the [`CameraMode::Free`] camera.
+const FREE_CAMERA_KEY: KeyCode = KeyCode::KeyC;
+/// How much one notch of the scroll wheel multiplies the free camera's speed by.
+const FREE_CAMERA_SPEED_STEP: f32 = 1.2;
+/// Cycles the [`CameraMode::Orbit`] camera, from orbiting the player, to orbiting where the player
+/// was, and back to following the player.
+const ORBIT_CAMERA_KEY: KeyCode = KeyCode::KeyV;
+/// How much one notch of the scroll wheel multiplies the orbit camera's distance by.
+const ORBIT_CAMERA_ZOOM_STEP: f32 = 1.1;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<PausePlugin>() {
+            app.add_plugins(PausePlugin);
+        }
+
         app.init_resource::<CameraRotation>()
+            .init_resource::<MouseLook>()
+            .init_resource::<CameraMode>()
             .add_systems(Startup, setup_camera)
-            .add_systems(Update, (rotate_camera, grab_cursor))
+            .add_systems(
+                Update,
+                (
+                    (toggle_first_person, toggle_free_camera, cycle_orbit_camera)
+                        .run_if(in_state(GamePauseState::Playing)),
+                    accumulate_mouse_look,
+                    (fly_free_camera, orbit_camera).run_if(in_state(GamePauseState::Playing)),
+                )
+                    .chain(),
+            )
+            .add_systems(CustomPreUpdate, rotate_camera.in_set(CameraRotationSet))
+            .add_systems(CustomPostUpdate, bob_head.after(CharacterControllerSet))
             .add_systems(
                 PostUpdate,
-                transform_camera
-                    // .after(CharacterControllerSet) todo: when to schedule camera
-                    .before(TransformSystem::TransformPropagate),
+                (
+                    transform_camera
+                        // .after(CharacterControllerSet) todo: when to schedule camera
+                        .before(TransformSystem::TransformPropagate),
+                    kick_fov,
+                ),
             );
     }
 }
 
+/// Where the [`CameraRotation`] is updated in [`CustomPreUpdate`]. Systems that move characters by
+/// the camera's heading should run after it.
+#[derive(SystemSet, Debug, Hash, Eq, PartialEq, Clone)]
+pub struct CameraRotationSet;
+
+/// Mouse movement since the last fixed step, in pixels. The camera only turns by it in fixed
+/// steps, so the heading characters move along is the same however many frames are rendered in
+/// between, and replays can record it and play it back by overwriting it.
+#[derive(Resource, Default)]
+pub struct MouseLook {
+    pub delta: Vec2,
+}
+
+/// What the camera is attached to.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    /// Orbits the player with the [`CameraRotation`], which moves the player.
+    #[default]
+    Follow,
+    /// Looks from the player's eyes with the [`CameraRotation`], which moves the player.
+    FirstPerson,
+    /// Flies freely with WASD, Space and Shift, while the player keeps simulating without input.
+    /// Scrolling changes its speed. It moves in real time, so it works while the custom schedule
+    /// is stepped manually.
+    Free(FreeCamera),
+    /// Orbits a point by dragging with the middle mouse button, and zooms by scrolling, while the
+    /// player keeps simulating without input. For inspecting collision geometry and gizmos while
+    /// the custom schedule is paused.
+    Orbit(OrbitCamera),
+}
+
+impl CameraMode {
+    /// Whether the player is controlled in this mode, rather than simulating without input.
+    pub fn controls_player(&self) -> bool {
+        matches!(self, CameraMode::Follow | CameraMode::FirstPerson)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FreeCamera {
+    pub translation: Vec3,
+    pub pitch: f32,
+    pub yaw: f32,
+    /// In meters per second.
+    pub speed: f32,
+}
+
+impl FreeCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitCamera {
+    /// The point to orbit, or the player if `None`.
+    pub target: Option<Vec3>,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub distance: f32,
+}
+
+impl OrbitCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+}
+
+/// Widens a camera's field of view with the player's horizontal speed, for a sense of speed when
+/// sprinting or launched. It follows the speed the player actually moved at, so running into a
+/// wall doesn't kick it, and eases towards its target so it doesn't jitter with the speed.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct FovKick {
+    /// The field of view at rest, in radians.
+    pub base_fov: f32,
+    /// The field of view at `max_speed` and above, in radians.
+    pub max_fov: f32,
+    /// The horizontal speed that gets the full kick.
+    pub max_speed: f32,
+    /// How quickly the field of view approaches its target, as the fraction of the difference
+    /// that remains after one second is `e^-ease_rate`.
+    pub ease_rate: f32,
+}
+
+impl Default for FovKick {
+    fn default() -> Self {
+        Self {
+            base_fov: 60.0_f32.to_radians(),
+            max_fov: 75.0_f32.to_radians(),
+            max_speed: 30.0,
+            ease_rate: 6.0,
+        }
+    }
+}
+
+/// Per camera settings, shared by all of its [`CameraMode`]s.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CameraSettings {
+    /// The lowest the camera can look, in radians below the horizon as a negative angle.
+    pub pitch_min: f32,
+    /// The highest the camera can look, in radians above the horizon.
+    pub pitch_max: f32,
+}
+
+impl Default for CameraSettings {
+    /// Stops short of looking straight up or down, where the orbit camera's view would line up
+    /// with its up axis and yaw would spin it in place.
+    fn default() -> Self {
+        Self {
+            pitch_min: -85.0_f32.to_radians(),
+            pitch_max: 85.0_f32.to_radians(),
+        }
+    }
+}
+
+impl CameraSettings {
+    pub fn clamp_pitch(&self, pitch: f32) -> f32 {
+        pitch.clamp(self.pitch_min, self.pitch_max)
+    }
+}
+
+/// Bobs a camera as the player walks and dips it when the player lands, in
+/// [`CameraMode::FirstPerson`]. It's driven by how far the player actually moved on the ground and
+/// by [`Landed`] events, so walking into a wall doesn't bob it.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct HeadBob {
+    /// How far the camera drops in the middle of a stride, in meters. It sways sideways by half of
+    /// this.
+    pub amplitude: f32,
+    /// How far the player walks per bob, in meters.
+    pub stride: f32,
+    /// How far the camera dips per meter per second the player lands with.
+    pub dip_per_speed: f32,
+    /// The deepest the camera dips on landing, in meters.
+    pub max_dip: f32,
+    /// How quickly the bob fades in and out and the dip recovers, as the fraction that remains
+    /// after one second is `e^-recovery_rate`.
+    pub recovery_rate: f32,
+    /// How far into the bob cycle the player is, in radians. Every stride adds `PI`.
+    pub phase: f32,
+    /// How much of the bob is applied, from 0 when standing still or in the air to 1 when walking.
+    pub weight: f32,
+    /// How far the camera is currently dipped, in meters.
+    pub dip: f32,
+}
+
+impl Default for HeadBob {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.05,
+            stride: 1.8,
+            dip_per_speed: 0.02,
+            max_dip: 0.3,
+            recovery_rate: 8.0,
+            phase: 0.0,
+            weight: 0.0,
+            dip: 0.0,
+        }
+    }
+}
+
+impl HeadBob {
+    /// The camera's offset in its heading's space, where +X is right and +Y is up.
+    pub fn offset(&self) -> Vec3 {
+        let sway = self.phase.sin();
+
+        Vec3::new(
+            0.5 * self.amplitude * sway * self.weight,
+            -self.amplitude * sway.abs() * self.weight - self.dip,
+            0.0,
+        )
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct CameraRotation {
     pub pitch: f32,
     pub yaw: f32,
+    /// The rotation that `pitch` and `yaw` are relative to, which follows the player's up
+    /// direction. It's turned the shortest way as the up direction changes, so the view doesn't
+    /// spin when walking around a planet.
+    pub frame: Quat,
+}
+
+impl CameraRotation {
+    /// The rotation of the camera, looking along its local -Z.
+    pub fn rotation(&self) -> Quat {
+        self.frame * Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+
+    /// The rotation of the camera's heading, without pitch.
+    pub fn heading(&self) -> Quat {
+        self.frame * Quat::from_rotation_y(self.yaw)
+    }
 }
 
 fn setup_camera(mut commands: Commands) {
-    commands.spawn((
-        Camera3dBundle {
-            projection: Projection::Perspective(PerspectiveProjection {
-                fov: 60.0_f32.to_radians(),
+    // Only used with the sky of the `example-tools` feature.
+    let _camera = commands
+        .spawn((
+            Camera3dBundle {
+                projection: Projection::Perspective(PerspectiveProjection {
+                    fov: 60.0_f32.to_radians(),
+                    ..default()
+                }),
                 ..default()
-            }),
-            ..default()
-        },
-        AtmosphereCamera::default(),
-    ));
+            },
+            CameraSettings::default(),
+        ))
+        .id();
+
+    #[cfg(feature = "example-tools")]
+    commands.entity(_camera).insert(AtmosphereCamera::default());
 }
 
-fn grab_cursor(
-    mut windows: Query<&mut Window>,
-    mouse: Res<ButtonInput<MouseButton>>,
+fn toggle_first_person(key: Res<ButtonInput<KeyCode>>, mut camera_mode: ResMut<CameraMode>) {
+    if !key.just_pressed(FIRST_PERSON_KEY) {
+        return;
+    }
+
+    *camera_mode = match *camera_mode {
+        CameraMode::FirstPerson => CameraMode::Follow,
+        _ => CameraMode::FirstPerson,
+    };
+}
+
+fn toggle_free_camera(
     key: Res<ButtonInput<KeyCode>>,
+    mut camera_mode: ResMut<CameraMode>,
+    camera: Query<(&Transform, Option<&CameraSettings>), With<Camera>>,
 ) {
-    let mut window = windows.single_mut();
-
-    if mouse.just_pressed(MouseButton::Left) {
-        window.cursor.visible = false;
-        window.cursor.grab_mode = CursorGrabMode::Locked;
+    if !key.just_pressed(FREE_CAMERA_KEY) {
+        return;
     }
 
-    if key.just_pressed(KeyCode::Escape) {
-        window.cursor.visible = true;
-        window.cursor.grab_mode = CursorGrabMode::None;
+    *camera_mode = match *camera_mode {
+        CameraMode::Follow | CameraMode::FirstPerson => {
+            let (transform, settings) = camera.single();
+            let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+
+            CameraMode::Free(FreeCamera {
+                translation: transform.translation,
+                pitch: settings.copied().unwrap_or_default().clamp_pitch(pitch),
+                yaw,
+                speed: 10.0,
+            })
+        }
+        CameraMode::Free(_) | CameraMode::Orbit(_) => CameraMode::Follow,
+    };
+}
+
+fn cycle_orbit_camera(
+    key: Res<ButtonInput<KeyCode>>,
+    mut camera_mode: ResMut<CameraMode>,
+    camera: Query<(&Transform, Option<&CameraSettings>), With<Camera>>,
+    player: Query<&Transform, (With<CharacterController>, Without<Camera>)>,
+) {
+    if !key.just_pressed(ORBIT_CAMERA_KEY) {
+        return;
     }
+
+    *camera_mode = match *camera_mode {
+        CameraMode::Orbit(OrbitCamera {
+            target: None,
+            pitch,
+            yaw,
+            distance,
+        }) => CameraMode::Orbit(OrbitCamera {
+            target: Some(player.single().translation),
+            pitch,
+            yaw,
+            distance,
+        }),
+        CameraMode::Orbit(_) => CameraMode::Follow,
+        CameraMode::Follow | CameraMode::FirstPerson | CameraMode::Free(_) => {
+            let (transform, settings) = camera.single();
+            let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+
+            CameraMode::Orbit(OrbitCamera {
+                target: None,
+                pitch: settings.copied().unwrap_or_default().clamp_pitch(pitch),
+                yaw,
+                distance: CAMERA_DISTANCE,
+            })
+        }
+    };
 }
 
-fn rotate_camera(
+/// Collects mouse movement into the [`MouseLook`] for the player's camera, or turns the free
+/// camera right away.
+fn accumulate_mouse_look(
     window: Query<&Window, With<PrimaryWindow>>,
-    mut camera_rotation: ResMut<CameraRotation>,
+    mut mouse_look: ResMut<MouseLook>,
+    mut camera_mode: ResMut<CameraMode>,
     mut mouse_motion: EventReader<MouseMotion>,
+    camera: Query<&CameraSettings>,
 ) {
     let window = window.single();
+    let settings = camera.get_single().copied().unwrap_or_default();
 
-    if window.cursor.grab_mode != CursorGrabMode::Locked {
+    // Motion from while the window was unfocused or the cursor was free shouldn't turn the camera
+    // once it's grabbed again.
+    if !window.focused || window.cursor.grab_mode != CursorGrabMode::Locked {
+        mouse_motion.clear();
         return;
     }
 
     for event in mouse_motion.read() {
-        camera_rotation.pitch =
-            (camera_rotation.pitch - SENSITIVITY * event.delta.y).clamp(PITCH_MIN, PITCH_MAX);
-        camera_rotation.yaw -= SENSITIVITY * event.delta.x;
+        match &mut *camera_mode {
+            CameraMode::Follow | CameraMode::FirstPerson => mouse_look.delta += event.delta,
+            CameraMode::Orbit(_) => {}
+            CameraMode::Free(free_camera) => {
+                free_camera.pitch =
+                    settings.clamp_pitch(free_camera.pitch - SENSITIVITY * event.delta.y);
+                free_camera.yaw -= SENSITIVITY * event.delta.x;
+            }
+        }
+    }
+}
+
+fn fly_free_camera(
+    key: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut camera_mode: ResMut<CameraMode>,
+    time: Res<Time<Real>>,
+) {
+    let CameraMode::Free(free_camera) = &mut *camera_mode else {
+        mouse_wheel.clear();
+        return;
+    };
+
+    for event in mouse_wheel.read() {
+        if event.y != 0.0 {
+            free_camera.speed *= FREE_CAMERA_SPEED_STEP.powf(event.y.signum());
+        }
+    }
+
+    let mut direction = Vec3::ZERO;
+
+    for (key_code, key_direction) in [
+        (KeyCode::KeyW, Vec3::NEG_Z),
+        (KeyCode::KeyA, Vec3::NEG_X),
+        (KeyCode::KeyS, Vec3::Z),
+        (KeyCode::KeyD, Vec3::X),
+        (KeyCode::Space, Vec3::Y),
+        (KeyCode::ShiftLeft, Vec3::NEG_Y),
+    ] {
+        if key.pressed(key_code) {
+            direction += key_direction;
+        }
+    }
+
+    free_camera.translation += free_camera.rotation()
+        * direction.normalize_or_zero()
+        * free_camera.speed
+        * time.delta_seconds();
+}
+
+/// Turns the camera by the [`MouseLook`] delta, and turns its frame to the player's up direction.
+/// Both happen in fixed steps, so the camera's heading only depends on what happened in them.
+fn rotate_camera(
+    mut camera_rotation: ResMut<CameraRotation>,
+    mut mouse_look: ResMut<MouseLook>,
+    camera: Query<&CameraSettings>,
+    player: Query<&CharacterController, Without<Camera>>,
+) {
+    let delta = std::mem::take(&mut mouse_look.delta);
+    let settings = camera.get_single().copied().unwrap_or_default();
+
+    camera_rotation.pitch = settings.clamp_pitch(camera_rotation.pitch - SENSITIVITY * delta.y);
+    camera_rotation.yaw -= SENSITIVITY * delta.x;
+
+    let Ok(character_controller) = player.get_single() else {
+        return;
+    };
+
+    let frame_up = camera_rotation.frame * Vec3::Y;
+    let up = *character_controller.up_direction;
+
+    if frame_up.dot(up) < 1.0 - 1e-6 {
+        camera_rotation.frame =
+            (Quat::from_rotation_arc(frame_up, up) * camera_rotation.frame).normalize();
+    }
+}
+
+fn orbit_camera(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut camera_mode: ResMut<CameraMode>,
+    camera: Query<&CameraSettings>,
+) {
+    let settings = camera.get_single().copied().unwrap_or_default();
+
+    let CameraMode::Orbit(orbit_camera) = &mut *camera_mode else {
+        mouse_motion.clear();
+        mouse_wheel.clear();
+        return;
+    };
+
+    for event in mouse_motion.read() {
+        if mouse.pressed(MouseButton::Middle) {
+            orbit_camera.pitch =
+                settings.clamp_pitch(orbit_camera.pitch - SENSITIVITY * event.delta.y);
+            orbit_camera.yaw -= SENSITIVITY * event.delta.x;
+        }
+    }
+
+    for event in mouse_wheel.read() {
+        if event.y != 0.0 {
+            orbit_camera.distance *= ORBIT_CAMERA_ZOOM_STEP.powf(-event.y.signum());
+        }
+    }
+}
+
+fn bob_head(
+    mut cameras: Query<&mut HeadBob>,
+    player: Query<(Entity, &CharacterController)>,
+    mut landed: EventReader<Landed>,
+    time: Res<Time>,
+) {
+    let Ok((entity, character_controller)) = player.get_single() else {
+        landed.clear();
+        return;
+    };
+
+    let landing_speed = landed
+        .read()
+        .filter(|landed| landed.character == entity)
+        .map(|landed| landed.speed)
+        .fold(0.0, f32::max);
+
+    let walked = if character_controller.is_grounded() {
+        character_controller
+            .actual_velocity
+            .reject_from_normalized(*character_controller.up_direction)
+            .length()
+            * time.delta_seconds()
+    } else {
+        0.0
+    };
+
+    for mut head_bob in &mut cameras {
+        let recovery = (-head_bob.recovery_rate * time.delta_seconds()).exp();
+        let target_weight = if walked > 0.0 { 1.0 } else { 0.0 };
+
+        head_bob.phase = (head_bob.phase + PI * walked / head_bob.stride) % (2.0 * PI);
+        head_bob.weight = target_weight + (head_bob.weight - target_weight) * recovery;
+        head_bob.dip = (head_bob.dip * recovery)
+            .max(landing_speed * head_bob.dip_per_speed)
+            .min(head_bob.max_dip);
+    }
+}
+
+fn kick_fov(
+    mut cameras: Query<(&FovKick, &mut Projection)>,
+    player: Query<&CharacterController>,
+    time: Res<Time>,
+) {
+    let Ok(character_controller) = player.get_single() else {
+        return;
+    };
+
+    let velocity = character_controller.actual_velocity;
+    let horizontal_speed = velocity
+        .reject_from_normalized(*character_controller.up_direction)
+        .length();
+
+    for (fov_kick, mut projection) in &mut cameras {
+        let Projection::Perspective(perspective) = &mut *projection else {
+            continue;
+        };
+
+        let kick = (horizontal_speed / fov_kick.max_speed).clamp(0.0, 1.0);
+        let target = fov_kick.base_fov + (fov_kick.max_fov - fov_kick.base_fov) * kick;
+
+        perspective.fov +=
+            (target - perspective.fov) * (1.0 - (-fov_kick.ease_rate * time.delta_seconds()).exp());
     }
 }
 
 fn transform_camera(
     camera_rotation: Res<CameraRotation>,
-    mut camera: Query<&mut Transform, With<Camera>>,
+    camera_mode: Res<CameraMode>,
+    mut camera: Query<(&mut Transform, Option<&HeadBob>), With<Camera>>,
     player: Query<&Transform, (With<CharacterController>, Without<Camera>)>,
 ) {
+    let (mut camera_transform, head_bob) = camera.single_mut();
+
     let player_transform = player.single();
-    let mut camera_transform = camera.single_mut();
 
-    let rotation =
-        Quat::from_rotation_y(camera_rotation.yaw) * Quat::from_rotation_x(camera_rotation.pitch);
+    match *camera_mode {
+        CameraMode::Follow => {}
+        CameraMode::FirstPerson => {
+            let offset = head_bob.map_or(Vec3::ZERO, HeadBob::offset);
+
+            camera_transform.rotation = camera_rotation.rotation();
+            camera_transform.translation = player_transform.translation
+                + camera_rotation.heading() * (Vec3::Y * EYE_HEIGHT + offset);
+            return;
+        }
+        CameraMode::Free(free_camera) => {
+            camera_transform.rotation = free_camera.rotation();
+            camera_transform.translation = free_camera.translation;
+            return;
+        }
+        CameraMode::Orbit(orbit_camera) => {
+            let target = orbit_camera.target.unwrap_or(player_transform.translation);
+
+            camera_transform.rotation = orbit_camera.rotation();
+            camera_transform.translation =
+                target + orbit_camera.rotation() * Vec3::Z * orbit_camera.distance;
+            return;
+        }
+    }
+
+    let rotation = camera_rotation.rotation();
     let rotation_matrix = Mat3::from_quat(rotation);
 
     camera_transform.rotation = rotation;
@@ -1,35 +1,941 @@
-use avian3d::prelude::*;
-use bevy::prelude::*;
-use bevy_debug_text_overlay::screen_print;
+use std::time::Duration;
 
-use crate::schedule::{CustomLast, CustomPostUpdate};
+use avian3d::{
+    math::{AdjustPrecision, AsF32, Quaternion, Scalar, Vector},
+    prelude::*,
+};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::Instant};
+use serde::{Deserialize, Serialize};
 
-const MAX_BOUNCES: u8 = 5;
-const SKIN_WIDTH: f32 = 0.005;
+use crate::{
+    backend::{AvianSpatialQuery, SpatialQueryBackend},
+    schedule::{CustomLast, CustomPostUpdate},
+    slide::{clip_velocity, nudge_off_planes},
+};
 
+pub const MAX_BOUNCES: usize = 5;
+pub const SKIN_WIDTH: Scalar = 0.005;
+/// How far below the character to look for ground.
+pub const GROUND_PROBE_DISTANCE: Scalar = 0.05;
+/// How far a grounded character is pulled down to stay on the ground, e.g. when walking down a
+/// slope.
+pub const GROUND_SNAP_DISTANCE: Scalar = 0.3;
+/// Slides shorter than this don't move the character, since directions this short can't be
+/// normalized precisely.
+pub const MIN_SLIDE_DISTANCE: Scalar = 1e-6;
+/// The longest single cast [`move_and_slide`] makes. Casts lose precision over long distances,
+/// enough to end up closer to a thin wall than the skin width and pass through it the next step, so
+/// longer movement is swept in segments of this length. Segments don't use up bounces.
+pub const MAX_SWEEP_DISTANCE: Scalar = 4.0;
+/// How many times a rotated character is pushed out of what it overlaps before giving up.
+pub const MAX_DEPENETRATION_ITERATIONS: usize = 4;
+/// How many times a rotation that can't be depenetrated is halved towards the last one that fit.
+pub const ROTATION_BACK_OFF_ITERATIONS: usize = 8;
+/// The steepest surface, in radians, that counts as ground by default.
+pub const MAX_SLOPE_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+/// The default `external_ground_damping` of a [`CharacterControllerConfig`].
+pub const EXTERNAL_VELOCITY_DAMPING: f32 = 6.0;
+
+/// Everything the controller runs in [`CustomPostUpdate`]. Order systems against [`KccSet`]
+/// instead to run at a specific point in the step.
 #[derive(SystemSet, Debug, Hash, Eq, PartialEq, Clone)]
 pub struct CharacterControllerSet;
 
+/// The stages of a character step in [`CustomPostUpdate`], run in order inside
+/// [`CharacterControllerSet`].
+#[derive(SystemSet, Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum KccSet {
+    /// Sets what characters are trying to do, like their [`CharacterController::velocity`] or
+    /// [`CharacterController::mode`], before they move, in the order of its [`VelocityStage`]s.
+    PrepareVelocity,
+    /// Rotates characters, resolves their rotation against geometry, and moves them.
+    ApplyMove,
+    /// Sends movement events, for systems that react to where characters ended up and what they
+    /// hit, like [`CharacterController::last_move`].
+    PostMove,
+}
+
+/// The modifiers that make up a character's velocity each step, as ordered stages inside
+/// [`KccSet::PrepareVelocity`]. Each feature plugin adds its modifier system to a stage, and games
+/// can add their own to any stage, or order them between stages, instead of competing for a spot
+/// before [`CharacterControllerSet`].
+#[derive(SystemSet, Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum VelocityStage {
+    /// Sets the [`CharacterController::velocity`] the character is trying to move with, like
+    /// [`crate::navigation`] agents.
+    Input,
+    /// Decides the gravity characters fall with, like [`crate::planet`] and
+    /// [`crate::gravity_zones`], and what counters it, like buoyancy in [`crate::water`]. The
+    /// controller resolves each character's [`CharacterController::gravity`] here, after the
+    /// modifiers that set [`CharacterGravity`] and before those that read it.
+    Gravity,
+    /// Velocity from what characters stand on, beyond the ground velocity the controller already
+    /// carries them with, like conveyor belts.
+    Platform,
+    /// One-off impulses with [`CharacterController::apply_impulse`], like knockback.
+    External,
+    /// Continuous pushes, like [`crate::wind`].
+    Wind,
+}
+
 pub struct CharacterControllerPlugin;
 
 impl Plugin for CharacterControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            CustomPostUpdate,
-            move_character_controllers.in_set(CharacterControllerSet),
-        )
-        .add_systems(CustomLast, print_collisions);
+        app.add_event::<Jumped>()
+            .add_event::<AirJump>()
+            .add_event::<Landed>()
+            .add_event::<SolverExhausted>()
+            .add_event::<MovementModeChanged>()
+            .observe(mark_spawned_characters)
+            .configure_sets(
+                CustomPostUpdate,
+                (
+                    (KccSet::PrepareVelocity, KccSet::ApplyMove, KccSet::PostMove)
+                        .chain()
+                        .in_set(CharacterControllerSet),
+                    (
+                        VelocityStage::Input,
+                        VelocityStage::Gravity,
+                        VelocityStage::Platform,
+                        VelocityStage::External,
+                        VelocityStage::Wind,
+                    )
+                        .chain()
+                        .in_set(KccSet::PrepareVelocity),
+                ),
+            )
+            .add_systems(
+                CustomPostUpdate,
+                (
+                    apply_gravity.in_set(VelocityStage::Gravity),
+                    (
+                        scale_colliders,
+                        recover_spawned_characters,
+                        align_to_up_direction,
+                        face_movement,
+                        resolve_rotations,
+                        move_character_controllers,
+                    )
+                        .chain()
+                        .in_set(KccSet::ApplyMove),
+                    (send_movement_events, debug_character_controllers)
+                        .chain()
+                        .in_set(KccSet::PostMove),
+                ),
+            )
+            .add_systems(CustomLast, print_collisions);
     }
 }
 
-#[derive(Component, Default)]
+/// A character's movement state. All of it is kept here between steps, so cloning it (along with
+/// the character's [`Position`] and [`Transform`]) is enough to roll it back, see
+/// [`crate::snapshot`].
+///
+/// The controller moves characters by their [`Position`], which avian keeps in `f64` with the
+/// `f64` feature, and avian copies it to the [`Transform`] in its next step.
+///
+/// The character's [`Collider`] is scaled by its [`GlobalTransform`] before every step, so
+/// scaled characters collide with the hull they're drawn with. Distances like [`SKIN_WIDTH`]
+/// and the config's step height are in world units and aren't scaled.
+#[derive(Component, Clone)]
 pub struct CharacterController {
+    /// The velocity the character is trying to move with, replaced by input every step.
     pub velocity: Vec3, // todo: this is a Vec3 but do we support vertical movement?
+    /// The velocity the character moves with, which approaches [`Self::velocity`] at a rate set by
+    /// the [`SurfaceMaterial`] of the ground.
+    pub move_velocity: Vec3,
+    /// Velocity along [`Self::up_direction`] from gravity. Reset when landing or hitting a ceiling,
+    /// and set to a positive speed to jump.
+    pub vertical_velocity: f32,
+    /// Velocity from outside the character, like knockback, added with
+    /// [`Self::apply_impulse`]. It's kept separate from [`Self::velocity`], which is replaced by
+    /// input every step, and dies down with the friction of the ground while grounded, and with
+    /// the config's `external_air_damping` in the air.
+    pub external_velocity: Vec3,
+    /// The direction the character stands up in. Ground is classified, slope limits are measured,
+    /// ledges are stepped up, and gravity and jumps move the character along it.
+    pub up_direction: Dir3,
+    /// The gravity the character falls with this step: avian's [`Gravity`], or its
+    /// [`CharacterGravity`], times its [`GravityScale`]. Resolved in [`VelocityStage::Gravity`].
+    pub gravity: Vec3,
+    /// The solver output from the most recent step.
+    pub last_move: MoveAndSlideOutput,
+    /// The solver output of the gravity pass from the most recent step.
+    pub last_fall: MoveAndSlideOutput,
+    /// The ground below the character after the most recent step, if any.
+    pub ground: Option<GroundInfo>,
+    /// The surface too steep to stand on that the character is sliding down, if any.
+    pub sliding: Option<Sliding>,
+    /// How the character moves, see [`MovementMode`] for how it changes.
+    pub mode: MovementMode,
+    /// The mode the character changed from in the most recent step, if it changed.
+    pub last_mode_change: Option<MovementMode>,
+    /// How long the character has been off the ground, in seconds of fixed steps, for coyote time.
+    /// Infinite after jumping, and before it first lands.
+    pub time_since_grounded: f32,
+    /// How many of the config's `air_jumps` the character has used since it was last grounded.
+    pub air_jumps_used: u32,
+    /// The jump the character made in the most recent step, if any.
+    pub last_jump: Option<JumpKind>,
+    /// How fast the character was falling if it landed in the most recent step.
+    pub last_landing: Option<f32>,
+    /// How fast the character actually moved in the most recent step, after colliding, stepping
+    /// and snapping to the ground, e.g. for camera effects that shouldn't react to running into a
+    /// wall.
+    pub actual_velocity: Vec3,
+    pub(crate) teleport: Option<Vector>,
+    pub(crate) root_motion: Option<Vec3>,
+    pub(crate) jump: Option<BufferedJump>,
+    /// The mode at the end of the most recent step, to tell when [`Self::mode`] changes.
+    pub(crate) stepped_mode: MovementMode,
+    /// The world rotation the character was last resolved at, see [`resolve_rotations`].
+    pub(crate) solved_rotation: Option<Quat>,
+    /// How far resolving the rotation pushed the character out of geometry in the most recent
+    /// step.
+    pub(crate) depenetration: Scalar,
+    /// How long the most recent step took to solve.
+    pub(crate) step_time: Duration,
+}
+
+/// How a character moves. The controller picks between [`Walking`](Self::Walking),
+/// [`Falling`](Self::Falling) and [`Sliding`](Self::Sliding) itself after every step, from the
+/// ground it finds. The other modes are entered by setting [`CharacterController::mode`], and
+/// last until it's set again. Setting it back to `Falling` hands the character back to the
+/// controller, which picks from the ground again after the next step.
+///
+/// A [`MovementModeChanged`] event is sent after every step that ends in a different mode than
+/// the last one, whether the controller or something else changed it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MovementMode {
+    /// On walkable ground.
+    Walking,
+    /// In the air, pulled by gravity.
+    #[default]
+    Falling,
+    /// On a surface too steep to stand on, sliding down it.
+    Sliding,
+    /// Under water, moving towards the input velocity in any direction with the config's
+    /// `swim_speed` and `swim_drag`, without gravity. Entered and left by the
+    /// [`WaterPlugin`](crate::water::WaterPlugin).
+    Swimming,
+    /// On a ladder or climbable wall, moving at exactly the input velocity without gravity, e.g.
+    /// up to climb and away from the wall to let go.
+    Climbing,
+    /// With a jetpack or in zero gravity, thrusting towards the input velocity in any direction
+    /// by the config's `thrust`, and drifting without input.
+    Flying,
+    /// A game-specific mode, moving by the velocity from the character's [`CustomMovement`].
+    /// Characters without one walk instead.
+    Custom,
+}
+
+impl MovementMode {
+    /// Whether the controller picks this mode itself from the ground after every step.
+    pub fn follows_ground(self) -> bool {
+        matches!(
+            self,
+            MovementMode::Walking | MovementMode::Falling | MovementMode::Sliding
+        )
+    }
+
+    /// Whether input moves the character in all three dimensions, rather than along the ground.
+    pub fn is_3d(self) -> bool {
+        matches!(self, MovementMode::Swimming | MovementMode::Flying)
+    }
+}
+
+/// A game-specific [`MovementMode`], like grinding on rails or gliding. Give the character a
+/// [`CustomMovement`] with it, and set its mode to [`MovementMode::Custom`] to use it.
+pub trait CustomMovementMode: Send + Sync + 'static {
+    /// The velocity to move the character by this step, in any direction. It's called before the
+    /// slide solve, which handles collisions like in the built-in modes, and velocity into
+    /// whatever the character hits is removed before the next call.
+    fn compute_velocity(&mut self, ctx: &CustomMovementContext) -> Vec3;
+}
+
+/// What a [`CustomMovementMode`] computes its velocity from.
+pub struct CustomMovementContext<'a> {
+    /// The velocity the character moved with in its last step, including along up.
+    pub velocity: Vec3,
+    /// [`CharacterController::velocity`], the velocity input asks for.
+    pub input_velocity: Vec3,
+    pub up: Dir3,
+    /// The character's gravity, scaled by the config's `gravity_scale`. Custom modes apply it
+    /// themselves if they want it.
+    pub gravity: Vec3,
+    pub translation: Vector,
+    pub config: &'a CharacterControllerConfig,
+    pub delta_seconds: f32,
+}
+
+/// The [`CustomMovementMode`] a character uses while in [`MovementMode::Custom`].
+#[derive(Component)]
+pub struct CustomMovement(pub Box<dyn CustomMovementMode>);
+
+impl CustomMovement {
+    pub fn new(mode: impl CustomMovementMode) -> Self {
+        Self(Box::new(mode))
+    }
+}
+
+/// Sent when a character's [`MovementMode`] changes.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MovementModeChanged {
+    pub character: Entity,
+    pub from: MovementMode,
+    pub to: MovementMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JumpKind {
+    /// Off the ground, or in coyote time.
+    Ground,
+    /// In the air, using one of the config's `air_jumps`.
+    Air,
+}
+
+/// Sent when a character jumps off the ground, or in coyote time.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Jumped {
+    pub character: Entity,
+}
+
+/// Sent when a character jumps in the air, separately from [`Jumped`] so that games can play
+/// different effects for it.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AirJump {
+    pub character: Entity,
+    /// Which air jump this is since the character was last grounded, starting at 1.
+    pub count: u32,
+}
+
+/// Sent when [`move_and_slide`] runs out of bounces while moving a character, with what it did
+/// about it.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct SolverExhausted {
+    pub character: Entity,
+    pub exhaustion: SlideExhaustion,
+}
+
+/// Sent when a character lands on the ground after being in the air.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct Landed {
+    pub character: Entity,
+    /// How fast the character was falling when it landed.
+    pub speed: f32,
+}
+
+/// A jump waiting for the character to be on the ground, see [`CharacterController::jump`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BufferedJump {
+    /// The vertical velocity the character jumps with.
+    pub speed: f32,
+    /// How long the jump has been waiting, in seconds.
+    pub age: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            velocity: Vec3::ZERO,
+            move_velocity: Vec3::ZERO,
+            vertical_velocity: 0.0,
+            external_velocity: Vec3::ZERO,
+            up_direction: Dir3::Y,
+            gravity: Vec3::ZERO,
+            last_move: MoveAndSlideOutput::default(),
+            last_fall: MoveAndSlideOutput::default(),
+            ground: None,
+            sliding: None,
+            mode: MovementMode::Falling,
+            last_mode_change: None,
+            stepped_mode: MovementMode::Falling,
+            time_since_grounded: f32::INFINITY,
+            air_jumps_used: 0,
+            last_jump: None,
+            last_landing: None,
+            actual_velocity: Vec3::ZERO,
+            teleport: None,
+            root_motion: None,
+            jump: None,
+            solved_rotation: None,
+            depenetration: 0.0,
+            step_time: Duration::ZERO,
+        }
+    }
+}
+
+impl CharacterController {
+    pub fn is_grounded(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    pub fn is_sliding(&self) -> bool {
+        self.sliding.is_some()
+    }
+
+    /// Moves the character to `translation` at the start of the next step, without sweeping,
+    /// and clears its velocity and ground. Its [`MovementMode`] starts over from falling, so it
+    /// doesn't keep climbing, flying or swimming wherever it ends up.
+    pub fn teleport(&mut self, translation: Vector) {
+        self.teleport = Some(translation);
+    }
+
+    /// Moves the character by `displacement` in its next step instead of by its velocity, e.g. to
+    /// follow the root motion of an animation. Gravity and collisions still apply.
+    ///
+    /// Displacement added over several frames accumulates until the next step, so animations can
+    /// add their root motion every frame regardless of how the frame rate lines up with the fixed
+    /// timestep.
+    pub fn add_root_motion(&mut self, displacement: Vec3) {
+        *self.root_motion.get_or_insert(Vec3::ZERO) += displacement;
+    }
+
+    /// Jumps with `speed` along the up direction in the next step the character is on the ground,
+    /// or left it less than the config's `coyote_time` ago, as long as that's within the config's
+    /// `jump_buffer` seconds. In the air, it uses one of the config's `air_jumps` instead of
+    /// waiting, if there are any left.
+    pub fn jump(&mut self, speed: f32) {
+        self.jump = Some(BufferedJump { speed, age: 0.0 });
+    }
+
+    /// Adds `impulse` to the character's velocity, e.g. for explosions, hits, or launch pads. The
+    /// part along the up direction launches it like a jump, and the rest moves it along with its
+    /// input until it dies down on the ground.
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.external_velocity += impulse;
+    }
+}
+
+/// Scales a grounded character's speed by how steep the ground is along its direction of
+/// movement. Without it, speed is the same on slopes as on flat ground.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SlopeSpeed {
+    /// The speed multiplier when moving straight up the steepest walkable slope.
+    pub uphill: f32,
+    /// The speed multiplier when moving straight down the steepest walkable slope.
+    pub downhill: f32,
+}
+
+impl Default for SlopeSpeed {
+    fn default() -> Self {
+        Self {
+            uphill: 0.6,
+            downhill: 1.15,
+        }
+    }
+}
+
+impl SlopeSpeed {
+    /// Returns the speed multiplier for moving along `direction` on ground with `normal`.
+    ///
+    /// The multiplier is interpolated linearly between 1 on flat ground and [`Self::uphill`] or
+    /// [`Self::downhill`] at `max_slope_angle`, the steepest ground the character can stand on, by
+    /// the incline of the path the character takes across the ground, so moving across a slope
+    /// isn't slowed.
+    pub fn multiplier(&self, direction: Vec3, normal: Vec3, max_slope_angle: f32) -> f32 {
+        let along_ground = direction - normal * direction.dot(normal);
+
+        let Some(along_ground) = along_ground.try_normalize() else {
+            return 1.0;
+        };
+
+        let steepness = (along_ground.y / max_slope_angle.sin()).clamp(-1.0, 1.0);
+
+        if steepness >= 0.0 {
+            1.0 + (self.uphill - 1.0) * steepness
+        } else {
+            1.0 + (self.downhill - 1.0) * -steepness
+        }
+    }
+}
+
+/// Turns the character about the Y axis to face the direction of its input velocity, at up to
+/// `turn_rate` radians per second. The character's forward direction is -Z.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FaceMovement {
+    pub turn_rate: f32,
+}
+
+impl Default for FaceMovement {
+    fn default() -> Self {
+        Self {
+            turn_rate: 4.0 * std::f32::consts::PI,
+        }
+    }
+}
+
+/// How a character moves. Characters without one use [`CharacterControllerConfig::default`], which
+/// falls with avian's gravity, changes speed instantly, and can't step up ledges.
+///
+/// Missing fields deserialize to their defaults, so config files only need the ones they change.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterControllerConfig {
+    /// Multiplies avian's [`Gravity`].
+    pub gravity_scale: f32,
+    /// How quickly the character reaches its input velocity on the ground, as the traction of
+    /// [`SurfaceMaterial`]. Slippery materials lower it further.
+    pub acceleration: f32,
+    /// How quickly the character reaches its input velocity in the air, as a traction.
+    pub air_control: f32,
+    /// The tallest ledge a grounded character walks up onto instead of being blocked by.
+    pub max_step_height: Scalar,
+    /// How far past its edge a ledge has to be walkable to count as a step, so that thin lips
+    /// aren't climbed onto.
+    pub min_step_depth: Scalar,
+    /// The steepest surface, in radians, that counts as ground.
+    pub max_slope_angle: f32,
+    /// Scales the part of gravity along surfaces steeper than `max_slope_angle`, which the
+    /// character slides down with.
+    pub slide_acceleration: f32,
+    /// The fraction of the character's speed across a slope that it keeps while sliding down it,
+    /// from 0 for sliding straight down the fall line to 1 for full control.
+    pub slide_control: f32,
+    /// The fastest the character moves horizontally from its input and external velocity
+    /// combined.
+    pub max_horizontal_speed: f32,
+    /// The fastest the character falls.
+    pub terminal_velocity: f32,
+    /// How long, in seconds, a jump requested in the air waits to happen on landing.
+    pub jump_buffer: f32,
+    /// How long, in seconds, the character can still jump after walking off a ledge.
+    pub coyote_time: f32,
+    /// How many times the character can jump in the air before landing again, e.g. 1 for a
+    /// double jump.
+    pub air_jumps: u32,
+    /// Skips ground friction in the step a character jumps, so that landing with a buffered jump
+    /// keeps the speed it had in the air, for bunny hopping.
+    pub preserve_momentum: bool,
+    /// What the solver does with the movement left over when it runs out of bounces.
+    pub solver_fallback: SolverFallback,
+    /// Multiplies the input velocity while [`MovementMode::Swimming`].
+    pub swim_speed: f32,
+    /// How quickly the character reaches its input velocity under water, as a traction. External
+    /// velocity dies down at the same rate.
+    pub swim_drag: f32,
+    /// The most the character accelerates by while [`MovementMode::Flying`].
+    pub thrust: f32,
+    /// How quickly external velocity, like knockback, dies down on the ground, as a traction.
+    /// Slippery materials lower it further. External velocity is across the up direction, so this
+    /// and `external_air_damping` damp the horizontal plane.
+    pub external_ground_damping: f32,
+    /// How quickly external velocity dies down in the air, as a traction. 0 keeps knockback going
+    /// until the character lands.
+    pub external_air_damping: f32,
+    /// How quickly velocity along the up direction dies down, as a traction. Like air resistance,
+    /// it slows jumps and caps falls at the speed where it cancels gravity, for floaty jumps,
+    /// without changing how fast the character runs.
+    pub vertical_damping: f32,
+}
+
+/// What [`move_and_slide`] does when it has bounced [`MAX_BOUNCES`] times and still has movement
+/// left, e.g. when wedged between several surfaces. A [`SolverExhausted`] event is sent either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SolverFallback {
+    /// Stops where the last bounce ended, dropping the rest of the movement.
+    #[default]
+    Halt,
+    /// Stops, then pushes the character back out to the skin width from every surface it hit, in
+    /// case nudging off of them left it too close.
+    Depenetrate,
+    /// Moves the rest of the way along the last surface hit, stopping at whatever it runs into
+    /// without bouncing off of it.
+    SlideAlongLastPlane,
+}
+
+impl Default for CharacterControllerConfig {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 1.0,
+            acceleration: f32::INFINITY,
+            air_control: f32::INFINITY,
+            max_step_height: 0.0,
+            min_step_depth: 0.0,
+            max_slope_angle: MAX_SLOPE_ANGLE,
+            slide_acceleration: 1.0,
+            slide_control: 0.25,
+            max_horizontal_speed: f32::INFINITY,
+            terminal_velocity: f32::INFINITY,
+            jump_buffer: 0.1,
+            coyote_time: 0.1,
+            air_jumps: 0,
+            preserve_momentum: false,
+            solver_fallback: SolverFallback::Halt,
+            swim_speed: 0.6,
+            swim_drag: 3.0,
+            thrust: 20.0,
+            external_ground_damping: EXTERNAL_VELOCITY_DAMPING,
+            external_air_damping: 0.0,
+            vertical_damping: 0.0,
+        }
+    }
+}
+
+impl CharacterControllerConfig {
+    /// Floaty jumps with heavy falls, a double jump, snappy ground movement, and generous air
+    /// control.
+    pub fn platformer() -> Self {
+        Self {
+            gravity_scale: 2.5,
+            acceleration: 12.0,
+            air_control: 6.0,
+            max_step_height: 0.25,
+            min_step_depth: 0.1,
+            max_slope_angle: 50.0_f32.to_radians(),
+            slide_acceleration: 1.5,
+            slide_control: 0.5,
+            max_horizontal_speed: f32::INFINITY,
+            terminal_velocity: 30.0,
+            jump_buffer: 0.15,
+            coyote_time: 0.15,
+            air_jumps: 1,
+            preserve_momentum: false,
+            solver_fallback: SolverFallback::Halt,
+            swim_speed: 0.6,
+            swim_drag: 3.0,
+            thrust: 20.0,
+            external_ground_damping: EXTERNAL_VELOCITY_DAMPING,
+            external_air_damping: 0.0,
+            vertical_damping: 0.0,
+        }
+    }
+
+    /// Near-instant acceleration on the ground, little steering in the air, and stairs that
+    /// don't interrupt aiming.
+    pub fn shooter() -> Self {
+        Self {
+            gravity_scale: 1.5,
+            acceleration: 20.0,
+            air_control: 2.0,
+            max_step_height: 0.4,
+            min_step_depth: 0.15,
+            max_slope_angle: 45.0_f32.to_radians(),
+            slide_acceleration: 1.0,
+            slide_control: 0.2,
+            max_horizontal_speed: f32::INFINITY,
+            terminal_velocity: f32::INFINITY,
+            jump_buffer: 0.1,
+            coyote_time: 0.1,
+            air_jumps: 0,
+            preserve_momentum: false,
+            solver_fallback: SolverFallback::Halt,
+            swim_speed: 0.6,
+            swim_drag: 3.0,
+            thrust: 20.0,
+            external_ground_damping: EXTERNAL_VELOCITY_DAMPING,
+            external_air_damping: 0.0,
+            vertical_damping: 0.0,
+        }
+    }
+
+    /// Values close to the Source engine's defaults: 800 units/s² gravity, `sv_accelerate 10`,
+    /// 18 unit steps, `sv_maxvelocity 3500`, and surfaces walkable up to a normal Y of 0.7.
+    /// Sliding down steeper ones has full control, for surfing, and momentum is preserved for
+    /// bunny hopping.
+    pub fn source_like() -> Self {
+        Self {
+            gravity_scale: 1.55,
+            acceleration: 10.0,
+            air_control: 1.0,
+            max_step_height: 0.34,
+            min_step_depth: 0.0,
+            max_slope_angle: 0.7_f32.acos(),
+            slide_acceleration: 1.0,
+            slide_control: 1.0,
+            max_horizontal_speed: 66.7,
+            terminal_velocity: 66.7,
+            jump_buffer: 0.1,
+            coyote_time: 0.0,
+            air_jumps: 0,
+            preserve_momentum: true,
+            solver_fallback: SolverFallback::Halt,
+            swim_speed: 0.6,
+            swim_drag: 3.0,
+            thrust: 20.0,
+            external_ground_damping: EXTERNAL_VELOCITY_DAMPING,
+            external_air_damping: 0.0,
+            vertical_damping: 0.0,
+        }
+    }
+}
+
+/// Overrides avian's [`Gravity`] for one character, e.g. to pull it towards a planet.
+///
+/// To scale a character's gravity rather than replace it, e.g. 0 for a ghost or 0.3 for a drone,
+/// give it avian's [`GravityScale`], which the controller respects like avian does for dynamic
+/// bodies.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CharacterGravity(pub Vec3);
+
+/// Scales how much time passes for one character each step, e.g. 0.5 to move it in slow motion.
+/// Everything timed in the controller runs on the scaled time, including its velocity, coyote time
+/// and the jump buffer.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CharacterTimeScale(pub f32);
+
+/// Suspends a character's controller without removing it. Disabled characters aren't moved,
+/// rotated, or pulled by gravity, input and wind leave them alone, and they send no movement
+/// events, but their [`CharacterController`] keeps its velocities and timers for when the marker
+/// is removed, e.g. after a cutscene, leaving a vehicle, or getting up from a ragdoll.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ControllerDisabled;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroundInfo {
+    pub entity: Entity,
+    pub point: Vector,
+    pub normal: Vector,
+    /// The gap between the bottom of the character and the ground.
+    pub distance: Scalar,
+    pub material: SurfaceMaterial,
+    /// The ground's [`Traction`] multiplier, or 1 if it has none.
+    #[serde(default = "no_traction_multiplier")]
+    pub traction: f32,
+    /// The velocity of the ground's rigid body, e.g. of a moving platform.
+    pub velocity: Vec3,
+}
+
+fn no_traction_multiplier() -> f32 {
+    1.0
+}
+
+/// A surface too steep to stand on, which the character slides down.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sliding {
+    pub entity: Entity,
+    pub normal: Vector,
+    /// The velocity the character has gained sliding, along the surface.
+    pub velocity: Vec3,
+}
+
+/// How a surface affects characters standing on it. It can be put on a collider or on the rigid
+/// body the collider belongs to, and surfaces without one are [`SurfaceMaterial::NORMAL`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceMaterial {
+    /// How quickly the character's velocity approaches its input velocity, as the fraction of the
+    /// difference that remains after one second is `e^-traction`. Infinite traction changes
+    /// velocity instantly.
+    pub traction: f32,
+    /// Multiplies the input velocity.
+    pub speed: f32,
+}
+
+impl SurfaceMaterial {
+    pub const NORMAL: Self = Self {
+        traction: f32::INFINITY,
+        speed: 1.0,
+    };
+    pub const ICE: Self = Self {
+        traction: 1.5,
+        speed: 1.0,
+    };
+    pub const MUD: Self = Self {
+        traction: 8.0,
+        speed: 0.5,
+    };
+
+    /// Moves `velocity` towards `target` over `delta_seconds`.
+    pub fn approach(&self, velocity: Vec3, target: Vec3, delta_seconds: f32) -> Vec3 {
+        if self.traction.is_infinite() {
+            return target;
+        }
+
+        velocity.lerp(target, 1.0 - (-self.traction * delta_seconds).exp())
+    }
+
+    /// How quickly [`CharacterController::external_velocity`] dies down on this surface, for a
+    /// character whose config damps it at `ground_damping`. Like acceleration, slippery surfaces
+    /// lower it, so knockback slides further on ice.
+    pub fn external_damping(&self, ground_damping: f32) -> f32 {
+        self.traction.min(ground_damping)
+    }
+}
+
+impl Default for SurfaceMaterial {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// Multiplies how quickly characters accelerate on a surface and how quickly friction stops their
+/// external velocity, on top of its [`SurfaceMaterial`], e.g. `Traction(0.2)` for a slippery patch
+/// of an icy surface. Acceleration that's instant stays instant. Like [`SurfaceMaterial`], it can
+/// be put on a collider or on its rigid body.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Traction(pub f32);
+
+/// Applies a [`Traction`] multiplier to a traction, keeping infinite traction infinite, since even
+/// a multiplier of 0 shouldn't turn instant acceleration into NaN.
+fn scale_traction(traction: f32, multiplier: f32) -> f32 {
+    if traction.is_infinite() {
+        traction
+    } else {
+        traction * multiplier
+    }
+}
+
+/// Looks up the [`SurfaceMaterial`] and velocity of colliders.
+#[derive(SystemParam)]
+pub struct SurfaceMaterials<'w, 's> {
+    materials: Query<'w, 's, &'static SurfaceMaterial>,
+    tractions: Query<'w, 's, &'static Traction>,
+    collider_parents: Query<'w, 's, &'static ColliderParent>,
+    velocities: Query<'w, 's, &'static LinearVelocity>,
+}
+
+impl SurfaceMaterials<'_, '_> {
+    /// Returns the material of a collider, falling back to the material of its rigid body.
+    pub fn get(&self, entity: Entity) -> SurfaceMaterial {
+        self.materials
+            .get(entity)
+            .or_else(|_| {
+                self.collider_parents
+                    .get(entity)
+                    .and_then(|parent| self.materials.get(parent.get()))
+            })
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the [`Traction`] multiplier of a collider, falling back to its rigid body's, or 1
+    /// if neither has one. Negative multipliers are clamped to 0.
+    pub fn traction(&self, entity: Entity) -> f32 {
+        self.tractions
+            .get(entity)
+            .or_else(|_| {
+                self.collider_parents
+                    .get(entity)
+                    .and_then(|parent| self.tractions.get(parent.get()))
+            })
+            .map_or(1.0, |traction| traction.0.max(0.0))
+    }
+
+    /// Returns the linear velocity of a collider's rigid body.
+    pub fn velocity(&self, entity: Entity) -> Vec3 {
+        let body = self
+            .collider_parents
+            .get(entity)
+            .map_or(entity, ColliderParent::get);
+
+        self.velocities
+            .get(body)
+            .map_or(Vec3::ZERO, |velocity| velocity.0.f32())
+    }
+}
+
+/// How the character treats a surface it touches, from [`SlideContext::classify`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SurfaceKind {
+    /// Flat enough to stand on.
+    #[default]
+    Walkable,
+    /// Too steep to stand on, so the character slides down it.
+    Steep,
+    /// Within [`WALL_TOLERANCE`] of vertical.
+    Wall,
+    /// Facing down, so the character bumps its head on it.
+    Ceiling,
+}
+
+/// How far the dot product of a surface's normal and the up direction can be from 0 for the
+/// surface to be a [`SurfaceKind::Wall`].
+pub const WALL_TOLERANCE: Scalar = 0.01;
+
+/// Returns how a surface with `normal` is treated by a character with `up` and `max_slope_angle`,
+/// without any overrides.
+pub fn surface_kind(normal: Vector, up: Dir3, max_slope_angle: f32) -> SurfaceKind {
+    let facing_up = normal.dot(vector(up));
+
+    if is_walkable(normal, up, max_slope_angle) {
+        SurfaceKind::Walkable
+    } else if facing_up > WALL_TOLERANCE {
+        SurfaceKind::Steep
+    } else if facing_up >= -WALL_TOLERANCE {
+        SurfaceKind::Wall
+    } else {
+        SurfaceKind::Ceiling
+    }
+}
+
+/// Makes a surface walkable however steep it is, e.g. a ladder modelled as a slope. Ceilings stay
+/// ceilings. Like [`SurfaceMaterial`], it can be put on a collider or on its rigid body.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct AlwaysWalkable;
+
+/// Makes a surface steep however flat it is, so characters slide off of it, e.g. a glass roof.
+/// Takes precedence over [`AlwaysWalkable`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct NeverWalkable;
+
+/// Makes a surface a wall however flat it is, so characters can neither stand on it, slide on it,
+/// nor step up onto it, e.g. an invisible boundary around the level. Ceilings stay ceilings. Takes
+/// precedence over [`AlwaysWalkable`] and [`NeverWalkable`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct NotWalkable;
+
+/// An override of a surface's [`SurfaceKind`], from its [`AlwaysWalkable`], [`NeverWalkable`], or
+/// [`NotWalkable`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkableOverride {
+    Always,
+    Never,
+    /// From [`NotWalkable`].
+    Boundary,
+}
+
+impl WalkableOverride {
+    /// Applies the override to a surface's kind.
+    pub fn apply(self, kind: SurfaceKind) -> SurfaceKind {
+        match (self, kind) {
+            (Self::Always, SurfaceKind::Steep | SurfaceKind::Wall) => SurfaceKind::Walkable,
+            (Self::Never, SurfaceKind::Walkable) => SurfaceKind::Steep,
+            (Self::Boundary, SurfaceKind::Walkable | SurfaceKind::Steep) => SurfaceKind::Wall,
+            (_, kind) => kind,
+        }
+    }
+}
+
+/// Looks up the [`WalkableOverride`]s of colliders for a [`SlideContext`].
+pub trait WalkableOverrides {
+    fn get(&self, entity: Entity) -> Option<WalkableOverride>;
+}
+
+/// Looks up the [`AlwaysWalkable`], [`NeverWalkable`], and [`NotWalkable`] markers of colliders.
+#[derive(SystemParam)]
+pub struct WalkableSurfaces<'w, 's> {
+    markers: Query<'w, 's, (Has<AlwaysWalkable>, Has<NeverWalkable>, Has<NotWalkable>)>,
+    collider_parents: Query<'w, 's, &'static ColliderParent>,
+}
+
+impl WalkableOverrides for WalkableSurfaces<'_, '_> {
+    /// Returns the override of a collider, falling back to the override of its rigid body.
+    fn get(&self, entity: Entity) -> Option<WalkableOverride> {
+        let get = |entity| match self.markers.get(entity) {
+            Ok((_, _, true)) => Some(WalkableOverride::Boundary),
+            Ok((_, true, false)) => Some(WalkableOverride::Never),
+            Ok((true, false, false)) => Some(WalkableOverride::Always),
+            _ => None,
+        };
+
+        get(entity).or_else(|| {
+            self.collider_parents
+                .get(entity)
+                .ok()
+                .and_then(|parent| get(parent.get()))
+        })
+    }
 }
 
+/// Draws the casts of the last step and the planes they slid along, and prints solver stats for
+/// this character.
+#[derive(Component, Default)]
+pub struct DebugCharacterController;
+
 fn print_collisions(
     mut collision_event_reader: EventReader<Collision>,
-    character_controllers: Query<&CharacterController>,
+    character_controllers: Query<&CharacterController, With<DebugCharacterController>>,
 ) {
     let has_collision = collision_event_reader.read().any(|Collision(contacts)| {
         character_controllers.contains(contacts.entity1)
@@ -46,91 +952,1664 @@ fn print_collisions(
     );
 }
 
-fn move_character_controllers(
-    mut query: Query<(Entity, &CharacterController, &Collider, &mut Transform)>,
-    spatial_query: SpatialQuery,
+/// How many subdivisions are used to approximate round colliders under non-uniform scale.
+const SCALED_COLLIDER_DETAIL: u32 = 10;
+
+/// Keeps character colliders scaled like their [`GlobalTransform`]. avian scales colliders in its
+/// own step, which runs after the controller has already cast with the old scale, and other
+/// backends might not scale them at all.
+fn scale_colliders(mut query: Query<(&mut Collider, &GlobalTransform), With<CharacterController>>) {
+    for (mut collider, global_transform) in &mut query {
+        let scale = global_transform
+            .compute_transform()
+            .scale
+            .adjust_precision();
+
+        // Rotations leave noise in the scale of a propagated transform, and rebuilding a
+        // non-uniformly scaled collider is expensive.
+        if !collider.scale().abs_diff_eq(scale, 1e-4) {
+            collider.set_scale(scale, SCALED_COLLIDER_DETAIL);
+        }
+    }
+}
+
+/// The world rotations of characters' parents, to convert between the local [`Transform`] that
+/// characters rotate by and the world space the controller works in. The controller moves
+/// characters by their [`Position`], which is already in world space, and avian writes it back
+/// through the hierarchy.
+#[derive(SystemParam)]
+pub struct ParentRotations<'w, 's> {
+    parents: Query<'w, 's, &'static Parent>,
+    global_transforms: Query<'w, 's, &'static GlobalTransform>,
+}
+
+impl ParentRotations<'_, '_> {
+    /// The world rotation of `entity`'s parent, or identity if it has none.
+    pub fn parent_rotation(&self, entity: Entity) -> Quat {
+        self.parents
+            .get(entity)
+            .and_then(|parent| self.global_transforms.get(parent.get()))
+            .map_or(Quat::IDENTITY, |global_transform| {
+                global_transform.compute_transform().rotation
+            })
+    }
+
+    /// The world rotation of `entity` with the local `transform`.
+    pub fn world_rotation(&self, entity: Entity, transform: &Transform) -> Quat {
+        self.parent_rotation(entity) * transform.rotation
+    }
+
+    /// Sets the local rotation of `entity` so that its world rotation is `rotation`.
+    pub fn set_world_rotation(&self, entity: Entity, transform: &mut Transform, rotation: Quat) {
+        transform.rotation = (self.parent_rotation(entity).inverse() * rotation).normalize();
+    }
+}
+
+/// Tilts characters the shortest way so that their local Y axis points along their
+/// [`CharacterController::up_direction`], keeping their heading.
+fn align_to_up_direction(
+    mut query: Query<(Entity, &CharacterController, &mut Transform), Without<ControllerDisabled>>,
+    parent_rotations: ParentRotations,
+) {
+    for (entity, character_controller, mut transform) in &mut query {
+        let up = character_controller.up_direction;
+        let rotation = parent_rotations.world_rotation(entity, &transform);
+        let current_up = rotation * Vec3::Y;
+
+        if current_up.dot(*up) < 1.0 - 1e-6 {
+            let alignment = Quat::from_rotation_arc(current_up, *up);
+            parent_rotations.set_world_rotation(entity, &mut transform, alignment * rotation);
+        }
+    }
+}
+
+fn face_movement(
+    mut query: Query<
+        (
+            Entity,
+            &CharacterController,
+            &FaceMovement,
+            &mut Transform,
+            Option<&CharacterTimeScale>,
+        ),
+        Without<ControllerDisabled>,
+    >,
+    parent_rotations: ParentRotations,
     time: Res<Time>,
-    mut gizmos: Gizmos,
 ) {
-    for (entity, character_controller, collider, mut transform) in &mut query {
-        let mut direction_result = Dir3::new(character_controller.velocity);
-        let mut distance = character_controller.velocity.length() * time.delta_seconds();
+    for (entity, character_controller, face_movement, mut transform, time_scale) in &mut query {
+        let up = character_controller.up_direction;
+        let direction = character_controller.velocity.reject_from_normalized(*up);
 
-        let Ok(start_direction) = direction_result else {
+        if direction.length_squared() < 1e-6 {
             continue;
+        }
+
+        let rotation = parent_rotations.world_rotation(entity, &transform);
+        let target = Transform::default().looking_to(direction, up).rotation;
+        let angle = rotation.angle_between(target);
+        let max_angle = face_movement.turn_rate
+            * time.delta_seconds()
+            * time_scale.map_or(1.0, |time_scale| time_scale.0);
+
+        let rotation = if angle <= max_angle {
+            target
+        } else {
+            rotation.slerp(target, max_angle / angle)
         };
+        parent_rotations.set_world_rotation(entity, &mut transform, rotation);
+    }
+}
 
-        let mut bounce_count = 0;
-        let mut hit_count = 0;
-        let mut planes = Vec::new();
+/// Rotating a collider that isn't symmetric around up, like a box or a capsule lying down, can
+/// swing it into nearby geometry without any cast seeing it. The solver only casts with the
+/// current rotation, so characters that rotated since their last step are pushed back out of
+/// whatever they now overlap before they move. Where there's no room to push out to, like a box
+/// turning in a narrow corridor, the rotation is backed off to the furthest it can turn instead.
+fn resolve_rotations(
+    mut characters: Query<
+        (
+            Entity,
+            &mut CharacterController,
+            &Collider,
+            &mut Position,
+            &mut Transform,
+        ),
+        Without<ControllerDisabled>,
+    >,
+    parent_rotations: ParentRotations,
+    spatial_query: AvianSpatialQuery,
+) {
+    for (entity, mut character_controller, collider, mut position, mut transform) in &mut characters
+    {
+        character_controller.depenetration = 0.0;
 
-        for _ in 0..MAX_BOUNCES {
-            bounce_count += 1;
+        let previous = character_controller.solved_rotation;
+        let rotation = parent_rotations.world_rotation(entity, &transform);
 
-            if let Ok(direction) = direction_result {
-                gizmos.ray(
-                    transform.translation,
-                    direction.as_vec3(),
-                    Color::linear_rgb(1.0, 0.0, 0.0),
-                );
+        if previous == Some(rotation) {
+            continue;
+        }
+
+        let overlap = Overlap {
+            spatial_query: &spatial_query,
+            collider,
+            filter: SpatialQueryFilter::from_excluded_entities([entity]),
+        };
+
+        let rotation = if let Some(translation) = overlap.depenetrate(position.0, rotation) {
+            character_controller.depenetration = translation.distance(position.0);
+            position.0 = translation;
+            rotation
+        } else if let Some(previous) = previous {
+            let rotation = overlap.back_off(position.0, previous, rotation);
+            parent_rotations.set_world_rotation(entity, &mut transform, rotation);
+            rotation
+        } else {
+            rotation
+        };
+
+        character_controller.solved_rotation = Some(rotation);
+    }
+}
+
+/// Marks characters that haven't taken a step since their [`CharacterController`] was added, so
+/// that their first step checks they didn't spawn inside geometry.
+#[derive(Component)]
+struct SpawnCheck;
+
+fn mark_spawned_characters(trigger: Trigger<OnAdd, CharacterController>, mut commands: Commands) {
+    commands.entity(trigger.entity()).insert(SpawnCheck);
+}
 
-                if let Some(hit) = spatial_query.cast_shape(
-                    collider,
-                    transform.translation,
-                    transform.rotation,
-                    direction,
-                    distance + SKIN_WIDTH,
-                    true,
-                    SpatialQueryFilter::from_excluded_entities([entity]),
-                ) {
-                    hit_count += 1;
+/// Pushes characters that spawned inside geometry out of it, with a warning, since it's usually a
+/// spawn point placed too low, and the character would otherwise behave erratically from its first
+/// step.
+fn recover_spawned_characters(
+    mut commands: Commands,
+    mut characters: Query<
+        (Entity, &Collider, &mut Position, &Transform),
+        (
+            With<CharacterController>,
+            With<SpawnCheck>,
+            Without<ControllerDisabled>,
+        ),
+    >,
+    parent_rotations: ParentRotations,
+    spatial_query: AvianSpatialQuery,
+) {
+    for (entity, collider, mut position, transform) in &mut characters {
+        commands.entity(entity).remove::<SpawnCheck>();
+
+        let overlap = Overlap {
+            spatial_query: &spatial_query,
+            collider,
+            filter: SpatialQueryFilter::from_excluded_entities([entity]),
+        };
+        let rotation = parent_rotations.world_rotation(entity, transform);
+
+        if !overlap.overlaps(position.0, rotation) {
+            continue;
+        }
+
+        match overlap.depenetrate(position.0, rotation) {
+            Some(translation) => {
+                warn!(
+                    "{entity} spawned inside geometry at {}, pushed it out by {}",
+                    position.0,
+                    translation.distance(position.0)
+                );
+                position.0 = translation;
+            }
+            None => warn!(
+                "{entity} spawned inside geometry at {} and couldn't be pushed out of it",
+                position.0
+            ),
+        }
+    }
+}
 
-                    screen_print!("normal: {}", hit.normal1);
+/// Overlap queries for a character's collider against everything but itself.
+struct Overlap<'a> {
+    spatial_query: &'a dyn SpatialQueryBackend,
+    collider: &'a Collider,
+    filter: SpatialQueryFilter,
+}
 
-                    let hit_point = *transform * hit.point2;
+impl Overlap<'_> {
+    fn overlaps(&self, translation: Vector, rotation: Quat) -> bool {
+        !self
+            .spatial_query
+            .shape_intersections(
+                self.collider,
+                translation,
+                rotation.adjust_precision(),
+                &self.filter,
+            )
+            .is_empty()
+    }
 
-                    gizmos.sphere(hit_point, Quat::IDENTITY, 0.1, Color::WHITE);
+    /// Pushes the collider out of everything it overlaps at `rotation`, to [`SKIN_WIDTH`] away.
+    /// Returns `None` if it still overlaps after [`MAX_DEPENETRATION_ITERATIONS`].
+    fn depenetrate(&self, translation: Vector, rotation: Quat) -> Option<Vector> {
+        let rotation = rotation.adjust_precision();
+        let mut translation = translation;
 
-                    if hit.time_of_impact >= distance {
-                        transform.translation +=
-                            direction * (hit.time_of_impact - SKIN_WIDTH).max(0.0);
-                        break;
-                    }
+        for _ in 0..MAX_DEPENETRATION_ITERATIONS {
+            let others = self.spatial_query.shape_intersections(
+                self.collider,
+                translation,
+                rotation,
+                &self.filter,
+            );
 
-                    if hit.time_of_impact >= SKIN_WIDTH {
-                        transform.translation += direction * (hit.time_of_impact - SKIN_WIDTH)
-                    }
+            if others.is_empty() {
+                return Some(translation);
+            }
 
-                    let extra_distance = distance - (hit.time_of_impact - SKIN_WIDTH).max(0.0);
-                    let extra_velocity = direction * extra_distance;
+            for other in others {
+                let Some(contact) =
+                    self.spatial_query
+                        .contact(self.collider, translation, rotation, other)
+                else {
+                    continue;
+                };
 
-                    let mut projected_velocity =
-                        extra_velocity - (extra_velocity.dot(hit.normal1) * hit.normal1);
+                let normal = contact.global_normal1(&Rotation(rotation));
+                translation -= normal * (contact.penetration + SKIN_WIDTH);
+            }
+        }
 
-                    if projected_velocity.dot(*start_direction) <= 0.0 {
-                        break;
-                    }
+        (!self.overlaps(translation, rotation.f32())).then_some(translation)
+    }
 
-                    for plane in &planes {
-                        if hit.normal1.dot(*plane) > 0.99 {
-                            projected_velocity += hit.normal1 * 0.01;
-                        }
-                    }
+    /// The rotation furthest from `from` towards `to` that doesn't overlap anything, or `from` if
+    /// it already overlaps.
+    fn back_off(&self, translation: Vector, from: Quat, to: Quat) -> Quat {
+        let mut free = 0.0;
+        let mut blocked = 1.0;
 
-                    planes.push(hit.normal1);
+        for _ in 0..ROTATION_BACK_OFF_ITERATIONS {
+            let fraction = (free + blocked) / 2.0;
 
-                    direction_result = Dir3::new(projected_velocity);
-                    distance = projected_velocity.length();
-                } else {
-                    transform.translation += direction * distance;
-                    break;
-                }
+            if self.overlaps(translation, from.slerp(to, fraction)) {
+                blocked = fraction;
             } else {
-                break;
+                free = fraction;
+            }
+        }
+
+        from.slerp(to, free)
+    }
+}
+
+/// The gravity modifier: resolves [`CharacterController::gravity`] from avian's [`Gravity`], the
+/// character's [`CharacterGravity`], and its [`GravityScale`].
+pub(crate) fn apply_gravity(
+    mut query: Query<
+        (
+            &mut CharacterController,
+            Option<&CharacterGravity>,
+            Option<&GravityScale>,
+        ),
+        Without<ControllerDisabled>,
+    >,
+    gravity: Res<Gravity>,
+) {
+    for (mut character_controller, character_gravity, gravity_scale) in &mut query {
+        character_controller.gravity = character_gravity
+            .map_or(gravity.0.f32(), |gravity| gravity.0)
+            * gravity_scale.map_or(1.0, |gravity_scale| gravity_scale.0.f32());
+    }
+}
+
+fn move_character_controllers(
+    mut query: Query<
+        (
+            Entity,
+            &mut CharacterController,
+            &Collider,
+            &mut Position,
+            &Transform,
+            Option<&CharacterControllerConfig>,
+            Option<&SlopeSpeed>,
+            Option<&CharacterTimeScale>,
+            Option<&mut CustomMovement>,
+        ),
+        Without<ControllerDisabled>,
+    >,
+    surface_materials: SurfaceMaterials,
+    walkable_surfaces: WalkableSurfaces,
+    parent_rotations: ParentRotations,
+    spatial_query: AvianSpatialQuery,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+    let default_config = CharacterControllerConfig::default();
+
+    query.par_iter_mut().for_each(
+        |(
+            entity,
+            mut character_controller,
+            collider,
+            mut position,
+            transform,
+            config,
+            slope_speed,
+            time_scale,
+            custom_movement,
+        )| {
+            let config = config.unwrap_or(&default_config);
+            let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+            let context = SlideContext {
+                spatial_query: &spatial_query,
+                collider,
+                rotation: parent_rotations
+                    .world_rotation(entity, transform)
+                    .adjust_precision(),
+                filter: &filter,
+                up: character_controller.up_direction,
+                max_slope_angle: config.max_slope_angle,
+                fallback: config.solver_fallback,
+                walkable_overrides: Some(&walkable_surfaces),
+            };
+
+            let gravity = character_controller.gravity;
+            let started = Instant::now();
+
+            step_character(
+                &mut character_controller,
+                &mut position.0,
+                &context,
+                config,
+                slope_speed,
+                custom_movement.map(|custom_movement| custom_movement.into_inner()),
+                &surface_materials,
+                gravity,
+                delta_seconds * time_scale.map_or(1.0, |time_scale| time_scale.0),
+            );
+
+            character_controller.step_time = started.elapsed();
+        },
+    );
+}
+
+/// Runs one step of a character's movement from `translation`. This is what the
+/// [`CharacterControllerPlugin`] runs for every character, and can be used to resimulate a
+/// character on its own.
+///
+/// Non-finite velocities, gravity, and time are treated as zero, with a warning the first time,
+/// and a step that would end somewhere non-finite leaves the character where it was.
+pub fn step_character(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    config: &CharacterControllerConfig,
+    slope_speed: Option<&SlopeSpeed>,
+    custom_movement: Option<&mut CustomMovement>,
+    surface_materials: &SurfaceMaterials,
+    gravity: Vec3,
+    delta_seconds: f32,
+) {
+    if !translation.is_finite() {
+        bevy::log::warn_once!("skipped stepping a character at a non-finite translation");
+        return;
+    }
+
+    sanitize_velocities(character_controller);
+    let gravity = if gravity.is_finite() {
+        gravity
+    } else {
+        bevy::log::warn_once!("treated non-finite gravity as zero");
+        Vec3::ZERO
+    };
+    let delta_seconds = if delta_seconds.is_finite() && delta_seconds >= 0.0 {
+        delta_seconds
+    } else {
+        bevy::log::warn_once!("treated a delta time of {delta_seconds} as zero");
+        0.0
+    };
+
+    if let Some(teleport) = character_controller.teleport.take() {
+        *translation = teleport;
+        character_controller.velocity = Vec3::ZERO;
+        character_controller.move_velocity = Vec3::ZERO;
+        character_controller.vertical_velocity = 0.0;
+        character_controller.external_velocity = Vec3::ZERO;
+        character_controller.ground = None;
+        character_controller.sliding = None;
+        character_controller.mode = MovementMode::Falling;
+        character_controller.stepped_mode = MovementMode::Falling;
+        character_controller.time_since_grounded = f32::INFINITY;
+        character_controller.air_jumps_used = 0;
+        character_controller.root_motion = None;
+        character_controller.jump = None;
+    }
+
+    let previous_mode = character_controller.stepped_mode;
+
+    if character_controller.mode == MovementMode::Custom && custom_movement.is_none() {
+        bevy::log::warn_once!(
+            "a character in MovementMode::Custom without a CustomMovement walked"
+        );
+        character_controller.mode = MovementMode::Falling;
+    }
+
+    match character_controller.mode {
+        MovementMode::Swimming => swim(
+            character_controller,
+            translation,
+            context,
+            config,
+            delta_seconds,
+        ),
+        MovementMode::Flying => fly(
+            character_controller,
+            translation,
+            context,
+            config,
+            delta_seconds,
+        ),
+        MovementMode::Climbing => climb(character_controller, translation, context, delta_seconds),
+        MovementMode::Custom => {
+            if let Some(custom_movement) = custom_movement {
+                let ctx = CustomMovementContext {
+                    velocity: character_controller.move_velocity
+                        + *context.up * character_controller.vertical_velocity,
+                    input_velocity: character_controller.velocity,
+                    up: context.up,
+                    gravity: gravity * config.gravity_scale,
+                    translation: *translation,
+                    config,
+                    delta_seconds,
+                };
+                let velocity = custom_movement.0.compute_velocity(&ctx);
+
+                move_freely(
+                    character_controller,
+                    translation,
+                    context,
+                    delta_seconds,
+                    |_| velocity,
+                );
             }
         }
+        MovementMode::Walking | MovementMode::Falling | MovementMode::Sliding => walk(
+            character_controller,
+            translation,
+            context,
+            config,
+            slope_speed,
+            surface_materials,
+            gravity,
+            delta_seconds,
+        ),
+    }
+
+    character_controller.last_mode_change =
+        (character_controller.mode != previous_mode).then_some(previous_mode);
+    character_controller.stepped_mode = character_controller.mode;
+}
+
+/// Moves a character along the ground, or through the air with gravity, and picks its
+/// [`MovementMode`] from the ground it ends up on.
+fn walk(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    config: &CharacterControllerConfig,
+    slope_speed: Option<&SlopeSpeed>,
+    surface_materials: &SurfaceMaterials,
+    gravity: Vec3,
+    delta_seconds: f32,
+) {
+    let initial_translation = *translation;
+    let was_grounded = character_controller.is_grounded();
 
-        screen_print!("bounces: {}", bounce_count);
-        screen_print!("hit count: {}", hit_count);
+    if was_grounded {
+        character_controller.time_since_grounded = 0.0;
+        character_controller.air_jumps_used = 0;
+    } else {
+        character_controller.time_since_grounded += delta_seconds;
     }
+
+    if was_grounded && character_controller.vertical_velocity <= 0.0 {
+        character_controller.vertical_velocity = 0.0;
+    }
+
+    // External velocity along up is handled like a jump, so landing and ceilings stop it.
+    let external_up = character_controller.external_velocity.dot(*context.up);
+    character_controller.vertical_velocity += external_up;
+    character_controller.external_velocity -= *context.up * external_up;
+
+    character_controller.last_jump = match character_controller.jump.take() {
+        Some(jump) if character_controller.time_since_grounded <= config.coyote_time => {
+            character_controller.vertical_velocity = jump.speed;
+            character_controller.time_since_grounded = f32::INFINITY;
+            Some(JumpKind::Ground)
+        }
+        Some(jump) if character_controller.air_jumps_used < config.air_jumps => {
+            character_controller.vertical_velocity = jump.speed;
+            character_controller.air_jumps_used += 1;
+            Some(JumpKind::Air)
+        }
+        Some(jump) => {
+            let age = jump.age + delta_seconds;
+
+            if age <= config.jump_buffer {
+                character_controller.jump = Some(BufferedJump { age, ..jump });
+            }
+
+            None
+        }
+        None => None,
+    };
+
+    let up = vector(context.up);
+    let gravity = gravity * config.gravity_scale;
+    let slide_velocity;
+
+    match character_controller.sliding {
+        // Sliding replaces falling, and only accelerates with the part of gravity along the
+        // surface. Falling speed carries over into the slide.
+        Some(sliding) if character_controller.vertical_velocity <= 0.0 => {
+            let normal = sliding.normal.f32();
+            let velocity = sliding.velocity + *context.up * character_controller.vertical_velocity;
+            let along_surface = gravity - normal * gravity.dot(normal);
+
+            slide_velocity = velocity - normal * velocity.dot(normal)
+                + along_surface * config.slide_acceleration * delta_seconds;
+            character_controller.vertical_velocity = 0.0;
+        }
+        sliding => {
+            slide_velocity = sliding.map_or(Vec3::ZERO, |sliding| sliding.velocity);
+            character_controller.vertical_velocity += gravity.dot(*context.up) * delta_seconds;
+            character_controller.vertical_velocity *=
+                (-config.vertical_damping * delta_seconds).exp();
+        }
+    }
+
+    character_controller.vertical_velocity = character_controller
+        .vertical_velocity
+        .max(-config.terminal_velocity);
+
+    let ground = character_controller.ground;
+
+    // How far the ground moved along the up direction since it was probed, e.g. an elevator.
+    let ground_travel = ground.map_or(0.0, |ground| {
+        (ground.velocity.dot(*context.up) * delta_seconds).adjust_precision()
+    });
+
+    // Rising ground has moved into the character, where casts no longer see it, so lift the
+    // character back on top of it.
+    if ground_travel > 0.0 {
+        *translation =
+            move_and_slide(context, *translation, up * ground_travel, None, false).translation;
+    }
+
+    // Ground the character is leaving without friction, when preserving momentum.
+    let traction_ground =
+        ground.filter(|_| character_controller.last_jump.is_none() || !config.preserve_momentum);
+
+    let material = match traction_ground {
+        Some(ground) => SurfaceMaterial {
+            traction: scale_traction(
+                ground.material.traction.min(config.acceleration),
+                ground.traction,
+            ),
+            ..ground.material
+        },
+        None => SurfaceMaterial {
+            traction: config.air_control,
+            ..SurfaceMaterial::NORMAL
+        },
+    };
+
+    character_controller.move_velocity = material.approach(
+        character_controller.move_velocity,
+        character_controller.velocity * material.speed,
+        delta_seconds,
+    );
+
+    // Only the external velocity is damped, so knockback bleeds off without making input any
+    // less responsive.
+    let external_damping = match traction_ground {
+        Some(ground) => scale_traction(
+            ground
+                .material
+                .external_damping(config.external_ground_damping),
+            ground.traction,
+        ),
+        None => config.external_air_damping,
+    };
+    character_controller.external_velocity *= (-external_damping * delta_seconds).exp();
+
+    let mut velocity = character_controller.move_velocity;
+
+    if let (Some(slope_speed), Some(ground)) = (slope_speed, ground) {
+        // The multiplier measures incline from +Y.
+        let to_y_up = Quat::from_rotation_arc(*context.up, Vec3::Y);
+        velocity *= slope_speed.multiplier(
+            to_y_up * velocity,
+            to_y_up * ground.normal.f32(),
+            context.max_slope_angle,
+        );
+    }
+
+    // On a steep slope, only movement along the fall line is at full speed, so the character can
+    // steer its slide but not run across the slope.
+    if let Some(sliding) = character_controller.sliding {
+        let downhill = context.horizontal(sliding.normal).f32().normalize_or_zero();
+        let along_fall_line = downhill * velocity.dot(downhill);
+
+        velocity = along_fall_line + (velocity - along_fall_line) * config.slide_control;
+    }
+
+    // Root motion is already a displacement, so it isn't scaled by the timestep, or capped.
+    let displacement = match character_controller.root_motion.take() {
+        Some(root_motion) => root_motion + character_controller.external_velocity * delta_seconds,
+        None => {
+            let velocity = velocity + character_controller.external_velocity;
+            let vertical = velocity.project_onto_normalized(*context.up);
+            let horizontal = (velocity - vertical).clamp_length_max(config.max_horizontal_speed);
+
+            (horizontal + vertical) * delta_seconds
+        }
+    };
+
+    let displacement = displacement.adjust_precision();
+    let start = *translation;
+    let mut output = move_and_slide(context, *translation, displacement, ground.as_ref(), false);
+
+    // Blocked while walking, so try stepping up onto whatever is in the way.
+    let mut stepped = false;
+    let steppable = output
+        .bounces()
+        .iter()
+        .filter_map(|bounce| bounce.hit)
+        .all(|hit| context.is_steppable(hit.entity));
+
+    if was_grounded && steppable && config.max_step_height > 0.0 {
+        let moved = context
+            .horizontal(output.translation - *translation)
+            .length();
+
+        if moved < context.horizontal(displacement).length() - SKIN_WIDTH {
+            if let Some(step) = step_up(context, *translation, displacement, config) {
+                if context.horizontal(step - *translation).length() > moved + SKIN_WIDTH {
+                    output.translation = step;
+                    stepped = true;
+                }
+            }
+        }
+    }
+
+    // Stop moving into walls, so that momentum on slippery ground doesn't keep pushing into them.
+    for hit in output.bounces().iter().filter_map(|bounce| bounce.hit) {
+        if !stepped && hit.kind != SurfaceKind::Walkable {
+            let normal = context.horizontal(hit.normal).f32().normalize_or_zero();
+            let speed_into_wall = character_controller.move_velocity.dot(normal);
+
+            if speed_into_wall < 0.0 {
+                character_controller.move_velocity -= normal * speed_into_wall;
+            }
+
+            let external_speed_into_wall = character_controller.external_velocity.dot(normal);
+
+            if external_speed_into_wall < 0.0 {
+                character_controller.external_velocity -= normal * external_speed_into_wall;
+            }
+        }
+    }
+
+    let slide = move_and_slide(
+        context,
+        output.translation,
+        (slide_velocity * delta_seconds).adjust_precision(),
+        None,
+        false,
+    );
+
+    let falling_speed = (-character_controller.vertical_velocity).max(0.0);
+    let fall = move_and_slide(
+        context,
+        slide.translation,
+        up * (character_controller.vertical_velocity * delta_seconds).adjust_precision(),
+        ground.as_ref(),
+        true,
+    );
+
+    // Stop falling on landing, and stop rising on hitting a ceiling.
+    if let Some(hit) = fall.bounces().iter().find_map(|bounce| bounce.hit) {
+        if hit.kind == SurfaceKind::Walkable || hit.normal.dot(up) < 0.0 {
+            character_controller.vertical_velocity = 0.0;
+        }
+    }
+
+    *translation = fall.translation;
+
+    let mut ground = probe_ground(context, *translation, GROUND_PROBE_DISTANCE);
+
+    // Keep grounded characters on the ground when it drops away beneath them, including when it's
+    // sinking, like a descending elevator.
+    if ground.is_none() && was_grounded && character_controller.vertical_velocity <= 0.0 {
+        let snap_distance = GROUND_SNAP_DISTANCE + (-ground_travel).max(0.0);
+
+        if let Some(snap) = probe_ground(context, *translation, snap_distance) {
+            *translation -= up * snap.distance;
+            ground = Some(GroundInfo {
+                point: snap.point - up * snap.distance,
+                distance: 0.0,
+                ..snap
+            });
+        }
+    }
+
+    // Past the crest of a ramp or over a bump, the ground curves away faster than the straight
+    // down snap reaches, so follow it by probing into the previous ground along its normal. Walkable
+    // ground can fall away by at most the steepest walkable slope over the distance moved.
+    let into_previous_ground = character_controller
+        .ground
+        .filter(|_| ground.is_none() && character_controller.vertical_velocity <= 0.0)
+        .and_then(|previous| Dir3::new(-previous.normal.f32()).ok());
+
+    if let Some(direction) = into_previous_ground {
+        let direction_vector = vector(direction);
+        let moved = context.horizontal(*translation - start).length();
+        let distance =
+            GROUND_SNAP_DISTANCE + moved * context.max_slope_angle.tan().adjust_precision();
+
+        if let Some(snap) = probe_ground_along(context, *translation, direction, distance) {
+            *translation += direction_vector * snap.distance;
+            ground = Some(GroundInfo {
+                point: snap.point + direction_vector * snap.distance,
+                distance: 0.0,
+                ..snap
+            });
+        }
+    }
+
+    if let Some(ground) = &mut ground {
+        ground.material = surface_materials.get(ground.entity);
+        ground.traction = surface_materials.traction(ground.entity);
+        ground.velocity = surface_materials.velocity(ground.entity);
+    }
+
+    let sliding = match ground {
+        Some(_) => None,
+        None => probe_slide(context, *translation, GROUND_PROBE_DISTANCE),
+    };
+
+    // Keep the slide's momentum when it ends, as movement on the ground, or in the air.
+    if sliding.is_none() {
+        let up_speed = slide_velocity.dot(*context.up);
+
+        character_controller.move_velocity += slide_velocity - *context.up * up_speed;
+
+        if ground.is_none() {
+            character_controller.vertical_velocity += up_speed;
+        }
+    }
+
+    if !translation.is_finite() {
+        bevy::log::warn_once!("a character's step ended at a non-finite translation, undid it");
+        *translation = initial_translation;
+        character_controller.ground = None;
+    }
+
+    character_controller.last_landing =
+        (!was_grounded && ground.is_some()).then_some(falling_speed);
+    character_controller.actual_velocity = if delta_seconds > 0.0 {
+        (*translation - initial_translation).f32() / delta_seconds
+    } else {
+        Vec3::ZERO
+    };
+    character_controller.last_move = output;
+    character_controller.last_fall = fall;
+    character_controller.ground = ground;
+    character_controller.sliding = sliding.map(|sliding| Sliding {
+        velocity: slide_velocity,
+        ..sliding
+    });
+    character_controller.mode = if character_controller.ground.is_some() {
+        MovementMode::Walking
+    } else if character_controller.sliding.is_some() {
+        MovementMode::Sliding
+    } else {
+        MovementMode::Falling
+    };
+}
+
+/// Moves an underwater character towards its input velocity in any direction, without gravity or
+/// ground.
+fn swim(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    config: &CharacterControllerConfig,
+    delta_seconds: f32,
+) {
+    let drag = (-config.swim_drag * delta_seconds).exp();
+    let target = character_controller.velocity * config.swim_speed;
+
+    character_controller.external_velocity *= drag;
+    move_freely(
+        character_controller,
+        translation,
+        context,
+        delta_seconds,
+        |velocity| target + (velocity - target) * drag,
+    );
+}
+
+/// Accelerates a flying character by the config's `thrust` towards its input velocity in any
+/// direction, without gravity or ground. Without input it drifts, like in space.
+fn fly(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    config: &CharacterControllerConfig,
+    delta_seconds: f32,
+) {
+    let target = character_controller.velocity;
+    let max_change = config.thrust * delta_seconds;
+
+    move_freely(
+        character_controller,
+        translation,
+        context,
+        delta_seconds,
+        |velocity| {
+            if target == Vec3::ZERO {
+                velocity
+            } else {
+                velocity + (target - velocity).clamp_length_max(max_change)
+            }
+        },
+    );
+}
+
+/// Moves a climbing character at exactly its input velocity, without gravity or ground.
+fn climb(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    delta_seconds: f32,
+) {
+    let target = character_controller.velocity;
+
+    move_freely(
+        character_controller,
+        translation,
+        context,
+        delta_seconds,
+        |_| target,
+    );
+}
+
+/// Moves a character that isn't affected by gravity or ground, with its velocity changed by
+/// `accelerate`. Its vertical velocity is folded into the movement and split back out afterwards,
+/// so entering and leaving a mode that moves freely carries its speed over.
+fn move_freely(
+    character_controller: &mut CharacterController,
+    translation: &mut Vector,
+    context: &SlideContext,
+    delta_seconds: f32,
+    accelerate: impl FnOnce(Vec3) -> Vec3,
+) {
+    let initial_translation = *translation;
+    let up = *context.up;
+    let mut velocity = accelerate(
+        character_controller.move_velocity + up * character_controller.vertical_velocity,
+    );
+
+    let displacement =
+        ((velocity + character_controller.external_velocity) * delta_seconds).adjust_precision();
+    let output = move_and_slide(context, *translation, displacement, None, false);
+
+    // Stop moving into whatever was hit, like walking into walls.
+    for hit in output.bounces().iter().filter_map(|bounce| bounce.hit) {
+        let normal = hit.normal.f32();
+
+        velocity -= normal * velocity.dot(normal).min(0.0);
+        character_controller.external_velocity -=
+            normal * character_controller.external_velocity.dot(normal).min(0.0);
+    }
+
+    *translation = output.translation;
+
+    if !translation.is_finite() {
+        bevy::log::warn_once!("a character's step ended at a non-finite translation, undid it");
+        *translation = initial_translation;
+    }
+
+    character_controller.vertical_velocity = velocity.dot(up);
+    character_controller.move_velocity = velocity - up * character_controller.vertical_velocity;
+    character_controller.time_since_grounded += delta_seconds;
+    character_controller.last_jump = None;
+    character_controller.last_landing = None;
+    character_controller.actual_velocity = if delta_seconds > 0.0 {
+        (*translation - initial_translation).f32() / delta_seconds
+    } else {
+        Vec3::ZERO
+    };
+    character_controller.last_move = output;
+    character_controller.last_fall = MoveAndSlideOutput::default();
+    character_controller.ground = None;
+    character_controller.sliding = None;
+}
+
+/// Zeroes any of the character's velocities that aren't finite, so one bad input doesn't turn its
+/// position into NaN.
+fn sanitize_velocities(character_controller: &mut CharacterController) {
+    for (name, velocity) in [
+        ("velocity", &mut character_controller.velocity),
+        ("move_velocity", &mut character_controller.move_velocity),
+        (
+            "external_velocity",
+            &mut character_controller.external_velocity,
+        ),
+    ] {
+        if !velocity.is_finite() {
+            bevy::log::warn_once!("zeroed a character's non-finite {name} of {velocity}");
+            *velocity = Vec3::ZERO;
+        }
+    }
+
+    if !character_controller.vertical_velocity.is_finite() {
+        bevy::log::warn_once!("zeroed a character's non-finite vertical_velocity");
+        character_controller.vertical_velocity = 0.0;
+    }
+}
+
+/// Lifts the character by up to the config's `max_step_height`, moves it by `displacement`, and
+/// puts it back down. Returns where it ends up if it lands on walkable ground that's at least
+/// `min_step_depth` deep.
+fn step_up(
+    context: &SlideContext,
+    translation: Vector,
+    displacement: Vector,
+    config: &CharacterControllerConfig,
+) -> Option<Vector> {
+    let up = vector(context.up);
+    let lift = context
+        .cast(translation, context.up, config.max_step_height + SKIN_WIDTH)
+        .map_or(config.max_step_height, |hit| {
+            (hit.time_of_impact - SKIN_WIDTH).max(0.0)
+        });
+
+    let forward = context.horizontal(displacement);
+    let raised = move_and_slide(context, translation + up * lift, forward, None, false);
+
+    let ground = probe_ground(context, raised.translation, lift + GROUND_PROBE_DISTANCE)?;
+
+    // Thin lips don't count as steps, so the step's top has to continue `min_step_depth` past where
+    // the character touches it, no lower than that point.
+    if config.min_step_depth > 0.0 {
+        let direction = Dir3::new(forward.f32()).ok()?;
+        let origin = ground.point + vector(direction) * config.min_step_depth + up * lift;
+        let hit = context.spatial_query.cast_ray(
+            origin,
+            -context.up,
+            lift + SKIN_WIDTH,
+            context.filter,
+        )?;
+
+        if !context.is_walkable(hit.entity, hit.normal) {
+            return None;
+        }
+    }
+
+    Some(raised.translation - up * ground.distance)
+}
+
+fn send_movement_events(
+    query: Query<(Entity, &CharacterController), Without<ControllerDisabled>>,
+    mut jumped: EventWriter<Jumped>,
+    mut air_jumps: EventWriter<AirJump>,
+    mut landed: EventWriter<Landed>,
+    mut exhausted: EventWriter<SolverExhausted>,
+    mut mode_changed: EventWriter<MovementModeChanged>,
+) {
+    for (entity, character_controller) in &query {
+        if let Some(from) = character_controller.last_mode_change {
+            mode_changed.send(MovementModeChanged {
+                character: entity,
+                from,
+                to: character_controller.mode,
+            });
+        }
+
+        for exhaustion in [
+            character_controller.last_move.exhausted,
+            character_controller.last_fall.exhausted,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            exhausted.send(SolverExhausted {
+                character: entity,
+                exhaustion,
+            });
+        }
+
+        if let Some(speed) = character_controller.last_landing {
+            landed.send(Landed {
+                character: entity,
+                speed,
+            });
+        }
+
+        match character_controller.last_jump {
+            Some(JumpKind::Ground) => {
+                jumped.send(Jumped { character: entity });
+            }
+            Some(JumpKind::Air) => {
+                air_jumps.send(AirJump {
+                    character: entity,
+                    count: character_controller.air_jumps_used,
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+fn debug_character_controllers(
+    query: Query<&CharacterController, With<DebugCharacterController>>,
+    mut gizmos: Gizmos,
+) {
+    for character_controller in &query {
+        let last_move = &character_controller.last_move;
+
+        for (index, bounce) in last_move.bounces().iter().enumerate() {
+            gizmos.ray(
+                bounce.origin.f32(),
+                bounce.direction.as_vec3(),
+                Color::linear_rgb(1.0, 0.0, 0.0),
+            );
+
+            if let Some(hit) = &bounce.hit {
+                gizmos.sphere(hit.point.f32(), Quat::IDENTITY, 0.1, Color::WHITE);
+                draw_slide_plane(&mut gizmos, hit, index);
+            }
+        }
+
+        let hits = last_move.bounces().iter().filter_map(|bounce| bounce.hit);
+
+        if let Some(hit) = hits.clone().last() {
+            screen_print!("normal: {}", hit.normal);
+        }
+        screen_print!("grounded: {}", character_controller.is_grounded());
+        screen_print!("bounces: {}", last_move.bounce_count);
+        screen_print!("hit count: {}", hits.count());
+    }
+}
+
+/// Draws the plane of a surface hit in the `index`th bounce as a disk around the hit point with its
+/// normal, colored by `index`, so corners and creases show which surface each bounce slid along.
+fn draw_slide_plane(gizmos: &mut Gizmos, hit: &SlideHit, index: usize) {
+    let Ok(normal) = Dir3::new(hit.normal.f32()) else {
+        return;
+    };
+    let point = hit.point.f32();
+    let color = Color::hsla(360.0 * index as f32 / MAX_BOUNCES as f32, 1.0, 0.5, 0.4);
+
+    for radius in [0.15, 0.3, 0.45] {
+        gizmos.circle(point, normal, radius, color);
+    }
+    gizmos.arrow(point, point + *normal * 0.5, color.with_alpha(1.0));
+}
+
+/// Contacts this close to the ground plane can be seams. Larger lips are treated as obstacles.
+pub const SEAM_TOLERANCE: Scalar = 0.02;
+
+/// Everything the solver needs that stays the same for every cast of one character's step.
+pub struct SlideContext<'a> {
+    pub spatial_query: &'a dyn SpatialQueryBackend,
+    pub collider: &'a Collider,
+    pub rotation: Quaternion,
+    pub filter: &'a SpatialQueryFilter,
+    /// The character's [`CharacterController::up_direction`].
+    pub up: Dir3,
+    /// The steepest surface, in radians, that counts as ground.
+    pub max_slope_angle: f32,
+    /// What to do when the bounces run out.
+    pub fallback: SolverFallback,
+    /// Overrides of how surfaces are classified. Without any, surfaces are classified by angle
+    /// alone.
+    pub walkable_overrides: Option<&'a dyn WalkableOverrides>,
+}
+
+impl SlideContext<'_> {
+    /// Returns how this character treats the surface of `entity` with `normal`.
+    pub fn classify(&self, entity: Entity, normal: Vector) -> SurfaceKind {
+        let kind = surface_kind(normal, self.up, self.max_slope_angle);
+
+        match self
+            .walkable_overrides
+            .and_then(|overrides| overrides.get(entity))
+        {
+            Some(walkable_override) => walkable_override.apply(kind),
+            None => kind,
+        }
+    }
+
+    /// Returns whether the surface of `entity` with `normal` is flat enough for this character to
+    /// stand on, or is [`AlwaysWalkable`].
+    pub fn is_walkable(&self, entity: Entity, normal: Vector) -> bool {
+        self.classify(entity, normal) == SurfaceKind::Walkable
+    }
+
+    /// Returns whether this character can step up onto the collider `entity`, which it can't if
+    /// it's [`NotWalkable`].
+    pub fn is_steppable(&self, entity: Entity) -> bool {
+        self.walkable_overrides
+            .and_then(|overrides| overrides.get(entity))
+            != Some(WalkableOverride::Boundary)
+    }
+
+    /// Removes the part of `value` along the character's up direction.
+    pub fn horizontal(&self, value: Vector) -> Vector {
+        let up = vector(self.up);
+
+        value - up * value.dot(up)
+    }
+
+    /// Casts the character's collider, flipping the hit normal to face against `direction`.
+    ///
+    /// Trimesh triangles are two-sided, and the reported normal can point away from the cast
+    /// shape depending on which side of the triangle is hit.
+    pub fn cast(
+        &self,
+        origin: Vector,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+    ) -> Option<ShapeHitData> {
+        let mut hit = self.spatial_query.cast_shape(
+            self.collider,
+            origin,
+            self.rotation,
+            direction,
+            max_time_of_impact,
+            self.filter,
+        )?;
+
+        if hit.normal1.dot(vector(direction)) > 0.0 {
+            hit.normal1 = -hit.normal1;
+        }
+
+        Some(hit)
+    }
+
+    /// Casts like [`SlideContext::cast`], but looks past any hit that [`is_seam`].
+    ///
+    /// Seams are skipped by recasting from just above the ground plane rather than by excluding
+    /// the hit entity, because with a trimesh the seam and the walls around it are the same
+    /// entity.
+    fn cast_ignoring_seams(
+        &self,
+        origin: Vector,
+        direction: Dir3,
+        max_time_of_impact: Scalar,
+        ground: Option<&GroundInfo>,
+    ) -> Option<ShapeHitData> {
+        let hit = self.cast(origin, direction, max_time_of_impact)?;
+
+        let Some(ground) = ground else {
+            return Some(hit);
+        };
+
+        if !is_seam(self, &hit, ground) {
+            return Some(hit);
+        }
+
+        self.cast(
+            origin + ground.normal * SEAM_TOLERANCE,
+            direction,
+            max_time_of_impact,
+        )
+    }
+}
+
+/// Converts a direction to avian's precision.
+fn vector(direction: Dir3) -> Vector {
+    direction.as_vec3().adjust_precision()
+}
+
+/// Returns whether a surface is at most `max_slope_angle` radians steep relative to `up`.
+pub fn is_walkable(normal: Vector, up: Dir3, max_slope_angle: f32) -> bool {
+    normal.angle_between(vector(up)) <= max_slope_angle.adjust_precision()
+}
+
+/// Returns whether `hit` is on an internal edge between the ground and a neighboring collider
+/// that is flush with it, like the seam between two floor tiles or two triangles of a trimesh.
+///
+/// Sweeping across such an edge can report a hit with a normal that opposes the movement, even
+/// though there's nothing to collide with, which makes the character hitch. A hit is a seam when
+/// it's at the ground plane and there is a walkable top surface just past it.
+pub fn is_seam(context: &SlideContext, hit: &ShapeHitData, ground: &GroundInfo) -> bool {
+    let height = (hit.point1 - ground.point).dot(ground.normal);
+
+    if height > SEAM_TOLERANCE {
+        return false;
+    }
+
+    let Ok(down) = Dir3::new(-ground.normal.f32()) else {
+        return false;
+    };
+
+    // Start just above the ground on the far side of the contact. Inside a wall the solid ray
+    // hits immediately with a zero normal, so walls are never mistaken for seams.
+    let origin = hit.point1 - hit.normal1 * SEAM_TOLERANCE + ground.normal * SEAM_TOLERANCE;
+
+    context
+        .spatial_query
+        .cast_ray(origin, down, SEAM_TOLERANCE * 2.0, context.filter)
+        .is_some_and(|ray_hit| {
+            context.is_steppable(ray_hit.entity)
+                && ray_hit.normal.angle_between(ground.normal)
+                    <= context.max_slope_angle.adjust_precision()
+        })
+}
+
+/// Casts down from `translation` by up to `distance` and returns the ground if it's flat enough
+/// to stand on. The ground's material, traction, and velocity aren't looked up, and are left as
+/// [`SurfaceMaterial::NORMAL`], 1, and zero.
+pub fn probe_ground(
+    context: &SlideContext,
+    translation: Vector,
+    distance: Scalar,
+) -> Option<GroundInfo> {
+    probe_ground_along(context, translation, -context.up, distance)
+}
+
+/// Casts down from `translation` by up to `distance` and returns the surface if it's
+/// [`SurfaceKind::Steep`], too steep to stand on but not a wall or ceiling.
+pub fn probe_slide(
+    context: &SlideContext,
+    translation: Vector,
+    distance: Scalar,
+) -> Option<Sliding> {
+    let hit = context.cast(translation, -context.up, distance + SKIN_WIDTH)?;
+
+    if context.classify(hit.entity, hit.normal1) != SurfaceKind::Steep {
+        return None;
+    }
+
+    Some(Sliding {
+        entity: hit.entity,
+        normal: hit.normal1,
+        velocity: Vec3::ZERO,
+    })
+}
+
+/// Like [`probe_ground`], but casts in `direction` instead of straight down, e.g. into the
+/// previous ground along its normal.
+pub fn probe_ground_along(
+    context: &SlideContext,
+    translation: Vector,
+    direction: Dir3,
+    distance: Scalar,
+) -> Option<GroundInfo> {
+    let hit = context.cast(translation, direction, distance + SKIN_WIDTH)?;
+
+    if !context.is_walkable(hit.entity, hit.normal1) {
+        return None;
+    }
+
+    Some(GroundInfo {
+        entity: hit.entity,
+        point: translation + context.rotation * hit.point2,
+        normal: hit.normal1,
+        distance: (hit.time_of_impact - SKIN_WIDTH).max(0.0),
+        material: SurfaceMaterial::NORMAL,
+        traction: 1.0,
+        velocity: Vec3::ZERO,
+    })
+}
+
+/// The farthest a slide can travel past its requested distance, from nudging off of parallel
+/// planes.
+pub const SLIDE_NUDGE_MARGIN: Scalar = 0.01 * MAX_BOUNCES as Scalar;
+
+/// Returns whether any collider besides the excluded ones could be hit while sliding `distance`.
+///
+/// Sliding never moves the character farther than the requested distance (plus nudges), so the
+/// reachable region is bounded by the character's AABB grown by that distance in every direction.
+fn has_nearby_colliders(context: &SlideContext, translation: Vector, distance: Scalar) -> bool {
+    let aabb = context.collider.aabb(translation, context.rotation);
+    let margin = Vector::splat(distance + SKIN_WIDTH + SLIDE_NUDGE_MARGIN);
+
+    context.spatial_query.intersects_aabb(
+        ColliderAabb {
+            min: aabb.min - margin,
+            max: aabb.max + margin,
+        },
+        context.filter,
+    )
+}
+
+/// A surface hit by the cast during one bounce of [`move_and_slide`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SlideHit {
+    pub entity: Entity,
+    pub point: Vector,
+    pub normal: Vector,
+    pub time_of_impact: Scalar,
+    /// How the character treated the surface. Recordings from before it existed load it as
+    /// walkable.
+    #[serde(default)]
+    pub kind: SurfaceKind,
+}
+
+/// One iteration of the bounce loop in [`move_and_slide`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SlideBounce {
+    pub origin: Vector,
+    pub direction: Dir3,
+    pub distance: Scalar,
+    pub hit: Option<SlideHit>,
+}
+
+impl SlideBounce {
+    const EMPTY: Self = Self {
+        origin: Vector::ZERO,
+        direction: Dir3::X,
+        distance: 0.0,
+        hit: None,
+    };
+}
+
+/// Where and why [`move_and_slide`] ran out of bounces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlideExhaustion {
+    /// Where the last bounce ended, before the fallback.
+    pub translation: Vector,
+    /// The movement that was left when the bounces ran out.
+    pub remaining: Vector,
+    /// The normal of the last surface hit.
+    pub last_normal: Vector,
+    /// Where the character ended up after the fallback.
+    pub resolved_translation: Vector,
+    pub fallback: SolverFallback,
+}
+
+/// The result of [`move_and_slide`]. Bounces are stored inline so solving doesn't allocate.
+#[derive(Clone, Copy, Debug)]
+pub struct MoveAndSlideOutput {
+    pub translation: Vector,
+    pub bounce_count: usize,
+    bounces: [SlideBounce; MAX_BOUNCES],
+    /// Set if the bounces ran out before the movement did.
+    pub exhausted: Option<SlideExhaustion>,
+}
+
+impl MoveAndSlideOutput {
+    fn new(translation: Vector) -> Self {
+        Self {
+            translation,
+            bounce_count: 0,
+            bounces: [SlideBounce::EMPTY; MAX_BOUNCES],
+            exhausted: None,
+        }
+    }
+
+    pub fn bounces(&self) -> &[SlideBounce] {
+        &self.bounces[..self.bounce_count]
+    }
+
+    /// How far the slide moved the character.
+    pub fn displacement(&self) -> Vector {
+        self.bounces()
+            .first()
+            .map_or(Vector::ZERO, |bounce| self.translation - bounce.origin)
+    }
+}
+
+impl Default for MoveAndSlideOutput {
+    fn default() -> Self {
+        Self::new(Vector::ZERO)
+    }
+}
+
+/// Sweeps `collider` from `translation` by `displacement`, sliding along every surface it hits.
+///
+/// `ground` is the ground the character was standing on, which is used to ignore the seams between
+/// adjacent floor colliders (see [`is_seam`]).
+///
+/// In the `gravity_pass`, the slide stops at the first walkable surface instead of sliding along
+/// it, so that standing on a slope doesn't slowly slide the character down it.
+///
+/// If the movement isn't used up after [`MAX_BOUNCES`], the context's [`SolverFallback`] decides
+/// what happens to the rest, and the output's `exhausted` says so.
+///
+/// A single broad-phase query covering every position the character can reach this step is made
+/// up front, and when it finds nothing the narrow-phase casts are skipped entirely. Characters in
+/// open space, which is most of them in a typical level, cost one AABB query instead of a cast.
+///
+/// This only reads from the spatial query pipeline, so it can be run for many characters in
+/// parallel. The pipeline is not updated until the next physics step, which means characters
+/// don't see each other's movement within the same step.
+pub fn move_and_slide(
+    context: &SlideContext,
+    translation: Vector,
+    displacement: Vector,
+    ground: Option<&GroundInfo>,
+    gravity_pass: bool,
+) -> MoveAndSlideOutput {
+    let mut output = MoveAndSlideOutput::new(translation);
+
+    let mut direction_result = Dir3::new(displacement.f32());
+    let mut distance = displacement.length();
+
+    // Dir3::new also rejects NaN and infinite displacements.
+    let Ok(start_direction) = direction_result else {
+        return output;
+    };
+
+    if distance < MIN_SLIDE_DISTANCE || !translation.is_finite() {
+        return output;
+    }
+
+    if !has_nearby_colliders(context, translation, distance) {
+        output.bounces[0] = SlideBounce {
+            origin: translation,
+            direction: start_direction,
+            distance,
+            hit: None,
+        };
+        output.bounce_count = 1;
+        output.translation += vector(start_direction) * distance;
+        return output;
+    }
+
+    let mut planes = [Vector::ZERO; MAX_BOUNCES];
+    let mut plane_count = 0;
+    // Only still set after the loop if its last bounce left movement for another.
+    let mut exhausted = false;
+
+    for _ in 0..MAX_BOUNCES {
+        exhausted = false;
+
+        let Ok(direction) = direction_result else {
+            break;
+        };
+
+        if distance < MIN_SLIDE_DISTANCE {
+            break;
+        }
+
+        let bounce = &mut output.bounces[output.bounce_count];
+        output.bounce_count += 1;
+
+        *bounce = SlideBounce {
+            origin: output.translation,
+            direction,
+            distance,
+            hit: None,
+        };
+
+        let Some(hit) = sweep(
+            context,
+            &mut output.translation,
+            direction,
+            &mut distance,
+            ground,
+        ) else {
+            output.translation += vector(direction) * distance;
+            break;
+        };
+
+        // Shapes touching at a single point or edge can report a zero normal, which there's no
+        // sliding along.
+        if !hit.normal1.is_finite() || hit.normal1.length_squared() < 0.5 {
+            bevy::log::warn_once!("stopped a slide at a degenerate normal {}", hit.normal1);
+            break;
+        }
+
+        let kind = context.classify(hit.entity, hit.normal1);
+
+        bounce.hit = Some(SlideHit {
+            entity: hit.entity,
+            point: output.translation + context.rotation * hit.point2,
+            normal: hit.normal1,
+            time_of_impact: hit.time_of_impact,
+            kind,
+        });
+
+        if hit.time_of_impact >= distance {
+            output.translation += vector(direction) * (hit.time_of_impact - SKIN_WIDTH).max(0.0);
+            break;
+        }
+
+        if hit.time_of_impact >= SKIN_WIDTH {
+            output.translation += vector(direction) * (hit.time_of_impact - SKIN_WIDTH)
+        }
+
+        if gravity_pass && kind == SurfaceKind::Walkable {
+            break;
+        }
+
+        let extra_distance = distance - (hit.time_of_impact - SKIN_WIDTH).max(0.0);
+        let extra_velocity = vector(direction) * extra_distance;
+
+        let mut projected_velocity = clip_velocity(extra_velocity, hit.normal1);
+
+        // Sliding up a surface too steep to stand on would let the character creep up it by
+        // approaching at a shallow angle, so slide along it level instead, like along a wall.
+        if !gravity_pass
+            && kind != SurfaceKind::Walkable
+            && projected_velocity.dot(vector(context.up)) > 0.0
+        {
+            let wall_normal = context.horizontal(hit.normal1).normalize_or_zero();
+
+            projected_velocity = clip_velocity(context.horizontal(extra_velocity), wall_normal);
+        }
+
+        if projected_velocity.dot(vector(start_direction)) <= 0.0 {
+            break;
+        }
+
+        let projected_velocity =
+            nudge_off_planes(projected_velocity, hit.normal1, &planes[..plane_count]);
+
+        planes[plane_count] = hit.normal1;
+        plane_count += 1;
+
+        direction_result = Dir3::new(projected_velocity.f32());
+        distance = projected_velocity.length();
+        exhausted = true;
+    }
+
+    if let (true, Ok(direction)) = (exhausted, direction_result) {
+        let planes = &planes[..plane_count];
+        let last_normal = planes.last().copied().unwrap_or_default();
+        let translation = output.translation;
+
+        output.translation = match context.fallback {
+            SolverFallback::Halt => translation,
+            SolverFallback::Depenetrate => depenetrate(context, translation, planes),
+            SolverFallback::SlideAlongLastPlane => slide_along_plane(
+                context,
+                translation,
+                vector(direction) * distance,
+                last_normal,
+            ),
+        };
+
+        output.exhausted = Some(SlideExhaustion {
+            translation,
+            remaining: vector(direction) * distance,
+            last_normal,
+            resolved_translation: output.translation,
+            fallback: context.fallback,
+        });
+    }
+
+    output
+}
+
+/// Casts from `translation` by `distance` in segments of at most [`MAX_SWEEP_DISTANCE`], advancing
+/// both past the segments that hit nothing, so the hit's time of impact is from `translation` and
+/// `distance` is what's left. Returns `None` with the last segment still to move if nothing is hit.
+fn sweep(
+    context: &SlideContext,
+    translation: &mut Vector,
+    direction: Dir3,
+    distance: &mut Scalar,
+    ground: Option<&GroundInfo>,
+) -> Option<ShapeHitData> {
+    loop {
+        let segment = distance.min(MAX_SWEEP_DISTANCE);
+        let hit =
+            context.cast_ignoring_seams(*translation, direction, segment + SKIN_WIDTH, ground);
+
+        if hit.is_some() || segment >= *distance {
+            return hit;
+        }
+
+        *translation += vector(direction) * segment;
+        *distance -= segment;
+    }
+}
+
+/// Pushes the character out to [`SKIN_WIDTH`] from each of `planes` it's closer to than that.
+fn depenetrate(context: &SlideContext, translation: Vector, planes: &[Vector]) -> Vector {
+    let mut translation = translation;
+
+    for &normal in planes {
+        let Ok(direction) = Dir3::new(-normal.f32()) else {
+            continue;
+        };
+
+        if let Some(hit) = context.cast(translation, direction, SKIN_WIDTH) {
+            translation += normal * (SKIN_WIDTH - hit.time_of_impact);
+        }
+    }
+
+    translation
+}
+
+/// Moves by `remaining` flattened onto the plane with `normal`, stopping short of any hit.
+fn slide_along_plane(
+    context: &SlideContext,
+    translation: Vector,
+    remaining: Vector,
+    normal: Vector,
+) -> Vector {
+    let along_plane = clip_velocity(remaining, normal);
+
+    let Ok(direction) = Dir3::new(along_plane.f32()) else {
+        return translation;
+    };
+    let distance = along_plane.length();
+
+    let travel = context
+        .cast(translation, direction, distance + SKIN_WIDTH)
+        .map_or(distance, |hit| {
+            (hit.time_of_impact - SKIN_WIDTH).clamp(0.0, distance)
+        });
+
+    translation + vector(direction) * travel
 }
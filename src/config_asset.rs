@@ -0,0 +1,143 @@
+//! [`CharacterControllerConfig`]s as RON assets, so they can be edited on disk and hot reloaded
+//! into a running game with bevy's `file_watcher` feature.
+//!
+//! Config files use the `.kcc.ron` extension and list any fields of
+//! [`CharacterControllerConfig`], with the rest taken from its default:
+//!
+//! ```ron
+//! (
+//!     acceleration: 10.0,
+//!     max_step_height: 0.3,
+//!     coyote_time: 0.15,
+//! )
+//! ```
+
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::character_controller::CharacterControllerConfig;
+
+pub struct KccConfigPlugin;
+
+impl Plugin for KccConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<KccConfig>()
+            .init_asset_loader::<KccConfigLoader>()
+            .add_systems(Update, apply_kcc_configs);
+    }
+}
+
+#[derive(Asset, TypePath, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KccConfig(pub CharacterControllerConfig);
+
+impl KccConfig {
+    /// The config as the contents of a `.kcc.ron` file, with every field listed.
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("configs are always serializable")
+    }
+}
+
+/// Writes `config` over the file of the asset at `path` in the assets folder, which hot reloads
+/// it into every character using it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_kcc_config(path: &AssetPath, config: &KccConfig) -> Result<(), String> {
+    use bevy::asset::io::file::FileAssetReader;
+
+    let file = FileAssetReader::get_base_path()
+        .join("assets")
+        .join(path.path());
+
+    std::fs::write(&file, config.to_ron()).map_err(|e| format!("{}: {e}", file.display()))
+}
+
+/// Sets the character's [`CharacterControllerConfig`] from a [`KccConfig`] asset whenever the
+/// asset is loaded or changes.
+#[derive(Component, Clone, Debug)]
+pub struct KccConfigHandle(pub Handle<KccConfig>);
+
+#[derive(Default)]
+pub struct KccConfigLoader;
+
+#[derive(Debug)]
+pub enum KccConfigError {
+    Io(std::io::Error),
+    Ron(ron::de::SpannedError),
+}
+
+impl fmt::Display for KccConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KccConfigError::Io(error) => write!(f, "couldn't read config: {error}"),
+            KccConfigError::Ron(error) => write!(f, "couldn't parse config: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for KccConfigError {}
+
+impl AssetLoader for KccConfigLoader {
+    type Asset = KccConfig;
+    type Settings = ();
+    type Error = KccConfigError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<KccConfig, KccConfigError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(KccConfigError::Io)?;
+
+        ron::de::from_bytes(&bytes).map_err(KccConfigError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["kcc.ron"]
+    }
+}
+
+fn apply_kcc_configs(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<KccConfig>>,
+    configs: Res<Assets<KccConfig>>,
+    characters: Query<(
+        Entity,
+        Ref<KccConfigHandle>,
+        Option<&CharacterControllerConfig>,
+    )>,
+) {
+    let changed: Vec<_> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::LoadedWithDependencies { id }
+            | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, handle, current) in &characters {
+        if !handle.is_changed() && !changed.contains(&handle.0.id()) {
+            continue;
+        }
+
+        let Some(config) = configs.get(&handle.0) else {
+            continue;
+        };
+
+        if current != Some(&config.0) {
+            commands.entity(entity).insert(config.0);
+        }
+    }
+}
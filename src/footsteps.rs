@@ -0,0 +1,78 @@
+//! Sends an event for every stride a grounded character takes.
+
+use avian3d::math::AsF32;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{
+        CharacterController, CharacterControllerSet, ControllerDisabled, SurfaceMaterial,
+    },
+    schedule::CustomPostUpdate,
+};
+
+pub struct FootstepPlugin;
+
+impl Plugin for FootstepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Footstep>().add_systems(
+            CustomPostUpdate,
+            send_footsteps.after(CharacterControllerSet),
+        );
+    }
+}
+
+/// Makes a character send a [`Footstep`] every `stride` meters it walks on the ground.
+#[derive(Component)]
+pub struct Footsteps {
+    pub stride: f32,
+    /// How far the character has walked since its last footstep.
+    pub distance: f32,
+}
+
+impl Footsteps {
+    pub fn new(stride: f32) -> Self {
+        Self {
+            stride,
+            distance: 0.0,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Footstep {
+    pub character: Entity,
+    /// The collider that was stepped on.
+    pub ground: Entity,
+    pub material: SurfaceMaterial,
+    pub point: Vec3,
+}
+
+fn send_footsteps(
+    mut characters: Query<
+        (Entity, &CharacterController, &mut Footsteps),
+        Without<ControllerDisabled>,
+    >,
+    mut footsteps: EventWriter<Footstep>,
+) {
+    for (entity, character_controller, mut character_footsteps) in &mut characters {
+        let Some(ground) = character_controller.ground else {
+            // Start a fresh stride on landing.
+            character_footsteps.distance = 0.0;
+            continue;
+        };
+
+        character_footsteps.distance +=
+            character_controller.last_move.displacement().f32().length();
+
+        if character_footsteps.distance >= character_footsteps.stride {
+            character_footsteps.distance %= character_footsteps.stride;
+
+            footsteps.send(Footstep {
+                character: entity,
+                ground: ground.entity,
+                material: ground.material,
+                point: ground.point.f32(),
+            });
+        }
+    }
+}
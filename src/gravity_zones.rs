@@ -0,0 +1,154 @@
+//! Volumes that change gravity inside them, like low gravity rooms or upside down corridors.
+
+use avian3d::{math::AsF32, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{apply_gravity, CharacterController, CharacterGravity, VelocityStage},
+    planet::{pull_towards_gravity_sources, PlanetGravity},
+    schedule::CustomPostUpdate,
+};
+
+pub struct GravityZonePlugin;
+
+impl Plugin for GravityZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            apply_gravity_zones
+                .after(pull_towards_gravity_sources)
+                .before(apply_gravity)
+                .in_set(VelocityStage::Gravity),
+        );
+    }
+}
+
+/// A sensor volume that changes the gravity of characters inside it, and stands them up against
+/// it. Characters leave with the gravity and up direction they had before entering, or those of the
+/// [`GravitySource`](crate::planet::GravitySource) they're in.
+///
+/// Overlapping zones apply from the lowest `priority` to the highest, starting from the gravity the
+/// character has outside of zones, like a gravity source's, or else avian's [`Gravity`], so a higher priority [`ZoneGravity::Replace`] wins over everything below it while
+/// [`ZoneGravity::Scale`] zones stack. Zones of equal priority apply in an arbitrary but consistent
+/// order.
+///
+/// Needs a [`Sensor`] collider and [`CollidingEntities`] on the same entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct GravityZone {
+    pub gravity: ZoneGravity,
+    pub priority: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoneGravity {
+    /// Replaces gravity, e.g. with `Vec3::Y * 9.81` to walk on the ceiling.
+    Replace(Vec3),
+    /// Multiplies gravity, e.g. by 0.3 for a low gravity room.
+    Scale(f32),
+}
+
+/// The gravity and up direction a character had before a [`GravityZone`] changed them, to stack
+/// zones on top of and to put back once it leaves every zone.
+#[derive(Component, Clone, Copy, PartialEq)]
+struct ZoneGravityApplied {
+    gravity: Option<Vec3>,
+    up_direction: Dir3,
+}
+
+impl ZoneGravityApplied {
+    /// What a gravity source leaves characters with when they leave it.
+    const OUTSIDE_GRAVITY_SOURCE: Self = Self {
+        gravity: None,
+        up_direction: Dir3::Y,
+    };
+}
+
+fn apply_gravity_zones(
+    mut commands: Commands,
+    zones: Query<(Entity, &GravityZone, &CollidingEntities)>,
+    mut characters: Query<(
+        Entity,
+        &mut CharacterController,
+        Option<&mut CharacterGravity>,
+        Option<&ZoneGravityApplied>,
+        Has<PlanetGravity>,
+    )>,
+    gravity: Res<Gravity>,
+) {
+    for (entity, mut character_controller, character_gravity, applied, on_planet) in &mut characters
+    {
+        let mut containing: Vec<_> = zones
+            .iter()
+            .filter(|(_, _, colliding_entities)| colliding_entities.contains(&entity))
+            .map(|(zone_entity, zone, _)| (zone.priority, zone_entity, zone.gravity))
+            .collect();
+
+        if containing.is_empty() {
+            if let Some(applied) = applied {
+                // Gravity sources set their gravity every step before zones apply, so there's
+                // only something to put back outside of them.
+                if !on_planet {
+                    character_controller.up_direction = applied.up_direction;
+
+                    match applied.gravity {
+                        Some(previous) => {
+                            commands.entity(entity).insert(CharacterGravity(previous));
+                        }
+                        None => {
+                            commands.entity(entity).remove::<CharacterGravity>();
+                        }
+                    }
+                }
+
+                commands.entity(entity).remove::<ZoneGravityApplied>();
+            }
+
+            continue;
+        }
+
+        // What the character has without zones: a gravity source's gravity is set fresh every
+        // step, while anything else is remembered from before the character entered.
+        let (base_gravity, outside) = if on_planet {
+            (
+                character_gravity.as_ref().map(|gravity| gravity.0),
+                ZoneGravityApplied::OUTSIDE_GRAVITY_SOURCE,
+            )
+        } else {
+            let outside = applied.copied().unwrap_or(ZoneGravityApplied {
+                gravity: character_gravity.as_ref().map(|gravity| gravity.0),
+                up_direction: character_controller.up_direction,
+            });
+
+            (outside.gravity, outside)
+        };
+
+        if applied != Some(&outside) {
+            commands.entity(entity).insert(outside);
+        }
+
+        containing.sort_by_key(|&(priority, zone_entity, _)| (priority, zone_entity));
+
+        let mut zone_gravity = base_gravity.unwrap_or(gravity.0.f32());
+
+        for (_, _, gravity) in containing {
+            zone_gravity = match gravity {
+                ZoneGravity::Replace(replacement) => replacement,
+                ZoneGravity::Scale(scale) => zone_gravity * scale,
+            };
+        }
+
+        // Without gravity there's no down, so the character keeps standing the way it was.
+        if let Ok(down) = Dir3::new(zone_gravity) {
+            character_controller.up_direction = -down;
+        }
+
+        match character_gravity {
+            Some(mut character_gravity) => character_gravity.0 = zone_gravity,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(CharacterGravity(zone_gravity));
+            }
+        }
+    }
+}
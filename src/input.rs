@@ -0,0 +1,94 @@
+//! Button input for the custom schedule. Bevy's [`ButtonInput`] is updated once per rendered frame,
+//! so reading `just_pressed` in a fixed step misses presses in frames where no step runs, and sees
+//! them twice in frames where two steps run. [`FixedInput`] keeps presses and releases until a step
+//! has seen them instead.
+
+use std::hash::Hash;
+
+use bevy::{input::InputSystem, prelude::*, utils::HashSet};
+
+use crate::schedule::CustomLast;
+
+pub struct FixedInputPlugin;
+
+impl Plugin for FixedInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FixedInput<KeyCode>>()
+            .init_resource::<FixedInput<MouseButton>>()
+            .add_systems(
+                PreUpdate,
+                (latch_input::<KeyCode>, latch_input::<MouseButton>).after(InputSystem),
+            )
+            .add_systems(
+                CustomLast,
+                (
+                    clear_latched_input::<KeyCode>,
+                    clear_latched_input::<MouseButton>,
+                ),
+            );
+    }
+}
+
+/// The state of buttons since the last fixed step, to read from the custom schedule instead of
+/// [`ButtonInput`].
+#[derive(Resource)]
+pub struct FixedInput<T: Copy + Eq + Hash + Send + Sync + 'static> {
+    pressed: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Default for FixedInput<T> {
+    fn default() -> Self {
+        Self {
+            pressed: HashSet::default(),
+            just_pressed: HashSet::default(),
+            just_released: HashSet::default(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> FixedInput<T> {
+    /// Whether the button is held, or was pressed since the last step. A tap shorter than a step
+    /// still counts as held for one step.
+    pub fn pressed(&self, input: T) -> bool {
+        self.pressed.contains(&input) || self.just_pressed.contains(&input)
+    }
+
+    /// Whether the button was pressed since the last step.
+    pub fn just_pressed(&self, input: T) -> bool {
+        self.just_pressed.contains(&input)
+    }
+
+    /// Whether the button was released since the last step.
+    pub fn just_released(&self, input: T) -> bool {
+        self.just_released.contains(&input)
+    }
+}
+
+fn latch_input<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    input: Option<Res<ButtonInput<T>>>,
+    mut fixed_input: ResMut<FixedInput<T>>,
+) {
+    let Some(input) = input else {
+        return;
+    };
+
+    let fixed_input = &mut *fixed_input;
+
+    fixed_input.pressed.clear();
+    fixed_input.pressed.extend(input.get_pressed().copied());
+    fixed_input
+        .just_pressed
+        .extend(input.get_just_pressed().copied());
+    fixed_input
+        .just_released
+        .extend(input.get_just_released().copied());
+}
+
+fn clear_latched_input<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    mut fixed_input: ResMut<FixedInput<T>>,
+) {
+    fixed_input.just_pressed.clear();
+    fixed_input.just_released.clear();
+}
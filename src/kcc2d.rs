@@ -0,0 +1,344 @@
+//! The character controller for 2D games on avian2d, e.g. platformers.
+//!
+//! It slides the same way as the 3D controller (see [`crate::slide`]), with the same tuning
+//! constants, and additionally steps up ledges up to [`MAX_STEP_HEIGHT`] high while grounded.
+//! Add avian2d's `PhysicsPlugins` in [`crate::schedule::CustomUpdate`], like avian3d's in the demo.
+
+use avian2d::{
+    math::{AdjustPrecision, AsF32, Scalar, Vector},
+    prelude::*,
+};
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{
+        GROUND_PROBE_DISTANCE, GROUND_SNAP_DISTANCE, MAX_BOUNCES, MAX_SLOPE_ANGLE, SKIN_WIDTH,
+    },
+    schedule::CustomPostUpdate,
+    slide::{clip_velocity, nudge_off_planes, SlideVector},
+};
+
+/// The tallest ledge a grounded character walks up onto instead of being blocked by.
+pub const MAX_STEP_HEIGHT: Scalar = 0.3;
+
+#[derive(SystemSet, Debug, Hash, Eq, PartialEq, Clone)]
+pub struct CharacterController2dSet;
+
+pub struct CharacterController2dPlugin;
+
+impl Plugin for CharacterController2dPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            move_character_controllers.in_set(CharacterController2dSet),
+        );
+    }
+}
+
+impl SlideVector for Vector {
+    fn dot(self, other: Self) -> Scalar {
+        Vector::dot(self, other)
+    }
+}
+
+/// A 2D character's movement state.
+#[derive(Component, Clone, Default)]
+pub struct CharacterController2d {
+    /// The horizontal speed the character is trying to move at.
+    pub velocity: f32,
+    pub vertical_velocity: f32,
+    pub last_move: MoveAndSlideOutput2d,
+    /// The ground below the character after the most recent step, if any.
+    pub ground: Option<GroundInfo2d>,
+    pub(crate) teleport: Option<Vector>,
+}
+
+impl CharacterController2d {
+    pub fn is_grounded(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    /// Moves the character to `translation` at the start of the next step, without sweeping,
+    /// and clears its velocity and ground.
+    pub fn teleport(&mut self, translation: Vector) {
+        self.teleport = Some(translation);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroundInfo2d {
+    pub entity: Entity,
+    pub point: Vector,
+    pub normal: Vector,
+    /// The gap between the bottom of the character and the ground.
+    pub distance: Scalar,
+}
+
+fn move_character_controllers(
+    mut query: Query<(
+        Entity,
+        &mut CharacterController2d,
+        &Collider,
+        &mut Position,
+        &Rotation,
+    )>,
+    spatial_query_pipeline: Res<SpatialQueryPipeline>,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    query.par_iter_mut().for_each(
+        |(entity, mut character_controller, collider, mut position, rotation)| {
+            let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+            let context = SlideContext2d {
+                spatial_query_pipeline: &spatial_query_pipeline,
+                collider,
+                rotation: rotation.as_radians(),
+                filter: &filter,
+            };
+
+            step_character_2d(
+                &mut character_controller,
+                &mut position.0,
+                &context,
+                gravity.0.f32(),
+                delta_seconds,
+            );
+        },
+    );
+}
+
+/// Runs one step of a 2D character's movement from `translation`.
+pub fn step_character_2d(
+    character_controller: &mut CharacterController2d,
+    translation: &mut Vector,
+    context: &SlideContext2d,
+    gravity: Vec2,
+    delta_seconds: f32,
+) {
+    if let Some(teleport) = character_controller.teleport.take() {
+        *translation = teleport;
+        character_controller.velocity = 0.0;
+        character_controller.vertical_velocity = 0.0;
+        character_controller.ground = None;
+    }
+
+    let was_grounded = character_controller.is_grounded();
+
+    if was_grounded && character_controller.vertical_velocity <= 0.0 {
+        character_controller.vertical_velocity = 0.0;
+    }
+
+    character_controller.vertical_velocity += gravity.y * delta_seconds;
+
+    let displacement =
+        Vector::X * (character_controller.velocity * delta_seconds).adjust_precision();
+
+    let mut output = move_and_slide_2d(context, *translation, displacement, false);
+
+    // Blocked while walking, so try stepping up onto whatever is in the way.
+    if was_grounded {
+        let moved = (output.translation.x - translation.x).abs();
+
+        if moved < displacement.x.abs() - SKIN_WIDTH {
+            if let Some(stepped) = step_up(context, *translation, displacement) {
+                if (stepped.x - translation.x).abs() > moved + SKIN_WIDTH {
+                    output.translation = stepped;
+                }
+            }
+        }
+    }
+
+    let fall = move_and_slide_2d(
+        context,
+        output.translation,
+        Vector::Y * (character_controller.vertical_velocity * delta_seconds).adjust_precision(),
+        true,
+    );
+
+    // Stop falling on landing, and stop rising on hitting a ceiling.
+    if let Some(normal) = fall.hit_normal {
+        if is_walkable_2d(normal) || normal.y < 0.0 {
+            character_controller.vertical_velocity = 0.0;
+        }
+    }
+
+    *translation = fall.translation;
+
+    let mut ground = probe_ground_2d(context, *translation, GROUND_PROBE_DISTANCE);
+
+    // Keep grounded characters on the ground when it drops away beneath them.
+    if ground.is_none() && was_grounded && character_controller.vertical_velocity <= 0.0 {
+        if let Some(snap) = probe_ground_2d(context, *translation, GROUND_SNAP_DISTANCE) {
+            translation.y -= snap.distance;
+            ground = Some(GroundInfo2d {
+                point: snap.point - Vector::Y * snap.distance,
+                distance: 0.0,
+                ..snap
+            });
+        }
+    }
+
+    character_controller.last_move = output;
+    character_controller.ground = ground;
+}
+
+/// Lifts the character by up to [`MAX_STEP_HEIGHT`], moves it by `displacement`, and puts it back
+/// down. Returns where it ends up if it lands on walkable ground.
+fn step_up(context: &SlideContext2d, translation: Vector, displacement: Vector) -> Option<Vector> {
+    let lift = context
+        .cast(translation, Dir2::Y, MAX_STEP_HEIGHT + SKIN_WIDTH)
+        .map_or(MAX_STEP_HEIGHT, |hit| {
+            (hit.time_of_impact - SKIN_WIDTH).max(0.0)
+        });
+
+    let raised = move_and_slide_2d(context, translation + Vector::Y * lift, displacement, false);
+
+    let ground = probe_ground_2d(context, raised.translation, lift + GROUND_PROBE_DISTANCE)?;
+
+    Some(raised.translation - Vector::Y * ground.distance)
+}
+
+/// Everything the solver needs that stays the same for every cast of one character's step.
+pub struct SlideContext2d<'a> {
+    pub spatial_query_pipeline: &'a SpatialQueryPipeline,
+    pub collider: &'a Collider,
+    pub rotation: Scalar,
+    pub filter: &'a SpatialQueryFilter,
+}
+
+impl SlideContext2d<'_> {
+    /// Casts the character's collider, flipping the hit normal to face against `direction`.
+    pub fn cast(
+        &self,
+        origin: Vector,
+        direction: Dir2,
+        max_time_of_impact: Scalar,
+    ) -> Option<ShapeHitData> {
+        let mut hit = self.spatial_query_pipeline.cast_shape(
+            self.collider,
+            origin,
+            self.rotation,
+            direction,
+            max_time_of_impact,
+            true,
+            self.filter.clone(),
+        )?;
+
+        if hit.normal1.dot(direction.as_vec2().adjust_precision()) > 0.0 {
+            hit.normal1 = -hit.normal1;
+        }
+
+        Some(hit)
+    }
+}
+
+/// Returns whether a surface is flat enough to stand on.
+pub fn is_walkable_2d(normal: Vector) -> bool {
+    normal.angle_between(Vector::Y).abs() <= MAX_SLOPE_ANGLE.adjust_precision()
+}
+
+/// Casts down from `translation` by up to `distance` and returns the ground if it's flat enough
+/// to stand on.
+pub fn probe_ground_2d(
+    context: &SlideContext2d,
+    translation: Vector,
+    distance: Scalar,
+) -> Option<GroundInfo2d> {
+    let hit = context.cast(translation, Dir2::NEG_Y, distance + SKIN_WIDTH)?;
+
+    if !is_walkable_2d(hit.normal1) {
+        return None;
+    }
+
+    Some(GroundInfo2d {
+        entity: hit.entity,
+        point: hit.point1,
+        normal: hit.normal1,
+        distance: (hit.time_of_impact - SKIN_WIDTH).max(0.0),
+    })
+}
+
+/// The result of [`move_and_slide_2d`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MoveAndSlideOutput2d {
+    pub translation: Vector,
+    pub bounce_count: usize,
+    /// The normal of the first surface hit, if any.
+    pub hit_normal: Option<Vector>,
+}
+
+/// Sweeps `collider` from `translation` by `displacement`, sliding along every surface it hits.
+///
+/// In the `gravity_pass`, the slide stops at the first walkable surface instead of sliding along
+/// it, so that standing on a slope doesn't slowly slide the character down it.
+pub fn move_and_slide_2d(
+    context: &SlideContext2d,
+    translation: Vector,
+    displacement: Vector,
+    gravity_pass: bool,
+) -> MoveAndSlideOutput2d {
+    let mut output = MoveAndSlideOutput2d {
+        translation,
+        ..default()
+    };
+
+    let mut direction_result = Dir2::new(displacement.f32());
+    let mut distance = displacement.length();
+
+    let Ok(start_direction) = direction_result else {
+        return output;
+    };
+
+    let mut planes = [Vector::ZERO; MAX_BOUNCES];
+    let mut plane_count = 0;
+
+    for _ in 0..MAX_BOUNCES {
+        let Ok(direction) = direction_result else {
+            break;
+        };
+
+        output.bounce_count += 1;
+
+        let direction_vector = direction.as_vec2().adjust_precision();
+
+        let Some(hit) = context.cast(output.translation, direction, distance + SKIN_WIDTH) else {
+            output.translation += direction_vector * distance;
+            break;
+        };
+
+        output.hit_normal.get_or_insert(hit.normal1);
+
+        if hit.time_of_impact >= distance {
+            output.translation += direction_vector * (hit.time_of_impact - SKIN_WIDTH).max(0.0);
+            break;
+        }
+
+        if hit.time_of_impact >= SKIN_WIDTH {
+            output.translation += direction_vector * (hit.time_of_impact - SKIN_WIDTH)
+        }
+
+        if gravity_pass && is_walkable_2d(hit.normal1) {
+            break;
+        }
+
+        let extra_distance = distance - (hit.time_of_impact - SKIN_WIDTH).max(0.0);
+        let projected_velocity = clip_velocity(direction_vector * extra_distance, hit.normal1);
+
+        if projected_velocity.dot(start_direction.as_vec2().adjust_precision()) <= 0.0 {
+            break;
+        }
+
+        let projected_velocity =
+            nudge_off_planes(projected_velocity, hit.normal1, &planes[..plane_count]);
+
+        planes[plane_count] = hit.normal1;
+        plane_count += 1;
+
+        direction_result = Dir2::new(projected_velocity.f32());
+        distance = projected_velocity.length();
+    }
+
+    output
+}
@@ -0,0 +1,88 @@
+//! Pads that launch characters when they land on or walk into them.
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet},
+    schedule::CustomPostUpdate,
+};
+
+pub struct LaunchPadPlugin;
+
+impl Plugin for LaunchPadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Launched>().add_systems(
+            CustomPostUpdate,
+            launch_characters.after(CharacterControllerSet),
+        );
+    }
+}
+
+/// Applies its velocity as an impulse to characters when they land on it, or enter it if it's a
+/// [`Sensor`] with [`CollidingEntities`]. Characters are launched once per touch, so standing on a
+/// pad with a sideways velocity doesn't keep pushing them.
+///
+/// It can be put on a collider or on the rigid body the collider belongs to.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct LaunchPad(pub Vec3);
+
+/// Sent when a [`LaunchPad`] launches a character.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct Launched {
+    pub character: Entity,
+    pub pad: Entity,
+    pub velocity: Vec3,
+}
+
+fn launch_characters(
+    mut characters: Query<(Entity, &mut CharacterController)>,
+    pads: Query<(Entity, &LaunchPad, Option<&CollidingEntities>)>,
+    collider_parents: Query<&ColliderParent>,
+    mut touching: Local<HashSet<(Entity, Entity)>>,
+    mut launched: EventWriter<Launched>,
+) {
+    let mut now_touching = HashSet::new();
+
+    for (character, character_controller) in &characters {
+        let pad = character_controller.ground.and_then(|ground| {
+            let body = collider_parents
+                .get(ground.entity)
+                .map_or(ground.entity, ColliderParent::get);
+
+            [ground.entity, body]
+                .into_iter()
+                .find(|&entity| pads.contains(entity))
+        });
+
+        if let Some(pad) = pad {
+            now_touching.insert((character, pad));
+        }
+    }
+
+    for (pad, _, colliding_entities) in &pads {
+        for &character in colliding_entities
+            .into_iter()
+            .flat_map(|entities| entities.iter())
+        {
+            if characters.contains(character) {
+                now_touching.insert((character, pad));
+            }
+        }
+    }
+
+    for &(character, pad) in now_touching.difference(&touching) {
+        let (_, launch_pad, _) = pads.get(pad).unwrap();
+        let (_, mut character_controller) = characters.get_mut(character).unwrap();
+
+        character_controller.apply_impulse(launch_pad.0);
+
+        launched.send(Launched {
+            character,
+            pad,
+            velocity: launch_pad.0,
+        });
+    }
+
+    *touching = now_touching;
+}
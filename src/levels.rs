@@ -0,0 +1,737 @@
+//! Built-in test levels for exercising specific controller behaviors, switchable at runtime.
+
+use std::{f32::consts::TAU, str::FromStr};
+
+use avian3d::{math::AdjustPrecision, prelude::*};
+use bevy::{
+    color::palettes,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::{
+    character_controller::{CharacterController, SurfaceMaterial, Traction},
+    gravity_zones::{GravityZone, ZoneGravity},
+    launch_pads::LaunchPad,
+    planet::GravitySource,
+    respawn::{Checkpoint, RespawnPoint},
+    schedule::CustomPreUpdate,
+    time_dilation::TimeDilationZone,
+    wind::Wind,
+};
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .init_resource::<SandboxScene>()
+            .init_resource::<RespawnPoint>()
+            .add_systems(Update, (switch_level, load_level).chain())
+            .add_systems(CustomPreUpdate, move_platforms);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Level {
+    #[default]
+    Sandbox,
+    Stairs,
+    Slopes,
+    MovingPlatforms,
+    Corridors,
+    SeamFloor,
+    Surfaces,
+    Planet,
+    ThinWalls,
+    IceLake,
+}
+
+impl Level {
+    pub const ALL: [Level; 10] = [
+        Level::Sandbox,
+        Level::Stairs,
+        Level::Slopes,
+        Level::MovingPlatforms,
+        Level::Corridors,
+        Level::SeamFloor,
+        Level::Surfaces,
+        Level::Planet,
+        Level::ThinWalls,
+        Level::IceLake,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Sandbox => "sandbox",
+            Level::Stairs => "stairs",
+            Level::Slopes => "slopes",
+            Level::MovingPlatforms => "moving-platforms",
+            Level::Corridors => "corridors",
+            Level::SeamFloor => "seam-floor",
+            Level::Surfaces => "surfaces",
+            Level::Planet => "planet",
+            Level::ThinWalls => "thin-walls",
+            Level::IceLake => "ice-lake",
+        }
+    }
+
+    /// Where the character is placed when the level is loaded.
+    pub fn spawn_point(self) -> Vec3 {
+        match self {
+            Level::Planet => PLANET_CENTER + Vec3::Y * (PLANET_RADIUS + 1.05),
+            _ => Vec3::new(0.0, 1.05, 0.0),
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Level::ALL.iter().map(|level| level.name()).collect();
+                format!("unknown level `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+/// The planet of [`Level::Planet`] floats above the kill plane, so walking around to its underside
+/// doesn't respawn the character.
+const PLANET_CENTER: Vec3 = Vec3::new(0.0, 30.0, 0.0);
+const PLANET_RADIUS: f32 = 15.0;
+
+/// The level that is loaded. Changing it despawns the current level and spawns the new one.
+#[derive(Resource, Default)]
+pub struct CurrentLevel(pub Level);
+
+/// The glTF scene used by [`Level::Sandbox`], relative to the assets folder.
+#[derive(Resource)]
+pub struct SandboxScene(pub String);
+
+impl Default for SandboxScene {
+    fn default() -> Self {
+        Self("levels/sandbox.gltf".to_string())
+    }
+}
+
+/// Marks the root entities of the loaded level.
+#[derive(Component)]
+pub struct LevelEntity;
+
+/// A kinematic platform that oscillates around its starting position.
+#[derive(Component)]
+pub struct MovingPlatform {
+    pub origin: Vec3,
+    pub offset: Vec3,
+    pub period: f32,
+}
+
+fn switch_level(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    const KEYS: [KeyCode; 10] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+        KeyCode::Digit0,
+    ];
+
+    for (key, level) in KEYS.into_iter().zip(Level::ALL) {
+        if keyboard_input.just_pressed(key) && current_level.0 != level {
+            current_level.0 = level;
+        }
+    }
+}
+
+fn load_level(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    sandbox_scene: Res<SandboxScene>,
+    mut respawn_point: ResMut<RespawnPoint>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+    mut characters: Query<&mut CharacterController>,
+) {
+    if !current_level.is_changed() {
+        return;
+    }
+
+    for entity in &level_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    respawn_point.0 = current_level.0.spawn_point();
+
+    for mut character_controller in &mut characters {
+        respawn_point.respawn(&mut character_controller);
+    }
+
+    let mut builder = LevelBuilder {
+        floor_material: materials.add(Color::Srgba(palettes::css::DARK_GRAY)),
+        material: materials.add(Color::Srgba(palettes::css::BLACK)),
+        checkpoint_material: materials.add(StandardMaterial {
+            base_color: Color::Srgba(palettes::css::LIME.with_alpha(0.3)),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        commands: &mut commands,
+        meshes: &mut meshes,
+    };
+
+    match current_level.0 {
+        Level::Sandbox => {
+            builder.floor(100.0);
+
+            // Every mesh in the level scene gets a static trimesh collider. Individual meshes can
+            // be given convex hulls instead with `with_constructor_for_name`.
+            builder.commands.spawn((
+                LevelEntity,
+                RigidBody::Static,
+                ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+                SceneBundle {
+                    scene: asset_server
+                        .load(GltfAssetLabel::Scene(0).from_asset(sandbox_scene.0.clone())),
+                    ..default()
+                },
+            ));
+
+            let terrain = grid_mesh(32, 30.0, |x, z| (x * 0.4).sin() * (z * 0.3).cos() * 1.5);
+
+            builder.commands.spawn((
+                LevelEntity,
+                RigidBody::Static,
+                Collider::trimesh_from_mesh(&terrain).unwrap(),
+                PbrBundle {
+                    mesh: builder.meshes.add(terrain),
+                    transform: Transform::from_xyz(30.0, -1.0, -30.0),
+                    material: materials.add(Color::Srgba(palettes::css::DARK_OLIVEGREEN)),
+                    ..default()
+                },
+            ));
+        }
+        Level::Stairs => {
+            builder.floor(60.0);
+
+            // Flights with increasingly tall steps, side by side.
+            for (i, rise) in [0.1, 0.2, 0.3, 0.5].into_iter().enumerate() {
+                let x = -9.0 + i as f32 * 6.0;
+
+                for step in 0..10 {
+                    let height = rise * (step + 1) as f32;
+
+                    builder.block(
+                        Vec3::new(x, height / 2.0, -5.0 - 0.5 * step as f32),
+                        Quat::IDENTITY,
+                        Vec3::new(4.0, height, 0.5),
+                    );
+                }
+
+                builder.checkpoint(
+                    Vec3::new(x, rise * 10.0 + 1.5, -9.5),
+                    Vec3::new(4.0, 3.0, 0.5),
+                );
+            }
+        }
+        Level::Slopes => {
+            builder.floor(80.0);
+
+            // Ramps of increasing steepness, all reaching the same height.
+            for (i, degrees) in [15.0_f32, 30.0, 40.0, 45.0, 50.0, 60.0]
+                .into_iter()
+                .enumerate()
+            {
+                let angle = degrees.to_radians();
+                let length = 4.0 / angle.sin();
+                let x = -15.0 + i as f32 * 6.0;
+
+                builder.block(
+                    Vec3::new(
+                        x,
+                        2.0 - 0.25 * angle.cos(),
+                        -5.0 - length * angle.cos() / 2.0,
+                    ),
+                    Quat::from_rotation_x(angle),
+                    Vec3::new(4.0, 0.5, length),
+                );
+            }
+        }
+        Level::MovingPlatforms => {
+            builder.floor(60.0);
+
+            builder
+                .block(
+                    Vec3::new(-5.0, 0.5, -8.0),
+                    Quat::IDENTITY,
+                    Vec3::new(4.0, 0.5, 4.0),
+                )
+                .insert((
+                    RigidBody::Kinematic,
+                    MovingPlatform {
+                        origin: Vec3::new(-5.0, 0.5, -8.0),
+                        offset: Vec3::new(8.0, 0.0, 0.0),
+                        period: 6.0,
+                    },
+                ));
+
+            builder
+                .block(
+                    Vec3::new(5.0, 0.25, -8.0),
+                    Quat::IDENTITY,
+                    Vec3::new(4.0, 0.5, 4.0),
+                )
+                .insert((
+                    RigidBody::Kinematic,
+                    MovingPlatform {
+                        origin: Vec3::new(5.0, 0.25, -8.0),
+                        offset: Vec3::new(0.0, 6.0, 0.0),
+                        period: 4.0,
+                    },
+                ));
+
+            // A ledge that can only be reached by riding the lift.
+            builder.block(
+                Vec3::new(5.0, 3.0, -14.0),
+                Quat::IDENTITY,
+                Vec3::new(4.0, 6.0, 8.0),
+            );
+            builder.checkpoint(Vec3::new(5.0, 7.5, -14.0), Vec3::new(4.0, 3.0, 8.0));
+        }
+        Level::Corridors => {
+            builder.floor(60.0);
+
+            // Straight corridors that are roomy, a tight fit, and too narrow for the character.
+            for (i, width) in [2.0, 1.05, 0.95].into_iter().enumerate() {
+                let x = -10.0 + i as f32 * 6.0;
+
+                for side in [-1.0, 1.0] {
+                    builder.block(
+                        Vec3::new(x + side * (width / 2.0 + 0.25), 1.5, -10.0),
+                        Quat::IDENTITY,
+                        Vec3::new(0.5, 3.0, 12.0),
+                    );
+                }
+            }
+
+            // A zigzag corridor, which has acute corners on both sides.
+            for i in 0..6 {
+                let angle = if i % 2 == 0 { 0.6 } else { -0.6 };
+                let z = -5.0 - i as f32 * 2.5;
+
+                for side in [-1.0, 1.0] {
+                    builder.block(
+                        Vec3::new(10.0 + side * 1.0, 1.5, z),
+                        Quat::from_rotation_y(angle),
+                        Vec3::new(0.25, 3.0, 3.0),
+                    );
+                }
+            }
+
+            // A wind tunnel blowing back down the roomy corridor, which slows walking into it.
+            let wind_size = Vec3::new(2.0, 3.0, 12.0).adjust_precision();
+
+            builder.commands.spawn((
+                LevelEntity,
+                Wind {
+                    acceleration: Vec3::Z * 40.0,
+                },
+                RigidBody::Static,
+                Sensor,
+                Collider::cuboid(wind_size.x, wind_size.y, wind_size.z),
+                CollidingEntities::default(),
+                TransformBundle::from_transform(Transform::from_xyz(-10.0, 1.5, -10.0)),
+            ));
+
+            // A corridor with gravity pulling up towards its ceiling, and a low gravity room.
+            builder.block(
+                Vec3::new(20.0, 5.5, -10.0),
+                Quat::IDENTITY,
+                Vec3::new(4.0, 1.0, 12.0),
+            );
+            builder.gravity_zone(
+                Vec3::new(20.0, 2.5, -10.0),
+                Vec3::new(4.0, 5.0, 12.0),
+                ZoneGravity::Replace(Vec3::Y * 9.81),
+            );
+            builder.gravity_zone(
+                Vec3::new(20.0, 4.0, 8.0),
+                Vec3::new(8.0, 8.0, 8.0),
+                ZoneGravity::Scale(0.3),
+            );
+        }
+        Level::SeamFloor => {
+            // A floor made of separate tiles, then a sloped tiled ramp, then a flat trimesh.
+            for x in -5..5 {
+                for z in -10..10 {
+                    builder.block(
+                        Vec3::new(x as f32 + 0.5, -0.5, z as f32 + 0.5),
+                        Quat::IDENTITY,
+                        Vec3::ONE,
+                    );
+                }
+            }
+
+            let angle = 10.0_f32.to_radians();
+
+            for i in 0..10 {
+                let along = i as f32 + 0.5;
+
+                builder.block(
+                    Vec3::new(
+                        -10.0,
+                        along * angle.sin() - 0.5 * angle.cos(),
+                        -along * angle.cos() - 0.5 * angle.sin(),
+                    ),
+                    Quat::from_rotation_x(angle),
+                    Vec3::new(4.0, 1.0, 1.0),
+                );
+            }
+
+            let floor = grid_mesh(20, 20.0, |_, _| 0.0);
+
+            builder.commands.spawn((
+                LevelEntity,
+                RigidBody::Static,
+                Collider::trimesh_from_mesh(&floor).unwrap(),
+                PbrBundle {
+                    mesh: builder.meshes.add(floor),
+                    transform: Transform::from_xyz(20.0, 0.0, 0.0),
+                    material: builder.floor_material.clone(),
+                    ..default()
+                },
+            ));
+        }
+        Level::Surfaces => {
+            builder.floor(60.0);
+
+            // Patches that are flush with the floor, so the character walks straight onto them.
+            let ice_material = materials.add(Color::Srgba(palettes::css::LIGHT_CYAN));
+            let mud_material = materials.add(Color::Srgba(palettes::css::SADDLE_BROWN));
+
+            builder
+                .block_with_material(
+                    Vec3::new(-6.0, -0.495, -10.0),
+                    Quat::IDENTITY,
+                    Vec3::new(8.0, 1.0, 12.0),
+                    ice_material.clone(),
+                )
+                .insert(SurfaceMaterial::ICE);
+
+            builder
+                .block_with_material(
+                    Vec3::new(6.0, -0.495, -10.0),
+                    Quat::IDENTITY,
+                    Vec3::new(8.0, 1.0, 12.0),
+                    mud_material,
+                )
+                .insert(SurfaceMaterial::MUD);
+
+            // An icy ramp leading up to a wall, for sliding along walls with momentum.
+            let angle = 15.0_f32.to_radians();
+
+            builder
+                .block_with_material(
+                    Vec3::new(-6.0, 1.0, -22.0),
+                    Quat::from_rotation_x(angle),
+                    Vec3::new(8.0, 0.5, 10.0),
+                    ice_material,
+                )
+                .insert(SurfaceMaterial::ICE);
+
+            builder.block(
+                Vec3::new(-10.25, 2.0, -22.0),
+                Quat::IDENTITY,
+                Vec3::new(0.5, 4.0, 10.0),
+            );
+
+            // A launch pad beside a ledge that's too high to jump onto, to steer onto mid-air.
+            let launch_pad_material = materials.add(Color::Srgba(palettes::css::ORANGE));
+
+            builder
+                .block_with_material(
+                    Vec3::new(10.0, -0.495, 10.0),
+                    Quat::IDENTITY,
+                    Vec3::new(3.0, 1.0, 3.0),
+                    launch_pad_material,
+                )
+                .insert(LaunchPad(Vec3::Y * 12.0));
+
+            builder.block(
+                Vec3::new(16.0, 2.5, 10.0),
+                Quat::IDENTITY,
+                Vec3::new(6.0, 5.0, 6.0),
+            );
+
+            // A bullet time bubble around the top of the launch, so coyote time and the jump buffer
+            // can be tried in slow motion.
+            builder.commands.spawn((
+                LevelEntity,
+                TimeDilationZone { time_scale: 0.25 },
+                RigidBody::Static,
+                Sensor,
+                Collider::sphere(3.0),
+                CollidingEntities::default(),
+                TransformBundle::from_transform(Transform::from_xyz(10.0, 6.0, 10.0)),
+            ));
+        }
+        Level::ThinWalls => {
+            builder.floor(200.0);
+
+            // Launch pads that fire the character sideways at increasing speeds into walls of
+            // decreasing thickness, which it should stop at rather than pass through.
+            let launch_pad_material = materials.add(Color::Srgba(palettes::css::ORANGE));
+
+            for (i, (speed, thickness)) in [(50.0, 0.1), (200.0, 0.02), (1000.0, 0.005)]
+                .into_iter()
+                .enumerate()
+            {
+                let x = -10.0 + i as f32 * 10.0;
+
+                builder
+                    .block_with_material(
+                        Vec3::new(x, -0.495, 0.0),
+                        Quat::IDENTITY,
+                        Vec3::new(3.0, 1.0, 3.0),
+                        launch_pad_material.clone(),
+                    )
+                    .insert(LaunchPad(Vec3::new(0.0, 2.0, -speed)));
+
+                builder.block(
+                    Vec3::new(x, 2.0, -20.0),
+                    Quat::IDENTITY,
+                    Vec3::new(6.0, 4.0, thickness),
+                );
+            }
+        }
+        Level::IceLake => {
+            builder.floor(100.0);
+
+            // A frozen lake that's flush with the floor, with a patch of slicker, polished ice in
+            // the middle and a gritty, snow-covered strip along its far shore.
+            let ice_material = materials.add(Color::Srgba(palettes::css::LIGHT_CYAN));
+            let polished_material = materials.add(Color::Srgba(palettes::css::AQUAMARINE));
+            let snow_material = materials.add(Color::Srgba(palettes::css::WHITE_SMOKE));
+
+            builder
+                .block_with_material(
+                    Vec3::new(0.0, -0.495, -20.0),
+                    Quat::IDENTITY,
+                    Vec3::new(30.0, 1.0, 30.0),
+                    ice_material,
+                )
+                .insert(SurfaceMaterial::ICE);
+
+            builder
+                .block_with_material(
+                    Vec3::new(0.0, -0.49, -20.0),
+                    Quat::IDENTITY,
+                    Vec3::new(10.0, 1.0, 10.0),
+                    polished_material,
+                )
+                .insert((SurfaceMaterial::ICE, Traction(0.2)));
+
+            builder
+                .block_with_material(
+                    Vec3::new(0.0, -0.49, -32.0),
+                    Quat::IDENTITY,
+                    Vec3::new(30.0, 1.0, 4.0),
+                    snow_material,
+                )
+                .insert((SurfaceMaterial::ICE, Traction(4.0)));
+
+            // Rocks frozen into the lake, to slide into and around.
+            for x in [-9.0, 9.0] {
+                builder.block(
+                    Vec3::new(x, 0.5, -20.0),
+                    Quat::from_rotation_y(0.6),
+                    Vec3::new(2.0, 1.0, 2.0),
+                );
+            }
+        }
+        Level::Planet => {
+            builder.commands.spawn((
+                LevelEntity,
+                RigidBody::Static,
+                Collider::sphere(PLANET_RADIUS.adjust_precision()),
+                GravitySource {
+                    strength: 9.81,
+                    radius: PLANET_RADIUS * 3.0,
+                },
+                PbrBundle {
+                    mesh: builder
+                        .meshes
+                        .add(Sphere::new(PLANET_RADIUS).mesh().ico(5).unwrap()),
+                    transform: Transform::from_translation(PLANET_CENTER),
+                    material: materials.add(Color::Srgba(palettes::css::DARK_OLIVEGREEN)),
+                    ..default()
+                },
+            ));
+
+            // Blocks standing on the surface all around the planet, to walk into and climb.
+            for i in 0..12 {
+                let rotation = Quat::from_rotation_z(i as f32 / 12.0 * TAU)
+                    * Quat::from_rotation_x(0.4 * (i % 3) as f32);
+                let up = rotation * Vec3::Y;
+
+                builder.block(
+                    PLANET_CENTER + up * PLANET_RADIUS,
+                    rotation,
+                    Vec3::new(2.0, 1.0, 2.0),
+                );
+            }
+        }
+    }
+}
+
+struct LevelBuilder<'a, 'w, 's> {
+    commands: &'a mut Commands<'w, 's>,
+    meshes: &'a mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+    floor_material: Handle<StandardMaterial>,
+    checkpoint_material: Handle<StandardMaterial>,
+}
+
+impl LevelBuilder<'_, '_, '_> {
+    fn block(&mut self, translation: Vec3, rotation: Quat, size: Vec3) -> EntityCommands<'_> {
+        let material = self.material.clone();
+        self.block_with_material(translation, rotation, size, material)
+    }
+
+    fn block_with_material(
+        &mut self,
+        translation: Vec3,
+        rotation: Quat,
+        size: Vec3,
+        material: Handle<StandardMaterial>,
+    ) -> EntityCommands<'_> {
+        let extents = size.adjust_precision();
+
+        self.commands.spawn((
+            LevelEntity,
+            RigidBody::Static,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            PbrBundle {
+                mesh: self.meshes.add(Cuboid::from_size(size)),
+                transform: Transform::from_translation(translation).with_rotation(rotation),
+                material,
+                ..default()
+            },
+        ))
+    }
+
+    fn floor(&mut self, size: f32) {
+        let material = self.floor_material.clone();
+
+        self.block_with_material(
+            Vec3::new(0.0, -0.5, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(size, 1.0, size),
+            material,
+        );
+    }
+
+    fn gravity_zone(&mut self, translation: Vec3, size: Vec3, gravity: ZoneGravity) {
+        let extents = size.adjust_precision();
+
+        self.commands.spawn((
+            LevelEntity,
+            GravityZone {
+                gravity,
+                priority: 0,
+            },
+            RigidBody::Static,
+            Sensor,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            CollidingEntities::default(),
+            TransformBundle::from_transform(Transform::from_translation(translation)),
+        ));
+    }
+
+    /// A checkpoint volume whose respawn point is at the bottom of the volume.
+    fn checkpoint(&mut self, translation: Vec3, size: Vec3) -> EntityCommands<'_> {
+        let extents = size.adjust_precision();
+
+        self.commands.spawn((
+            LevelEntity,
+            Checkpoint {
+                spawn_point: translation - Vec3::Y * (size.y / 2.0 - 1.05),
+            },
+            RigidBody::Static,
+            Sensor,
+            Collider::cuboid(extents.x, extents.y, extents.z),
+            CollidingEntities::default(),
+            PbrBundle {
+                mesh: self.meshes.add(Cuboid::from_size(size)),
+                transform: Transform::from_translation(translation),
+                material: self.checkpoint_material.clone(),
+                ..default()
+            },
+        ))
+    }
+}
+
+/// A square grid of triangles centered on the origin, with heights given by `height(x, z)`.
+fn grid_mesh(cells: u32, size: f32, height: impl Fn(f32, f32) -> f32) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for z in 0..=cells {
+        for x in 0..=cells {
+            let x = (x as f32 / cells as f32 - 0.5) * size;
+            let z = (z as f32 / cells as f32 - 0.5) * size;
+
+            positions.push([x, height(x, z), z]);
+        }
+    }
+
+    for z in 0..cells {
+        for x in 0..cells {
+            let i = z * (cells + 1) + x;
+
+            indices.extend([i, i + cells + 1, i + 1]);
+            indices.extend([i + 1, i + cells + 1, i + cells + 2]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+    .with_computed_normals()
+}
+
+fn move_platforms(
+    time: Res<Time>,
+    mut query: Query<(&MovingPlatform, &Transform, &mut LinearVelocity)>,
+) {
+    if time.delta_seconds() == 0.0 {
+        return;
+    }
+
+    for (platform, transform, mut linear_velocity) in &mut query {
+        // Aim for where the platform should be at the end of this step.
+        let phase = (time.elapsed_seconds() + time.delta_seconds()) / platform.period * TAU;
+        let target = platform.origin + platform.offset * phase.sin();
+
+        linear_velocity.0 =
+            ((target - transform.translation) / time.delta_seconds()).adjust_precision();
+    }
+}
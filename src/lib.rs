@@ -0,0 +1,61 @@
+#[cfg(all(feature = "f32", feature = "f64"))]
+compile_error!(
+    "the `f32` and `f64` features can't be enabled together, build with \
+     `--no-default-features --features f64` for double precision"
+);
+
+/// Prints to the debug text overlay with the `example-tools` feature, and does nothing without it.
+#[cfg(feature = "example-tools")]
+macro_rules! screen_print {
+    ($($arg:tt)*) => {
+        bevy_debug_text_overlay::screen_print!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "example-tools"))]
+macro_rules! screen_print {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format!($($arg)*);
+        }
+    };
+}
+
+pub mod actions;
+pub mod animation;
+pub mod backend;
+pub mod camera;
+pub mod character_controller;
+pub mod config_asset;
+pub mod footsteps;
+pub mod gravity_zones;
+pub mod input;
+#[cfg(feature = "kcc2d")]
+pub mod kcc2d;
+pub mod launch_pads;
+pub mod levels;
+pub mod mounts;
+#[cfg(feature = "navigation")]
+pub mod navigation;
+pub mod pause;
+pub mod planet;
+pub mod platforms;
+#[cfg(feature = "tuning")]
+pub mod plots;
+pub mod prelude;
+pub mod replay;
+#[cfg(feature = "replicon")]
+pub mod replication;
+pub mod respawn;
+pub mod scenario;
+pub mod schedule;
+pub mod slide;
+pub mod snapshot;
+pub mod solver_trace;
+pub mod telemetry;
+pub mod testing;
+pub mod time_dilation;
+#[cfg(feature = "tuning")]
+pub mod tuning;
+pub mod water;
+pub mod wind;
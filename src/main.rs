@@ -1,90 +1,401 @@
-mod camera;
-mod character_controller;
-mod schedule;
-
-use std::{
-    collections::HashMap,
-    fs::{self, OpenOptions},
-    io::Write,
-    path::{Path, PathBuf},
-};
+use std::{collections::BTreeMap, ops::Range};
 
-use avian3d::prelude::*;
-use bevy::{color::palettes, prelude::*};
+use avian3d::{math::AsF32, prelude::*};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::SystemTime};
 use bevy_atmosphere::prelude::*;
 use bevy_debug_text_overlay::{screen_print, OverlayPlugin};
 use clap::Parser;
-use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    camera::{CameraPlugin, CameraRotation},
-    character_controller::{
-        CharacterController, CharacterControllerPlugin, CharacterControllerSet,
+use souls::{
+    levels::{CurrentLevel, Level, LevelsPlugin, SandboxScene},
+    prelude::*,
+    replay::{
+        config_checksum, frames_to_csv, read_replay_stream, to_canonical_ron, Divergence,
+        FrameRecord, ReplayMetadata, ReplayRng, ReplayStore, ReplayStream,
     },
-    light_consts::lux::AMBIENT_DAYLIGHT,
-    schedule::{step_custom_schedule, CustomPreUpdate, CustomUpdate, SchedulePlugin},
+    schedule::{step_custom_schedule, TIMESTEP},
 };
 
+use crate::light_consts::lux::AMBIENT_DAYLIGHT;
+
 const PLAYER_SPEED: f32 = 15.0;
+/// Where recordings are streamed to while they're recorded, so a crash doesn't lose them. Streams
+/// play back like recordings saved on exit.
+const STREAM_NAME: &str = "out.stream";
+/// How many frames are appended to the stream at a time, about a second's worth.
+const STREAM_CHUNK_FRAMES: usize = 64;
+/// Autosaved checkpoints alternate between these, so one survives the app being killed mid-save.
+const CHECKPOINT_NAMES: [&str; 2] = ["checkpoint-0.ron", "checkpoint-1.ron"];
+/// Plays back the most recently saved checkpoint when passed as the recording to play back.
+const LATEST_CHECKPOINT: &str = "latest";
+
+#[derive(Resource, Default)]
+struct FrameCount(u32);
+
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+struct RecordedVelocities(BTreeMap<u32, Vec3>);
+
+/// The movement keys' direction, relative to the camera, of every frame any were held.
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+struct RecordedDirections(BTreeMap<u32, Vec3>);
+
+/// The [`MouseLook`] delta of every frame the mouse moved in.
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+struct RecordedMouseLook(BTreeMap<u32, Vec2>);
 
+/// The solver's inputs and outputs of every frame, to verify playback against.
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+struct RecordedFrames(BTreeMap<u32, FrameRecord>);
+
+/// The frames of the recording the ghost follows, if any.
+#[derive(Resource, Default)]
+struct GhostFrames(BTreeMap<u32, FrameRecord>);
+
+/// A translucent character without a collider, which follows the positions recorded in
+/// [`GhostFrames`] while the player plays normally.
+#[derive(Component)]
+struct Ghost;
+
+/// The metadata of the recording being played back, if any.
 #[derive(Resource)]
-struct CustomStepping {
-    enabled: bool,
+struct PlaybackMetadata(Option<ReplayMetadata>);
+
+/// The player's state going into the current frame's step.
+#[derive(Resource, Default)]
+struct FrameInput(Option<CharacterSnapshot>);
+
+/// What's saved to and loaded from the [`ReplayStore`].
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    metadata: ReplayMetadata,
+    velocities: RecordedVelocities,
+    #[serde(default)]
+    directions: RecordedDirections,
+    #[serde(default)]
+    mouse_look: RecordedMouseLook,
+    #[serde(default)]
+    frames: RecordedFrames,
+    /// When this was saved as a checkpoint, in milliseconds since the Unix epoch, to find the
+    /// latest one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    saved_at: Option<u64>,
 }
 
+/// The recording in progress.
+#[derive(SystemParam)]
+struct Recorded<'w> {
+    velocities: Res<'w, RecordedVelocities>,
+    directions: Res<'w, RecordedDirections>,
+    mouse_look: Res<'w, RecordedMouseLook>,
+    frames: Res<'w, RecordedFrames>,
+}
+
+impl Recorded<'_> {
+    fn recording(&self, metadata: ReplayMetadata) -> Recording {
+        Recording {
+            metadata,
+            velocities: self.velocities.clone(),
+            directions: self.directions.clone(),
+            mouse_look: self.mouse_look.clone(),
+            frames: self.frames.clone(),
+            saved_at: None,
+        }
+    }
+}
+
+impl Recording {
+    /// Rebuilds a recording from the frames streamed to [`STREAM_NAME`].
+    fn from_stream(contents: &str) -> Result<Self, String> {
+        let (metadata, streamed_frames) = read_replay_stream::<StreamedFrame>(contents)?;
+
+        let mut recording = Recording {
+            metadata,
+            velocities: RecordedVelocities::default(),
+            directions: RecordedDirections::default(),
+            mouse_look: RecordedMouseLook::default(),
+            frames: RecordedFrames::default(),
+            saved_at: None,
+        };
+
+        for streamed in streamed_frames {
+            let frame = streamed.frame;
+
+            if let Some(velocity) = streamed.velocity {
+                recording.velocities.0.insert(frame, velocity);
+            }
+            if let Some(direction) = streamed.direction {
+                recording.directions.0.insert(frame, direction);
+            }
+            if let Some(delta) = streamed.mouse_look {
+                recording.mouse_look.0.insert(frame, delta);
+            }
+            if let Some(record) = streamed.record {
+                recording.frames.0.insert(frame, record);
+            }
+        }
+
+        Ok(recording)
+    }
+}
+
+/// Everything recorded in one frame, as streamed to [`STREAM_NAME`].
+#[derive(Serialize, Deserialize)]
+struct StreamedFrame {
+    frame: u32,
+    velocity: Option<Vec3>,
+    direction: Option<Vec3>,
+    mouse_look: Option<Vec2>,
+    record: Option<FrameRecord>,
+}
+
+/// The stream of the recording in progress, once its first frame has been recorded.
 #[derive(Resource, Default)]
-struct FrameCount(u32);
+struct RecordingStream(Option<ReplayStream<StreamedFrame>>);
+
+/// Loads a recording saved on exit, one streamed to [`STREAM_NAME`], or the latest checkpoint.
+fn load_recording(replay_store: &ReplayStore, name: &str) -> Result<Recording, String> {
+    if name == LATEST_CHECKPOINT {
+        return CHECKPOINT_NAMES
+            .iter()
+            .filter_map(|name| load_recording(replay_store, name).ok())
+            .max_by_key(|recording| recording.saved_at)
+            .ok_or_else(|| "there are no checkpoints to play back".to_string());
+    }
 
-#[derive(Resource, Default, Serialize, Deserialize)]
-struct RecordedVelocities(HashMap<u32, Vec3>);
+    let contents = replay_store.0.load(name)?;
+
+    if name.ends_with(".stream") {
+        Recording::from_stream(&contents)
+    } else {
+        ron::de::from_str(&contents).map_err(|e| format!("{name}: {e}"))
+    }
+}
 
 #[derive(Parser, Resource)]
 struct Cli {
+    /// A recording to play back, or `latest` for the latest autosaved checkpoint. On the web,
+    /// recordings are kept in the browser's storage.
     #[arg(short)]
-    playback: Option<PathBuf>,
+    playback: Option<String>,
+    /// Saves a checkpoint of the recording every this many seconds, so it isn't lost if the app is
+    /// killed. 0 disables it. Saving on the web downloads the recording, so it's native only.
+    #[arg(long, default_value_t = 30.0)]
+    autosave: f32,
+    /// The level to start in. Levels can also be switched at runtime with the number keys.
+    #[arg(long, default_value = "sandbox")]
+    level: Level,
+    /// A glTF scene to use for the sandbox level, relative to the assets folder.
+    #[arg(long, default_value = "levels/sandbox.gltf")]
+    scene: String,
+    /// Plays back a recording even if it was recorded in a different level, timestep, or config,
+    /// which usually desyncs it.
+    #[arg(long)]
+    ignore_mismatch: bool,
+    /// Checks every frame of the playback against the recording, and on the first one that
+    /// differs, dumps both versions of it to `divergence-<frame>.ron` and exits.
+    #[arg(long, requires = "playback")]
+    verify: bool,
+    /// Exports the frames of the played back recording to this CSV file, then exits.
+    #[arg(long, requires = "playback")]
+    csv: Option<String>,
+    /// A recording to show as a ghost running alongside the player, to compare against.
+    #[arg(long)]
+    ghost: Option<String>,
+    /// Traces the solver's bounces in a range of frames, e.g. `120..180`, and saves them to
+    /// `trace-<start>.ron` once the range ends. Useful with a playback of the recording to debug.
+    #[arg(long, value_parser = parse_frame_range)]
+    trace: Option<Range<u32>>,
+}
+
+fn parse_frame_range(range: &str) -> Result<Range<u32>, String> {
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, got `{range}`"))?;
+    let parse = |frame: &str| frame.parse::<u32>().map_err(|e| format!("`{frame}`: {e}"));
+
+    Ok(parse(start)?..parse(end)?)
 }
 
 fn main() -> AppExit {
     let args = Cli::parse();
 
-    let recorded_velocities = match &args.playback {
-        Some(playback_path) => {
-            ron::de::from_str(&fs::read_to_string(playback_path).unwrap()).unwrap()
+    let replay_store = ReplayStore::default();
+
+    let mut playback_metadata = None;
+
+    let (recorded_velocities, recorded_directions, recorded_mouse_look, recorded_frames, rng) =
+        match &args.playback {
+            Some(playback_name) => {
+                let recording = load_recording(&replay_store, playback_name).unwrap();
+
+                if (args.verify || args.csv.is_some()) && recording.frames.0.is_empty() {
+                    eprintln!("{playback_name} has no recorded frames");
+                    return AppExit::error();
+                }
+
+                // Exporting doesn't play anything back, so it doesn't care about mismatches.
+                if let Some(csv_name) = &args.csv {
+                    let frames = recording
+                        .frames
+                        .0
+                        .iter()
+                        .map(|(&frame, record)| (frame, record));
+
+                    return match replay_store.0.save(csv_name, &frames_to_csv(frames)) {
+                        Ok(()) => AppExit::Success,
+                        Err(error) => {
+                            eprintln!("couldn't export {playback_name}: {error}");
+                            AppExit::error()
+                        }
+                    };
+                }
+
+                let mismatches = recording
+                    .metadata
+                    .mismatches(&startup_metadata(&args, &recording.metadata));
+
+                if !mismatches.is_empty() {
+                    eprintln!("{playback_name} doesn't match how it's being played back:");
+
+                    for mismatch in &mismatches {
+                        eprintln!("  {mismatch}");
+                    }
+
+                    if !args.ignore_mismatch {
+                        eprintln!("pass --ignore-mismatch to play it back anyway");
+                        return AppExit::error();
+                    }
+                }
+
+                playback_metadata = Some(recording.metadata.clone());
+
+                (
+                    recording.velocities,
+                    recording.directions,
+                    recording.mouse_look,
+                    recording.frames,
+                    ReplayRng::new(recording.metadata.seed),
+                )
+            }
+            None => (
+                RecordedVelocities::default(),
+                RecordedDirections::default(),
+                RecordedMouseLook::default(),
+                RecordedFrames::default(),
+                ReplayRng::from_time(),
+            ),
+        };
+
+    let ghost_frames = match &args.ghost {
+        Some(ghost_name) => {
+            let recording = load_recording(&replay_store, ghost_name).unwrap();
+
+            if recording.frames.0.is_empty() {
+                eprintln!("{ghost_name} has no frames for the ghost to follow");
+                return AppExit::error();
+            }
+
+            // The ghost doesn't desync, it just shows a different run, so this only warns.
+            for mismatch in recording
+                .metadata
+                .mismatches(&startup_metadata(&args, &recording.metadata))
+            {
+                eprintln!("ghost {ghost_name} was recorded differently, {mismatch}");
+            }
+
+            recording.frames.0
         }
-        None => RecordedVelocities::default(),
+        None => BTreeMap::new(),
     };
 
-    App::new()
-        .add_plugins((
-            DefaultPlugins,
-            AtmospherePlugin,
-            PhysicsPlugins::new(CustomUpdate),
-            SchedulePlugin,
-            CameraPlugin,
-            CharacterControllerPlugin,
-            OverlayPlugin {
-                font_size: 24.0,
-                ..default()
-            },
-        ))
-        .init_resource::<FrameCount>()
-        .insert_resource(recorded_velocities)
-        .insert_resource(CustomStepping {
-            enabled: args.playback.is_some(),
-        })
-        .insert_resource(args)
-        .init_resource::<AtmosphereModel>()
-        .add_systems(Startup, (setup_level, setup_character, setup_sun))
-        .add_systems(
-            CustomPreUpdate,
-            (increment_frame, set_velocity)
+    let mut app = App::new();
+
+    app.add_plugins((
+        DefaultPlugins,
+        AtmospherePlugin,
+        PhysicsPlugins::new(CustomUpdate),
+        (SchedulePlugin, FixedInputPlugin),
+        CameraPlugin,
+        CharacterControllerPlugin,
+        LevelsPlugin,
+        RespawnPlugin,
+        FootstepPlugin,
+        CharacterAnimationPlugin,
+        PlanetPlugin,
+        LaunchPadPlugin,
+        WindPlugin,
+        (
+            GravityZonePlugin,
+            TimeDilationPlugin,
+            KccConfigPlugin,
+            SolverTracePlugin,
+            SolverTelemetryPlugin,
+        ),
+        OverlayPlugin {
+            font_size: 24.0,
+            ..default()
+        },
+    ))
+    .init_resource::<FrameCount>()
+    .insert_resource(recorded_velocities)
+    .insert_resource(recorded_directions)
+    .insert_resource(recorded_mouse_look)
+    .insert_resource(recorded_frames)
+    .init_resource::<FrameInput>()
+    .init_resource::<RecordingStream>()
+    .insert_resource(GhostFrames(ghost_frames))
+    .insert_resource(PlaybackMetadata(playback_metadata))
+    .insert_resource(rng)
+    .insert_resource(replay_store)
+    .insert_resource(CustomStepping {
+        enabled: args.playback.is_some(),
+    })
+    .insert_resource(CurrentLevel(args.level))
+    .insert_resource(SandboxScene(args.scene.clone()))
+    .insert_resource(args)
+    .init_resource::<AtmosphereModel>()
+    .add_systems(Startup, (setup_character, setup_ghost, setup_sun))
+    // After `Startup`, where the camera plugin spawns the camera.
+    .add_systems(PostStartup, add_camera_effects)
+    .add_systems(
+        CustomPreUpdate,
+        (
+            (increment_frame, record_mouse_look)
                 .chain()
+                .before(CameraRotationSet),
+            set_velocity
+                .after(CameraRotationSet)
                 .before(CharacterControllerSet),
-        )
-        .add_systems(Update, (toggle_system_stepping, step))
-        .add_systems(Last, serialize_captured_input_on_exit)
-        .run()
+        ),
+    )
+    .add_systems(
+        CustomPostUpdate,
+        (
+            capture_frame_input.before(CharacterControllerSet),
+            (record_frame, move_ghost).after(CharacterControllerSet),
+            stream_frame.after(record_frame),
+        ),
+    )
+    .add_systems(
+        Update,
+        (
+            toggle_system_stepping,
+            step,
+            respawn_player.run_if(in_state(GamePauseState::Playing)),
+            print_footsteps,
+            print_animation_state,
+            print_solver_telemetry,
+            check_playback_config,
+            autosave_checkpoint,
+        ),
+    )
+    .add_systems(Last, serialize_captured_input_on_exit);
+
+    #[cfg(feature = "tuning")]
+    app.add_plugins((TuningPlugin, PlotPlugin));
+
+    app.run()
 }
 
 fn toggle_system_stepping(
@@ -104,91 +415,42 @@ fn step(world: &mut World) {
     }
 }
 
-fn setup_level(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+fn respawn_player(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    respawn_point: Res<RespawnPoint>,
+    mut query: Query<&mut CharacterController>,
 ) {
-    // commands.spawn((
-    //     RigidBody::Static,
-    //     Collider::cuboid(100.0, 0.1, 100.0),
-    //     PbrBundle {
-    //         mesh: meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(50.0))),
-    //         material: materials.add(Color::Srgba(palettes::css::BLACK)),
-    //         ..default()
-    //     },
-    // ));
-
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(2.0, 5.0, 10.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(2.0, 5.0, 10.0))),
-            transform: Transform::from_xyz(15.0, 2.5, 0.0),
-            material: materials.add(Color::Srgba(palettes::css::BLACK)),
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(10.0, 5.0, 2.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(10.0, 5.0, 2.0))),
-            transform: Transform::from_xyz(-15.0, 2.5, 0.0)
-                .with_rotation(Quat::from_rotation_y(15.0_f32.to_radians())),
-            material: materials.add(Color::Srgba(palettes::css::BLACK)),
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(10.0, 5.0, 2.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(10.0, 5.0, 2.0))),
-            transform: Transform::from_xyz(0.0, 2.5, 15.0)
-                .with_rotation(Quat::from_rotation_y(-15.0_f32.to_radians())),
-            material: materials.add(Color::Srgba(palettes::css::BLACK)),
-            ..default()
-        },
-    ));
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        for mut character_controller in &mut query {
+            respawn_point.respawn(&mut character_controller);
+        }
+    }
+}
 
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(2.0, 5.0, 10.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(2.0, 5.0, 10.0))),
-            transform: Transform::from_xyz(0.0, 2.5, 15.0)
-                .with_rotation(Quat::from_rotation_y(35.0_f32.to_radians())),
-            material: materials.add(Color::Srgba(palettes::css::BLACK)),
-            ..default()
-        },
-    ));
+fn print_footsteps(mut footsteps: EventReader<Footstep>) {
+    for footstep in footsteps.read() {
+        screen_print!("footstep on {:?}", footstep.material);
+    }
+}
 
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(2.0, 5.0, 10.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(2.0, 5.0, 10.0))),
-            transform: Transform::from_xyz(0.0, 2.5, 30.0)
-                .with_rotation(Quat::from_rotation_x(35.0_f32.to_radians())),
-            material: materials.add(Color::Srgba(palettes::css::BLACK)),
-            ..default()
-        },
-    ));
+fn print_animation_state(query: Query<&CharacterAnimationState>) {
+    for animation in &query {
+        screen_print!("animation: {:?}", animation.state);
+    }
+}
 
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(10.0, 2.0, 30.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::from_size(Vec3::new(10.0, 2.0, 30.0))),
-            transform: Transform::from_xyz(-15.0, -2.0, 30.0)
-                .with_rotation(Quat::from_rotation_x(35.0_f32.to_radians())),
-            material: materials.add(Color::linear_rgba(0.0, 0.0, 0.0, 0.2)),
-            ..default()
-        },
-    ));
+fn print_solver_telemetry(telemetry: Res<SolverTelemetry>) {
+    screen_print!(
+        "bounces: {:.2} avg, {} max, {} exhausted",
+        telemetry.bounces.average(),
+        telemetry.bounces.max(),
+        telemetry.exhausted.total()
+    );
+    screen_print!(
+        "step time: {:.1} µs avg, {:.1} µs max",
+        telemetry.step_time.average() * 1e6,
+        telemetry.step_time.max() * 1e6
+    );
 }
 
 fn setup_sun(mut commands: Commands, mut atmosphere: AtmosphereMut<Nishita>) {
@@ -210,34 +472,181 @@ fn setup_character(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    cli: Res<Cli>,
+) {
+    let player = commands
+        .spawn((
+            CharacterController::default(),
+            player_config(),
+            KccConfigHandle(asset_server.load("configs/player.kcc.ron")),
+            DebugCharacterController,
+            SlopeSpeed::default(),
+            FaceMovement::default(),
+            Footsteps::new(1.4),
+            CharacterAnimationState::default(),
+            RigidBody::Kinematic,
+            Collider::cylinder(0.5, 2.0),
+            PbrBundle {
+                mesh: meshes.add(Cylinder::new(0.5, 2.0)),
+                material: materials.add(Color::Srgba(Srgba::new(1.0, 0.0, 0.0, 0.5))),
+                transform: Transform::from_xyz(0.0, 1.0, 0.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    if let Some(frames) = &cli.trace {
+        commands.entity(player).insert(SolverTrace::new(
+            format!("trace-{}.ron", frames.start),
+            frames.clone(),
+        ));
+    }
+}
+
+fn add_camera_effects(mut commands: Commands, cameras: Query<Entity, With<Camera3d>>) {
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .insert((FovKick::default(), HeadBob::default()));
+    }
+}
+
+fn setup_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ghost_frames: Res<GhostFrames>,
 ) {
+    if ghost_frames.0.is_empty() {
+        return;
+    }
+
     commands.spawn((
-        CharacterController::default(),
-        RigidBody::Kinematic,
-        Collider::cylinder(0.5, 2.0),
+        Ghost,
         PbrBundle {
             mesh: meshes.add(Cylinder::new(0.5, 2.0)),
-            material: materials.add(Color::Srgba(Srgba::new(1.0, 0.0, 0.0, 0.5))),
+            material: materials.add(StandardMaterial {
+                base_color: Color::srgba(0.6, 0.8, 1.0, 0.3),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
             transform: Transform::from_xyz(0.0, 1.0, 0.0),
             ..default()
         },
     ));
 }
 
+/// Puts the ghost where the recorded character was at the end of this frame. It stays at its last
+/// position once the recording runs out.
+fn move_ghost(
+    mut query: Query<&mut Transform, With<Ghost>>,
+    ghost_frames: Res<GhostFrames>,
+    frame_count: Res<FrameCount>,
+) {
+    let Some(frame) = ghost_frames.0.get(&frame_count.0) else {
+        return;
+    };
+
+    for mut transform in &mut query {
+        transform.translation = frame.output.translation.f32();
+        transform.rotation = frame.output.rotation;
+    }
+}
+
+/// The player's config until its asset loads.
+fn player_config() -> CharacterControllerConfig {
+    CharacterControllerConfig::default()
+}
+
+/// The metadata of a recording made with these arguments and config.
+fn replay_metadata(cli: &Cli, seed: u64, config: &CharacterControllerConfig) -> ReplayMetadata {
+    ReplayMetadata::new(cli.level.name(), TIMESTEP, config, seed)
+}
+
+/// The metadata to check a recording against before the app starts. The player's config comes
+/// from its asset, which hasn't loaded yet, so this copies the recorded config checksum and
+/// [`check_playback_config`] compares it once the asset has loaded.
+fn startup_metadata(cli: &Cli, recorded: &ReplayMetadata) -> ReplayMetadata {
+    ReplayMetadata {
+        config_checksum: recorded.config_checksum,
+        ..replay_metadata(cli, recorded.seed, &player_config())
+    }
+}
+
+fn check_playback_config(
+    query: Query<(&CharacterControllerConfig, &KccConfigHandle)>,
+    configs: Res<Assets<KccConfig>>,
+    playback_metadata: Res<PlaybackMetadata>,
+    cli: Res<Cli>,
+    mut checked: Local<bool>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let Some(recorded) = &playback_metadata.0 else {
+        return;
+    };
+
+    let (config, handle) = query.single();
+
+    if *checked || configs.get(&handle.0).map(|asset| asset.0) != Some(*config) {
+        return;
+    }
+
+    *checked = true;
+
+    let current = ReplayMetadata {
+        config_checksum: config_checksum(config),
+        ..recorded.clone()
+    };
+
+    for mismatch in recorded.mismatches(&current) {
+        eprintln!("the recording doesn't match how it's being played back, {mismatch}");
+
+        if !cli.ignore_mismatch {
+            eprintln!("pass --ignore-mismatch to play it back anyway");
+            app_exit_events.send(AppExit::error());
+        }
+    }
+}
+
 fn increment_frame(mut frame_count: ResMut<FrameCount>) {
     frame_count.0 += 1;
     screen_print!("FRAME: {}", frame_count.0);
 }
 
+fn record_mouse_look(
+    mut mouse_look: ResMut<MouseLook>,
+    mut recorded_mouse_look: ResMut<RecordedMouseLook>,
+    frame_count: Res<FrameCount>,
+    cli: Res<Cli>,
+) {
+    if cli.playback.is_some() {
+        mouse_look.delta = recorded_mouse_look
+            .0
+            .get(&frame_count.0)
+            .copied()
+            .unwrap_or_default();
+    } else if mouse_look.delta != Vec2::ZERO {
+        recorded_mouse_look
+            .0
+            .insert(frame_count.0, mouse_look.delta);
+    }
+}
+
 fn set_velocity(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    keyboard_input: Res<FixedInput<KeyCode>>,
     camera_rotation: Res<CameraRotation>,
-    mut query: Query<&mut CharacterController>,
+    camera_mode: Res<CameraMode>,
+    pause_state: Res<State<GamePauseState>>,
+    mut query: Query<&mut CharacterController, Without<ControllerDisabled>>,
     mut recorded_velocities: ResMut<RecordedVelocities>,
+    mut recorded_directions: ResMut<RecordedDirections>,
     frame_count: Res<FrameCount>,
     cli: Res<Cli>,
 ) {
-    if cli.playback.is_some() {
+    // Recordings from before directions were recorded only have the velocities they resulted in.
+    if cli.playback.is_some() && recorded_directions.0.is_empty() {
         let velocity = recorded_velocities.0.get(&frame_count.0);
 
         if velocity.is_none() {
@@ -253,58 +662,232 @@ fn set_velocity(
 
     let mut direction = Vec3::ZERO;
 
-    if keyboard_input.pressed(KeyCode::KeyW) {
-        direction.z -= 1.0;
+    if cli.playback.is_some() {
+        direction = recorded_directions
+            .0
+            .get(&frame_count.0)
+            .copied()
+            .unwrap_or_default();
+    } else if camera_mode.controls_player() && pause_state.get().is_playing() {
+        if keyboard_input.pressed(KeyCode::KeyW) {
+            direction.z -= 1.0;
+        }
+
+        if keyboard_input.pressed(KeyCode::KeyA) {
+            direction.x -= 1.0;
+        }
+
+        if keyboard_input.pressed(KeyCode::KeyS) {
+            direction.z += 1.0;
+        }
+
+        if keyboard_input.pressed(KeyCode::KeyD) {
+            direction.x += 1.0;
+        }
+
+        if direction != Vec3::ZERO {
+            recorded_directions.0.insert(frame_count.0, direction);
+        }
+    }
+
+    // Movement goes through the camera's heading on playback too, which replays the recorded
+    // mouse look. Swimming and flying, it goes where the camera looks, including up and down.
+    for mut character_controller in &mut query {
+        let camera_rotation = if character_controller.mode.is_3d() {
+            camera_rotation.rotation()
+        } else {
+            camera_rotation.heading()
+        };
+        let move_direction = camera_rotation * direction;
+
+        character_controller.velocity = move_direction.normalize_or_zero() * PLAYER_SPEED;
+
+        if cli.playback.is_none() {
+            recorded_velocities
+                .0
+                .insert(frame_count.0, character_controller.velocity);
+        }
+    }
+}
+
+fn capture_frame_input(
+    query: Query<(&CharacterController, &Position, &Transform)>,
+    mut frame_input: ResMut<FrameInput>,
+) {
+    let (character_controller, position, transform) = query.single();
+
+    frame_input.0 = Some(CharacterSnapshot::save(
+        character_controller,
+        position,
+        transform,
+    ));
+}
+
+/// Records the frame when recording, or checks it against the recording when verifying.
+fn record_frame(
+    query: Query<(&CharacterController, &Position, &Transform)>,
+    mut frame_input: ResMut<FrameInput>,
+    mut recorded_frames: ResMut<RecordedFrames>,
+    frame_count: Res<FrameCount>,
+    replay_store: Res<ReplayStore>,
+    cli: Res<Cli>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let (character_controller, position, transform) = query.single();
+    let Some(input) = frame_input.0.take() else {
+        return;
+    };
+    let output = CharacterSnapshot::save(character_controller, position, transform);
+    let frame = FrameRecord::new(input, output, character_controller);
+
+    if cli.playback.is_none() {
+        recorded_frames.0.insert(frame_count.0, frame);
+        return;
     }
 
-    if keyboard_input.pressed(KeyCode::KeyA) {
-        direction.x -= 1.0;
+    if !cli.verify {
+        return;
     }
 
-    if keyboard_input.pressed(KeyCode::KeyS) {
-        direction.z += 1.0;
+    let Some(expected) = recorded_frames.0.get(&frame_count.0) else {
+        return;
+    };
+
+    if let Some(divergence) = Divergence::check(frame_count.0, expected, &frame) {
+        match divergence.dump(&replay_store) {
+            Ok(name) => eprintln!("frame {} diverged, dumped to {name}", frame_count.0),
+            Err(error) => eprintln!(
+                "frame {} diverged, but couldn't dump it: {error}",
+                frame_count.0
+            ),
+        }
+
+        app_exit_events.send(AppExit::error());
     }
+}
 
-    if keyboard_input.pressed(KeyCode::KeyD) {
-        direction.x += 1.0;
+fn stream_frame(
+    mut stream: ResMut<RecordingStream>,
+    recorded: Recorded,
+    frame_count: Res<FrameCount>,
+    replay_store: Res<ReplayStore>,
+    rng: Res<ReplayRng>,
+    cli: Res<Cli>,
+    config: Query<&CharacterControllerConfig, With<KccConfigHandle>>,
+    mut failed: Local<bool>,
+) {
+    if cli.playback.is_some() || *failed {
+        return;
     }
 
-    let camera_rotation = Mat3::from_quat(Quat::from_rotation_y(camera_rotation.yaw));
-    let move_direction = camera_rotation.mul_vec3(direction);
+    let stream = match &mut stream.0 {
+        Some(stream) => stream,
+        None => {
+            let metadata = replay_metadata(&cli, rng.seed(), config.single());
+
+            match ReplayStream::create(
+                &*replay_store.0,
+                STREAM_NAME,
+                &metadata,
+                STREAM_CHUNK_FRAMES,
+            ) {
+                Ok(created) => stream.0.insert(created),
+                Err(error) => {
+                    eprintln!("couldn't stream the recording: {error}");
+                    *failed = true;
+                    return;
+                }
+            }
+        }
+    };
 
-    for mut character_controller in &mut query {
-        character_controller.velocity = move_direction.normalize_or_zero() * PLAYER_SPEED;
-        recorded_velocities
-            .0
-            .insert(frame_count.0, character_controller.velocity);
+    let frame = frame_count.0;
+    let streamed = StreamedFrame {
+        frame,
+        velocity: recorded.velocities.0.get(&frame).copied(),
+        direction: recorded.directions.0.get(&frame).copied(),
+        mouse_look: recorded.mouse_look.0.get(&frame).copied(),
+        record: recorded.frames.0.get(&frame).cloned(),
+    };
+
+    if let Err(error) = stream.push(&*replay_store.0, streamed) {
+        eprintln!("couldn't stream the recording: {error}");
+        *failed = true;
+    }
+}
+
+fn autosave_checkpoint(
+    recorded: Recorded,
+    replay_store: Res<ReplayStore>,
+    rng: Res<ReplayRng>,
+    cli: Res<Cli>,
+    config: Query<&CharacterControllerConfig, With<KccConfigHandle>>,
+    time: Res<Time<Real>>,
+    mut since_checkpoint: Local<f32>,
+    mut checkpoints: Local<usize>,
+) {
+    if cfg!(target_arch = "wasm32") || cli.playback.is_some() || cli.autosave <= 0.0 {
+        return;
+    }
+
+    *since_checkpoint += time.delta_seconds();
+
+    if *since_checkpoint < cli.autosave {
+        return;
+    }
+
+    *since_checkpoint = 0.0;
+
+    let Ok(config) = config.get_single() else {
+        return;
+    };
+
+    let recording = Recording {
+        saved_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .map(|since_epoch| since_epoch.as_millis() as u64),
+        ..recorded.recording(replay_metadata(&cli, rng.seed(), config))
+    };
+
+    let name = CHECKPOINT_NAMES[*checkpoints % CHECKPOINT_NAMES.len()];
+    *checkpoints += 1;
+
+    if let Err(error) =
+        to_canonical_ron(&recording).and_then(|contents| replay_store.0.save(name, &contents))
+    {
+        eprintln!("couldn't save checkpoint {name}: {error}");
     }
 }
 
 fn serialize_captured_input_on_exit(
     app_exit_events: EventReader<AppExit>,
-    recorded_velocities: Res<RecordedVelocities>,
+    mut stream: ResMut<RecordingStream>,
+    recorded: Recorded,
+    replay_store: Res<ReplayStore>,
+    rng: Res<ReplayRng>,
     cli: Res<Cli>,
+    config: Query<&CharacterControllerConfig, With<KccConfigHandle>>,
 ) {
     if !app_exit_events.is_empty() && cli.playback.is_none() {
-        serialize_timestamped_inputs(&recorded_velocities);
+        if let Some(stream) = &mut stream.0 {
+            if let Err(error) = stream.flush(&*replay_store.0) {
+                eprintln!("couldn't finish streaming the recording: {error}");
+            }
+        }
+
+        let recording = recorded.recording(replay_metadata(&cli, rng.seed(), config.single()));
+
+        serialize_timestamped_inputs(&recording, &replay_store);
     }
 }
 
-fn serialize_timestamped_inputs(recorded_velocities: &RecordedVelocities) {
-    let file_path = Path::new("out.ron");
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(file_path)
-        .expect("Could not open file.");
+fn serialize_timestamped_inputs(recording: &Recording, replay_store: &ReplayStore) {
+    let contents =
+        to_canonical_ron(recording).expect("Could not convert captured input to a string.");
 
-    write!(
-        file,
-        "{}",
-        ron::ser::to_string_pretty(recorded_velocities, PrettyConfig::default())
-            .expect("Could not convert captured input to a string.")
-    )
-    .expect("Could not write string to file.");
+    replay_store
+        .0
+        .save("out.ron", &contents)
+        .expect("Could not save captured input.");
 }
@@ -0,0 +1,164 @@
+//! Getting characters in and out of vehicles and onto mounts. Entering parents the character to
+//! the [`Mount`] at its seat, disables its controller, and makes its collider a sensor so it
+//! doesn't push the vehicle around. Exiting tries each of the mount's exits in order and puts
+//! the character at the first one its collider fits at, or leaves it seated if none do.
+//!
+//! Send [`MountRequest`]s from input systems. Like [`crate::actions`], requests sent in frames
+//! where no fixed step runs are kept until the next step.
+
+use avian3d::{math::AdjustPrecision, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    backend::{AvianSpatialQuery, SpatialQueryBackend},
+    character_controller::{CharacterController, CharacterControllerSet, ControllerDisabled},
+    schedule::CustomPreUpdate,
+};
+
+pub struct MountPlugin;
+
+impl Plugin for MountPlugin {
+    fn build(&self, app: &mut App) {
+        // Not `add_event`, which would drop requests that no step has read after two frames.
+        app.init_resource::<Events<MountRequest>>()
+            .add_event::<MountEntered>()
+            .add_event::<MountExited>()
+            .add_event::<MountExitBlocked>()
+            .add_systems(
+                CustomPreUpdate,
+                handle_mount_requests.before(CharacterControllerSet),
+            );
+    }
+}
+
+/// Something characters can ride, like a vehicle or a horse.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Mount {
+    /// Where the character sits, relative to the mount.
+    pub seat: Vec3,
+    /// Where the character can get off, relative to the mount, in order of preference. These
+    /// are the center of the character's collider, so they should be high enough off the ground
+    /// for it to fit.
+    pub exits: Vec<Vec3>,
+}
+
+/// The mount a character is riding. Added and removed by the [`MountPlugin`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Riding {
+    pub mount: Entity,
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MountRequest {
+    /// Ignored if the character is already riding something.
+    Enter { character: Entity, mount: Entity },
+    /// Ignored if the character isn't riding anything.
+    Exit { character: Entity },
+}
+
+/// Sent when a character gets on a mount.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MountEntered {
+    pub character: Entity,
+    pub mount: Entity,
+}
+
+/// Sent when a character gets off a mount.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MountExited {
+    pub character: Entity,
+    pub mount: Entity,
+}
+
+/// Sent when a character tried to get off a mount, but every exit was blocked.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MountExitBlocked {
+    pub character: Entity,
+    pub mount: Entity,
+}
+
+fn handle_mount_requests(
+    mut commands: Commands,
+    mut requests: ResMut<Events<MountRequest>>,
+    mounts: Query<(&Mount, &GlobalTransform)>,
+    mut characters: Query<(
+        &mut CharacterController,
+        &Collider,
+        &GlobalTransform,
+        Option<&Riding>,
+    )>,
+    spatial_query: AvianSpatialQuery,
+    mut entered: EventWriter<MountEntered>,
+    mut exited: EventWriter<MountExited>,
+    mut blocked: EventWriter<MountExitBlocked>,
+) {
+    for request in requests.drain() {
+        match request {
+            MountRequest::Enter { character, mount } => {
+                let Ok((_, _, _, None)) = characters.get(character) else {
+                    continue;
+                };
+                let Ok((mount_settings, _)) = mounts.get(mount) else {
+                    continue;
+                };
+
+                commands.entity(character).set_parent(mount).insert((
+                    Transform::from_translation(mount_settings.seat),
+                    Riding { mount },
+                    ControllerDisabled,
+                    Sensor,
+                ));
+                entered.send(MountEntered { character, mount });
+            }
+            MountRequest::Exit { character } => {
+                let Ok((mut character_controller, collider, global_transform, Some(riding))) =
+                    characters.get_mut(character)
+                else {
+                    continue;
+                };
+                let mount = riding.mount;
+                let Ok((mount_settings, mount_transform)) = mounts.get(mount) else {
+                    continue;
+                };
+
+                // Get off upright, facing the way the mount faces.
+                let (_, mount_rotation, _) = mount_transform.to_scale_rotation_translation();
+                let (yaw, _, _) = mount_rotation.to_euler(EulerRot::YXZ);
+                let rotation = Quat::from_rotation_y(yaw);
+                let filter = SpatialQueryFilter::from_excluded_entities([character]);
+
+                let exit = mount_settings
+                    .exits
+                    .iter()
+                    .map(|&exit| mount_transform.transform_point(exit))
+                    .find(|&exit| {
+                        spatial_query
+                            .shape_intersections(
+                                collider,
+                                exit.adjust_precision(),
+                                rotation.adjust_precision(),
+                                &filter,
+                            )
+                            .is_empty()
+                    });
+
+                let Some(exit) = exit else {
+                    blocked.send(MountExitBlocked { character, mount });
+                    continue;
+                };
+
+                character_controller.teleport(exit.adjust_precision());
+                commands
+                    .entity(character)
+                    .remove_parent()
+                    .remove::<(Riding, ControllerDisabled, Sensor)>()
+                    .insert(Transform {
+                        translation: exit,
+                        rotation,
+                        scale: global_transform.compute_transform().scale,
+                    });
+                exited.send(MountExited { character, mount });
+            }
+        }
+    }
+}
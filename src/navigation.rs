@@ -0,0 +1,150 @@
+//! Drives characters from navigation output, so AI characters move through the same solver as the
+//! player.
+//!
+//! Anything that produces a [`DesiredVelocity`], like a crowd simulation, can steer an agent. For
+//! pathfinders that produce waypoints, like `oxidized_navigation`'s `find_path`, a
+//! [`NavigationPath`] turns the path into a desired velocity.
+
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, ControllerDisabled, VelocityStage},
+    schedule::CustomPostUpdate,
+};
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            (follow_paths, drive_navigation_agents)
+                .chain()
+                .in_set(VelocityStage::Input),
+        );
+    }
+}
+
+/// Converts a [`DesiredVelocity`] into the character's input velocity, slowing down when
+/// approaching the destination and stopping at it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NavigationAgent {
+    /// Where the agent is going. Cleared when the agent arrives.
+    pub destination: Option<Vec3>,
+    pub max_speed: f32,
+    /// The agent stops once it's this close to the destination.
+    pub stopping_distance: f32,
+    /// The agent starts slowing down this far from the destination.
+    pub slowdown_distance: f32,
+}
+
+impl Default for NavigationAgent {
+    fn default() -> Self {
+        Self {
+            destination: None,
+            max_speed: 6.0,
+            stopping_distance: 0.2,
+            slowdown_distance: 2.0,
+        }
+    }
+}
+
+impl NavigationAgent {
+    /// Returns whether the agent is within the stopping distance of its destination.
+    pub fn has_arrived(&self, translation: Vec3) -> bool {
+        self.destination.is_some_and(|destination| {
+            (destination - translation).with_y(0.0).length() <= self.stopping_distance
+        })
+    }
+
+    /// Returns the velocity to move with from `translation` when steering with `desired`.
+    pub fn steer(&self, translation: Vec3, desired: Vec3) -> Vec3 {
+        let Some(destination) = self.destination else {
+            return Vec3::ZERO;
+        };
+
+        let distance = (destination - translation).with_y(0.0).length();
+
+        if distance <= self.stopping_distance {
+            return Vec3::ZERO;
+        }
+
+        let speed = if self.slowdown_distance > 0.0 {
+            self.max_speed * (distance / self.slowdown_distance).min(1.0)
+        } else {
+            self.max_speed
+        };
+
+        desired.with_y(0.0).clamp_length_max(speed)
+    }
+}
+
+/// The velocity a navigation system wants the agent to move with.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DesiredVelocity(pub Vec3);
+
+/// Waypoints for the agent to walk through in order, ending at its destination.
+#[derive(Component, Clone, Debug, Default)]
+pub struct NavigationPath {
+    pub waypoints: Vec<Vec3>,
+    /// How close the agent has to get to a waypoint before heading to the next one.
+    pub waypoint_radius: f32,
+}
+
+fn follow_paths(
+    mut agents: Query<(
+        &Transform,
+        &mut NavigationPath,
+        &mut NavigationAgent,
+        &mut DesiredVelocity,
+    )>,
+) {
+    for (transform, mut path, mut agent, mut desired_velocity) in &mut agents {
+        let waypoint_radius = path.waypoint_radius;
+
+        // Skip the waypoints that have been reached, but keep the destination so the agent can
+        // slow down for it.
+        while path.waypoints.len() > 1
+            && (path.waypoints[0] - transform.translation)
+                .with_y(0.0)
+                .length()
+                <= waypoint_radius
+        {
+            path.waypoints.remove(0);
+        }
+
+        agent.destination = path.waypoints.last().copied();
+
+        if agent.has_arrived(transform.translation) {
+            path.waypoints.clear();
+            agent.destination = None;
+        }
+
+        desired_velocity.0 = path.waypoints.first().map_or(Vec3::ZERO, |waypoint| {
+            (*waypoint - transform.translation)
+                .with_y(0.0)
+                .normalize_or_zero()
+                * agent.max_speed
+        });
+    }
+}
+
+fn drive_navigation_agents(
+    mut agents: Query<
+        (
+            &Transform,
+            &mut NavigationAgent,
+            &DesiredVelocity,
+            &mut CharacterController,
+        ),
+        Without<ControllerDisabled>,
+    >,
+) {
+    for (transform, mut agent, desired_velocity, mut character_controller) in &mut agents {
+        if agent.has_arrived(transform.translation) {
+            agent.destination = None;
+        }
+
+        character_controller.velocity = agent.steer(transform.translation, desired_velocity.0);
+    }
+}
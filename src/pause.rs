@@ -0,0 +1,136 @@
+//! Pausing the game for menus. While [`GamePauseState::Paused`], the cursor is released, virtual
+//! time is paused so the custom schedule stops without a catch-up burst on resume, and character
+//! input is ignored. Games can extend it with their own `OnEnter`/`OnExit` systems, or by setting
+//! the state from their menus.
+//!
+//! Losing window focus releases the cursor too, and pauses unless [`PauseOnFocusLoss`] is
+//! disabled, so alt-tabbing doesn't spin the camera or simulate a burst of steps on return.
+
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow, WindowFocused},
+};
+
+/// Pauses with Escape.
+const PAUSE_KEY: KeyCode = KeyCode::Escape;
+/// Resumes by clicking into the window.
+const RESUME_BUTTON: MouseButton = MouseButton::Left;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GamePauseState>()
+            .init_resource::<PauseOnFocusLoss>()
+            .add_systems(Update, (toggle_pause, handle_focus_change))
+            .add_systems(OnEnter(GamePauseState::Playing), (grab_cursor, resume_time))
+            .add_systems(
+                OnEnter(GamePauseState::Paused),
+                (release_cursor, pause_time),
+            );
+    }
+}
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GamePauseState {
+    #[default]
+    Playing,
+    /// In a menu, or otherwise not playing.
+    Paused,
+}
+
+impl GamePauseState {
+    /// Whether character and camera input should be read.
+    pub fn is_playing(&self) -> bool {
+        *self == GamePauseState::Playing
+    }
+}
+
+/// Whether losing window focus pauses the game, rather than only releasing the cursor until focus
+/// returns.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct PauseOnFocusLoss(pub bool);
+
+impl Default for PauseOnFocusLoss {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn toggle_pause(
+    key: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    state: Res<State<GamePauseState>>,
+    mut next_state: ResMut<NextState<GamePauseState>>,
+) {
+    match state.get() {
+        GamePauseState::Playing if key.just_pressed(PAUSE_KEY) => {
+            next_state.set(GamePauseState::Paused);
+        }
+        GamePauseState::Paused if mouse.just_pressed(RESUME_BUTTON) => {
+            next_state.set(GamePauseState::Playing);
+        }
+        _ => {}
+    }
+}
+
+fn handle_focus_change(
+    mut focus_events: EventReader<WindowFocused>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    pause_on_focus_loss: Res<PauseOnFocusLoss>,
+    state: Res<State<GamePauseState>>,
+    mut next_state: ResMut<NextState<GamePauseState>>,
+    window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(primary_window) = primary_window.get_single() else {
+        focus_events.clear();
+        return;
+    };
+
+    let Some(focused) = focus_events
+        .read()
+        .filter(|event| event.window == primary_window)
+        .last()
+        .map(|event| event.focused)
+    else {
+        return;
+    };
+
+    if !state.get().is_playing() {
+        return;
+    }
+
+    if focused {
+        grab_cursor(window);
+    } else if pause_on_focus_loss.0 {
+        next_state.set(GamePauseState::Paused);
+    } else {
+        release_cursor(window);
+    }
+}
+
+fn grab_cursor(mut window: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+
+    window.cursor.visible = false;
+    window.cursor.grab_mode = CursorGrabMode::Locked;
+}
+
+fn release_cursor(mut window: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+
+    window.cursor.visible = true;
+    window.cursor.grab_mode = CursorGrabMode::None;
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
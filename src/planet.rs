@@ -0,0 +1,97 @@
+//! Gravity towards the center of planets, with characters standing up away from it.
+
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{apply_gravity, CharacterController, CharacterGravity, VelocityStage},
+    schedule::CustomPostUpdate,
+};
+
+pub struct PlanetPlugin;
+
+impl Plugin for PlanetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            pull_towards_gravity_sources
+                .before(apply_gravity)
+                .in_set(VelocityStage::Gravity),
+        );
+    }
+}
+
+/// Marks a [`CharacterGravity`] as set by a [`GravitySource`], so that it's only removed by
+/// leaving the source's range and not when some other system set it. Gravity zones stack on top of
+/// it.
+#[derive(Component)]
+pub(crate) struct PlanetGravity;
+
+/// Pulls characters within `radius` of the entity's center towards it, and sets their up direction
+/// to point away from it. Characters leave with avian's gravity and an up direction of +Y.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GravitySource {
+    /// The acceleration towards the center.
+    pub strength: f32,
+    pub radius: f32,
+}
+
+pub(crate) fn pull_towards_gravity_sources(
+    mut commands: Commands,
+    sources: Query<(&GravitySource, &GlobalTransform)>,
+    mut characters: Query<(
+        Entity,
+        &mut CharacterController,
+        &Transform,
+        Option<&mut CharacterGravity>,
+        Has<PlanetGravity>,
+    )>,
+) {
+    for (entity, mut character_controller, transform, character_gravity, on_planet) in
+        &mut characters
+    {
+        // The closest source's center, relative to the character.
+        let nearest = sources
+            .iter()
+            .map(|(source, source_transform)| {
+                (
+                    source,
+                    source_transform.translation() - transform.translation,
+                )
+            })
+            .filter(|(source, offset)| offset.length() <= source.radius)
+            .min_by(|(_, a), (_, b)| a.length_squared().total_cmp(&b.length_squared()));
+
+        match nearest {
+            Some((source, offset)) => {
+                let Ok(down) = Dir3::new(offset) else {
+                    continue;
+                };
+
+                let gravity = down * source.strength;
+                character_controller.up_direction = -down;
+
+                match character_gravity {
+                    Some(mut character_gravity) => {
+                        character_gravity.0 = gravity;
+
+                        if !on_planet {
+                            commands.entity(entity).insert(PlanetGravity);
+                        }
+                    }
+                    None => {
+                        commands
+                            .entity(entity)
+                            .insert((CharacterGravity(gravity), PlanetGravity));
+                    }
+                }
+            }
+            None if on_planet => {
+                character_controller.up_direction = Dir3::Y;
+                commands
+                    .entity(entity)
+                    .remove::<(CharacterGravity, PlanetGravity)>();
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Tracks which platform each character is standing on, and sends events when it changes.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet},
+    schedule::CustomPostUpdate,
+};
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlatformAttached>()
+            .add_event::<PlatformDetached>()
+            .add_systems(
+                CustomPostUpdate,
+                update_standing_on.after(CharacterControllerSet),
+            );
+    }
+}
+
+/// The rigid body a character is standing on, or the collider if it has no rigid body. Updated
+/// after every step for characters that have it.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandingOn(pub Option<Entity>);
+
+/// Sent when a character starts standing on a platform.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformAttached {
+    pub character: Entity,
+    pub platform: Entity,
+}
+
+/// Sent when a character stops standing on a platform, before any [`PlatformAttached`] for the
+/// platform it moved onto.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformDetached {
+    pub character: Entity,
+    pub platform: Entity,
+}
+
+fn update_standing_on(
+    mut characters: Query<(Entity, &CharacterController, &mut StandingOn)>,
+    collider_parents: Query<&ColliderParent>,
+    mut attached: EventWriter<PlatformAttached>,
+    mut detached: EventWriter<PlatformDetached>,
+) {
+    for (entity, character_controller, mut standing_on) in &mut characters {
+        let platform = character_controller.ground.map(|ground| {
+            collider_parents
+                .get(ground.entity)
+                .map_or(ground.entity, ColliderParent::get)
+        });
+
+        if standing_on.0 == platform {
+            continue;
+        }
+
+        if let Some(previous) = standing_on.0 {
+            detached.send(PlatformDetached {
+                character: entity,
+                platform: previous,
+            });
+        }
+
+        if let Some(platform) = platform {
+            attached.send(PlatformAttached {
+                character: entity,
+                platform,
+            });
+        }
+
+        standing_on.0 = platform;
+    }
+}
@@ -0,0 +1,122 @@
+//! An egui window plotting the recent movement of debugged characters, with the `tuning` feature.
+
+use std::collections::VecDeque;
+
+use avian3d::{math::AsF32, prelude::*};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet, DebugCharacterController},
+    schedule::CustomPostUpdate,
+};
+
+/// How many fixed steps of history are plotted.
+pub const PLOT_HISTORY: usize = 512;
+
+pub struct PlotPlugin;
+
+impl Plugin for PlotPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+
+        app.add_systems(
+            CustomPostUpdate,
+            record_movement_history.after(CharacterControllerSet),
+        )
+        .add_systems(Update, plot_panel);
+    }
+}
+
+/// A character's movement over the last [`PLOT_HISTORY`] fixed steps. It's added to characters
+/// with [`DebugCharacterController`] automatically.
+#[derive(Component, Default)]
+pub struct MovementHistory {
+    pub samples: VecDeque<MovementSample>,
+    /// The fixed step of the last sample, counting from when the history was added.
+    pub step: u64,
+    last_position: Option<Vec3>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementSample {
+    /// How fast the character actually moved across its up direction in the step, which shows
+    /// jitter that its velocity alone doesn't.
+    pub horizontal_speed: f32,
+    pub vertical_velocity: f32,
+    pub grounded: bool,
+}
+
+fn record_movement_history(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &CharacterController,
+            &Position,
+            Option<&mut MovementHistory>,
+        ),
+        With<DebugCharacterController>,
+    >,
+    time: Res<Time>,
+) {
+    for (entity, character_controller, position, history) in &mut query {
+        let Some(mut history) = history else {
+            commands.entity(entity).insert(MovementHistory::default());
+            continue;
+        };
+
+        let position = position.0.f32();
+        let displacement = position - history.last_position.unwrap_or(position);
+        let horizontal = displacement.reject_from_normalized(*character_controller.up_direction);
+
+        history.last_position = Some(position);
+        history.step += 1;
+
+        if history.samples.len() == PLOT_HISTORY {
+            history.samples.pop_front();
+        }
+
+        history.samples.push_back(MovementSample {
+            horizontal_speed: horizontal.length() / time.delta_seconds(),
+            vertical_velocity: character_controller.vertical_velocity,
+            grounded: character_controller.is_grounded(),
+        });
+    }
+}
+
+fn plot_panel(mut contexts: EguiContexts, query: Query<(Entity, &MovementHistory)>) {
+    egui::Window::new("Movement").show(contexts.ctx_mut(), |ui| {
+        for (entity, history) in &query {
+            let first_step = history.step + 1 - history.samples.len() as u64;
+            let series = |value: fn(&MovementSample) -> f32| -> PlotPoints {
+                history
+                    .samples
+                    .iter()
+                    .enumerate()
+                    .map(|(i, sample)| [(first_step + i as u64) as f64, value(sample) as f64])
+                    .collect()
+            };
+
+            Plot::new(entity)
+                .height(200.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(
+                        Line::new(series(|sample| sample.horizontal_speed))
+                            .name("horizontal speed"),
+                    );
+                    plot_ui.line(
+                        Line::new(series(|sample| sample.vertical_velocity))
+                            .name("vertical velocity"),
+                    );
+                    plot_ui.line(
+                        Line::new(series(|sample| sample.grounded as u8 as f32)).name("grounded"),
+                    );
+                });
+        }
+    });
+}
@@ -0,0 +1,61 @@
+//! `use souls::prelude::*;` to import the plugins, components, and events of the character
+//! controller and its companion modules.
+
+pub use crate::{
+    actions::{ActionKind, CharacterAction, CharacterActionPlugin, StepActions},
+    animation::{AnimationState, CharacterAnimationPlugin, CharacterAnimationState},
+    camera::{
+        CameraMode, CameraPlugin, CameraRotation, CameraRotationSet, CameraSettings, FovKick,
+        FreeCamera, HeadBob, MouseLook, OrbitCamera,
+    },
+    character_controller::{
+        AirJump, AlwaysWalkable, CharacterController, CharacterControllerConfig,
+        CharacterControllerPlugin, CharacterControllerSet, CharacterGravity, CharacterTimeScale,
+        ControllerDisabled, CustomMovement, CustomMovementContext, CustomMovementMode,
+        DebugCharacterController, FaceMovement, GroundInfo, Jumped, KccSet, Landed, MovementMode,
+        MovementModeChanged, NeverWalkable, NotWalkable, Sliding, SlopeSpeed, SolverExhausted,
+        SolverFallback, SurfaceKind, SurfaceMaterial, Traction, VelocityStage,
+    },
+    config_asset::{KccConfig, KccConfigHandle, KccConfigPlugin},
+    footsteps::{Footstep, FootstepPlugin, Footsteps},
+    gravity_zones::{GravityZone, GravityZonePlugin, ZoneGravity},
+    input::{FixedInput, FixedInputPlugin},
+    launch_pads::{LaunchPad, LaunchPadPlugin, Launched},
+    mounts::{
+        Mount, MountEntered, MountExitBlocked, MountExited, MountPlugin, MountRequest, Riding,
+    },
+    pause::{GamePauseState, PauseOnFocusLoss, PausePlugin},
+    planet::{GravitySource, PlanetPlugin},
+    platforms::{PlatformAttached, PlatformDetached, PlatformPlugin, StandingOn},
+    respawn::{Checkpoint, KillPlane, RespawnPlugin, RespawnPoint},
+    schedule::{
+        CustomFirst, CustomLast, CustomPostUpdate, CustomPreUpdate, CustomStepping, CustomUpdate,
+        SchedulePlugin,
+    },
+    snapshot::CharacterSnapshot,
+    solver_trace::{SolverTrace, SolverTracePlugin},
+    telemetry::{RollingStat, SolverTelemetry, SolverTelemetryPlugin},
+    time_dilation::{TimeDilationPlugin, TimeDilationZone},
+    water::{Submerged, Swimmer, Water, WaterPlugin},
+    wind::{Wind, WindPlugin},
+};
+
+#[cfg(feature = "kcc2d")]
+pub use crate::kcc2d::{
+    CharacterController2d, CharacterController2dPlugin, CharacterController2dSet,
+};
+
+#[cfg(feature = "navigation")]
+pub use crate::navigation::{DesiredVelocity, NavigationAgent, NavigationPath, NavigationPlugin};
+
+#[cfg(feature = "replicon")]
+pub use crate::replication::{
+    CharacterInput, CharacterReplicationPlugin, ControlledBy, PredictedCharacter,
+    ServerCharacterState,
+};
+
+#[cfg(feature = "tuning")]
+pub use crate::{
+    plots::{MovementHistory, PlotPlugin},
+    tuning::TuningPlugin,
+};
@@ -0,0 +1,502 @@
+//! Where the demo's input recordings are kept.
+//!
+//! On native platforms recordings are files. The web has no file system, so there they're kept in
+//! the browser's `localStorage` and saving also downloads them, so they can be played back
+//! natively.
+//!
+//! Recordings start with a [`ReplayMetadata`] header, so playing one back in a different level,
+//! timestep, or config can be caught before it silently desyncs. The header also has the seed of
+//! the [`ReplayRng`], so randomness plays back the same way.
+//!
+//! Recordings can also keep a [`FrameRecord`] of every step, which playback can verify against and
+//! dump the first [`Divergence`] of, and which [`frames_to_csv`] exports for analysis elsewhere.
+//!
+//! Everything is saved with [`to_canonical_ron`], and recordings keep their frames in sorted maps,
+//! so saving the same recording twice gives the same file and recordings diff cleanly.
+//!
+//! Long sessions can also be streamed to storage as they're recorded with a [`ReplayStream`], so a
+//! crash doesn't lose them, and read back with [`read_replay_stream`].
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::SystemTime};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerConfig, SlideBounce},
+    snapshot::{CharacterSnapshot, Checksum},
+};
+
+/// What a recording depends on besides its inputs. Playing back inputs with any of it changed
+/// moves the character differently than when they were recorded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayMetadata {
+    /// The name of the level the recording starts in.
+    pub level: String,
+    /// The fixed timestep of the custom schedule.
+    pub timestep: Duration,
+    /// A hash of the recorded character's [`CharacterControllerConfig`], see [`config_checksum`].
+    pub config_checksum: u64,
+    /// The seed of the [`ReplayRng`] when recording, which playback reseeds it with. Recordings
+    /// from before it was recorded have a seed of 0.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl ReplayMetadata {
+    pub fn new(
+        level: &str,
+        timestep: Duration,
+        config: &CharacterControllerConfig,
+        seed: u64,
+    ) -> Self {
+        Self {
+            level: level.to_string(),
+            timestep,
+            config_checksum: config_checksum(config),
+            seed,
+        }
+    }
+
+    /// Describes every way `current` differs from this recorded metadata, one line each. Empty if
+    /// the recording can be played back as is. The seed isn't compared, since playback reseeds.
+    pub fn mismatches(&self, current: &ReplayMetadata) -> Vec<String> {
+        let mut mismatches = Vec::new();
+
+        if self.level != current.level {
+            mismatches.push(format!(
+                "level: recorded in `{}`, but playing in `{}`",
+                self.level, current.level
+            ));
+        }
+
+        if self.timestep != current.timestep {
+            mismatches.push(format!(
+                "timestep: recorded at {:?}, but playing at {:?}",
+                self.timestep, current.timestep
+            ));
+        }
+
+        if self.config_checksum != current.config_checksum {
+            mismatches.push(format!(
+                "config: recorded with checksum {:#018x}, but playing with {:#018x}",
+                self.config_checksum, current.config_checksum
+            ));
+        }
+
+        mismatches
+    }
+}
+
+/// The solver's inputs and outputs for one character in one step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FrameRecord {
+    /// The character's state going into the step.
+    pub input: CharacterSnapshot,
+    /// The character's state coming out of the step.
+    pub output: CharacterSnapshot,
+    /// The bounces of the step's movement, with the surfaces they hit.
+    pub moved: Vec<SlideBounce>,
+    /// The bounces of the step's gravity pass.
+    pub fell: Vec<SlideBounce>,
+}
+
+impl FrameRecord {
+    pub fn new(
+        input: CharacterSnapshot,
+        output: CharacterSnapshot,
+        character_controller: &CharacterController,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            moved: character_controller.last_move.bounces().to_vec(),
+            fell: character_controller.last_fall.bounces().to_vec(),
+        }
+    }
+
+    /// Whether the step went exactly the same, bit for bit.
+    pub fn matches(&self, other: &FrameRecord) -> bool {
+        self.input.checksum() == other.input.checksum()
+            && self.output.checksum() == other.output.checksum()
+            && self.moved == other.moved
+            && self.fell == other.fell
+    }
+}
+
+/// The first step a playback went differently than its recording, with both versions of it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    pub frame: u32,
+    pub expected: FrameRecord,
+    pub actual: FrameRecord,
+}
+
+impl Divergence {
+    /// Compares a played back step to its recording, returning a [`Divergence`] if they differ.
+    pub fn check(frame: u32, expected: &FrameRecord, actual: &FrameRecord) -> Option<Self> {
+        (!expected.matches(actual)).then(|| Self {
+            frame,
+            expected: expected.clone(),
+            actual: actual.clone(),
+        })
+    }
+
+    /// Saves the divergence as `divergence-<frame>.ron` in `replay_store`, returning the name.
+    pub fn dump(&self, replay_store: &ReplayStore) -> Result<String, String> {
+        let name = format!("divergence-{}.ron", self.frame);
+        let contents = to_canonical_ron(self)?;
+
+        replay_store.0.save(&name, &contents)?;
+
+        Ok(name)
+    }
+}
+
+/// Serializes `value` to pretty RON that's the same on every platform: `\n` newlines, four space
+/// indents, and floats in their shortest form that parses back to the same value. Maps should be
+/// sorted, e.g. `BTreeMap`s, for the output to be stable.
+pub fn to_canonical_ron<T: Serialize>(value: &T) -> Result<String, String> {
+    let config = ron::ser::PrettyConfig::new()
+        .new_line("\n".to_string())
+        .indentor("    ".to_string());
+
+    ron::ser::to_string_pretty(value, config).map_err(|e| e.to_string())
+}
+
+/// The columns of [`frames_to_csv`].
+pub const CSV_HEADER: &str = "frame,x,y,z,velocity_x,velocity_y,velocity_z,grounded,bounces";
+
+/// Converts recorded frames to CSV, one row per frame in order, for plotting movement in a
+/// spreadsheet or notebook. Positions and velocities are at the end of each frame's step, with
+/// the velocity combining input, vertical and external velocity, and `bounces` counts the bounces
+/// of the step's movement.
+pub fn frames_to_csv<'a>(frames: impl IntoIterator<Item = (u32, &'a FrameRecord)>) -> String {
+    let mut frames: Vec<_> = frames.into_iter().collect();
+    frames.sort_by_key(|&(frame, _)| frame);
+
+    let mut csv = format!("{CSV_HEADER}\n");
+
+    for (frame, record) in frames {
+        let output = &record.output;
+        let position = output.translation;
+        let velocity = output.move_velocity
+            + output.external_velocity
+            + *output.up_direction * output.vertical_velocity;
+
+        csv.push_str(&format!(
+            "{frame},{},{},{},{},{},{},{},{}\n",
+            position.x,
+            position.y,
+            position.z,
+            velocity.x,
+            velocity.y,
+            velocity.z,
+            output.ground.is_some(),
+            record.moved.len(),
+        ));
+    }
+
+    csv
+}
+
+/// The random number generator for anything random in the demo, seeded from the recording when
+/// playing back so that randomness doesn't desync replays. Systems should draw from it in the
+/// custom schedule, so the draws happen in the same fixed steps on playback.
+#[derive(Resource, Clone, Debug)]
+pub struct ReplayRng {
+    seed: u64,
+    state: u64,
+}
+
+impl ReplayRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Seeds from the clock, for recording.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_nanos() as u64);
+
+        Self::new(nanos)
+    }
+
+    /// The seed the generator started with, to save in [`ReplayMetadata`].
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// SplitMix64, which is simple and has no bad seeds.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly random float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// A hash of every field of `config`, which is the same on every machine and build.
+pub fn config_checksum(config: &CharacterControllerConfig) -> u64 {
+    let mut checksum = Checksum::default();
+
+    checksum.floats(&[
+        config.gravity_scale,
+        config.acceleration,
+        config.air_control,
+        config.max_slope_angle,
+        config.slide_acceleration,
+        config.slide_control,
+        config.max_horizontal_speed,
+        config.terminal_velocity,
+        config.jump_buffer,
+        config.coyote_time,
+        config.swim_speed,
+        config.swim_drag,
+        config.thrust,
+        config.external_ground_damping,
+        config.external_air_damping,
+        config.vertical_damping,
+    ]);
+    checksum.scalars(&[config.max_step_height, config.min_step_depth]);
+    checksum.bytes(&config.air_jumps.to_le_bytes());
+    checksum.bytes(&[config.preserve_momentum as u8]);
+    checksum.bytes(&[config.solver_fallback as u8]);
+
+    checksum.0
+}
+
+pub trait ReplayStorage: Send + Sync {
+    /// Reads the recording called `name`.
+    fn load(&self, name: &str) -> Result<String, String>;
+
+    /// Writes `contents` as the recording called `name`, replacing any existing one.
+    fn save(&self, name: &str, contents: &str) -> Result<(), String>;
+
+    /// Starts the recording called `name` with `contents`, replacing any existing one. Unlike
+    /// [`Self::save`], the recording isn't finished, so more will be appended to it.
+    fn create(&self, name: &str, contents: &str) -> Result<(), String>;
+
+    /// Adds `contents` to the end of the recording called `name`, creating it if it doesn't exist.
+    fn append(&self, name: &str, contents: &str) -> Result<(), String>;
+}
+
+/// Writes a recording to a [`ReplayStorage`] while it's being recorded. The recording is a line
+/// with its [`ReplayMetadata`], followed by a line per chunk of `chunk_len` items, appended as
+/// each chunk fills up. Only the items since the last flush are kept in memory.
+pub struct ReplayStream<T> {
+    name: String,
+    chunk_len: usize,
+    pending: Vec<T>,
+}
+
+impl<T: Serialize> ReplayStream<T> {
+    /// Starts the recording called `name` with its `metadata`, replacing any existing one.
+    pub fn create(
+        storage: &dyn ReplayStorage,
+        name: &str,
+        metadata: &ReplayMetadata,
+        chunk_len: usize,
+    ) -> Result<Self, String> {
+        storage.create(name, &chunk_line(metadata)?)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            chunk_len: chunk_len.max(1),
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds `item` to the recording, flushing if that fills up a chunk.
+    pub fn push(&mut self, storage: &dyn ReplayStorage, item: T) -> Result<(), String> {
+        self.pending.push(item);
+
+        if self.pending.len() >= self.chunk_len {
+            self.flush(storage)?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends the items since the last flush as a chunk, if there are any.
+    pub fn flush(&mut self, storage: &dyn ReplayStorage) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        storage.append(&self.name, &chunk_line(&self.pending)?)?;
+        self.pending.clear();
+
+        Ok(())
+    }
+}
+
+fn chunk_line<T: Serialize + ?Sized>(value: &T) -> Result<String, String> {
+    let mut line = ron::ser::to_string(value).map_err(|e| e.to_string())?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Reads a recording written by a [`ReplayStream`], returning its metadata and items in the order
+/// they were pushed. A last chunk that was cut off, e.g. by a crash while appending it, is
+/// skipped.
+pub fn read_replay_stream<T: DeserializeOwned>(
+    contents: &str,
+) -> Result<(ReplayMetadata, Vec<T>), String> {
+    let mut lines = contents.lines().filter(|line| !line.is_empty()).peekable();
+
+    let metadata = ron::from_str(lines.next().ok_or("the stream is empty")?)
+        .map_err(|e| format!("metadata: {e}"))?;
+
+    let mut items = Vec::new();
+    let mut chunk_index = 0;
+
+    while let Some(line) = lines.next() {
+        match ron::from_str::<Vec<T>>(line) {
+            Ok(chunk) => items.extend(chunk),
+            Err(_) if lines.peek().is_none() && !contents.ends_with('\n') => break,
+            Err(e) => return Err(format!("chunk {chunk_index}: {e}")),
+        }
+
+        chunk_index += 1;
+    }
+
+    Ok((metadata, items))
+}
+
+/// The platform's [`ReplayStorage`].
+#[derive(Resource)]
+pub struct ReplayStore(pub Box<dyn ReplayStorage>);
+
+impl Default for ReplayStore {
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Self(Box::new(FileStorage));
+
+        #[cfg(target_arch = "wasm32")]
+        return Self(Box::new(BrowserStorage));
+    }
+}
+
+/// Stores recordings as files, with names as paths relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReplayStorage for FileStorage {
+    fn load(&self, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(name).map_err(|e| format!("{name}: {e}"))
+    }
+
+    fn save(&self, name: &str, contents: &str) -> Result<(), String> {
+        std::fs::write(name, contents).map_err(|e| format!("{name}: {e}"))
+    }
+
+    fn create(&self, name: &str, contents: &str) -> Result<(), String> {
+        self.save(name, contents)
+    }
+
+    fn append(&self, name: &str, contents: &str) -> Result<(), String> {
+        use std::io::Write;
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(name)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|e| format!("{name}: {e}"))
+    }
+}
+
+/// Stores recordings in `localStorage` under their names, and downloads them when saved.
+#[cfg(target_arch = "wasm32")]
+pub struct BrowserStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl BrowserStorage {
+    fn local_storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .ok_or("there is no window")?
+            .local_storage()
+            .map_err(js_error)?
+            .ok_or_else(|| "localStorage is unavailable".to_string())
+    }
+
+    fn download(name: &str, contents: &str) -> Result<(), String> {
+        use wasm_bindgen::{JsCast, JsValue};
+
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("there is no document")?;
+
+        let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+        let blob = web_sys::Blob::new_with_str_sequence(&parts).map_err(js_error)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+        let anchor: web_sys::HtmlAnchorElement = document
+            .create_element("a")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| "created element isn't an anchor")?;
+
+        anchor.set_href(&url);
+        anchor.set_download(name);
+        anchor.click();
+
+        web_sys::Url::revoke_object_url(&url).map_err(js_error)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ReplayStorage for BrowserStorage {
+    fn load(&self, name: &str) -> Result<String, String> {
+        Self::local_storage()?
+            .get_item(name)
+            .map_err(js_error)?
+            .ok_or_else(|| format!("{name}: no such recording"))
+    }
+
+    fn save(&self, name: &str, contents: &str) -> Result<(), String> {
+        Self::local_storage()?
+            .set_item(name, contents)
+            .map_err(js_error)?;
+
+        Self::download(name, contents)
+    }
+
+    /// Doesn't download, since the recording isn't finished.
+    fn create(&self, name: &str, contents: &str) -> Result<(), String> {
+        Self::local_storage()?
+            .set_item(name, contents)
+            .map_err(js_error)
+    }
+
+    /// Doesn't download, since the recording isn't finished.
+    fn append(&self, name: &str, contents: &str) -> Result<(), String> {
+        let storage = Self::local_storage()?;
+        let mut existing = storage
+            .get_item(name)
+            .map_err(js_error)?
+            .unwrap_or_default();
+        existing.push_str(contents);
+
+        storage.set_item(name, &existing).map_err(js_error)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn js_error(error: wasm_bindgen::JsValue) -> String {
+    format!("{error:?}")
+}
@@ -0,0 +1,240 @@
+//! Server-authoritative characters with [`bevy_replicon`].
+//!
+//! Clients send a [`CharacterInput`] for every step, and the server applies them in order to the
+//! character [`ControlledBy`] that client, which also needs a [`ServerInputBuffer`] and
+//! [`Replicated`]. After each step the server writes the character's [`ServerCharacterState`],
+//! which is replicated back.
+//!
+//! A client predicts its own character by giving it a [`PredictedCharacter`] and applying its
+//! input with [`PredictedCharacter::predict`] instead of setting the velocity directly. When a
+//! new server state arrives, the character is reset to it and the inputs the server hasn't
+//! processed yet are replayed on top.
+//!
+//! Ground entities in replicated state are server entities. The solver only uses the ground's
+//! geometry, so this doesn't matter as long as the level is the same on both ends.
+
+use std::collections::VecDeque;
+
+use avian3d::{math::AdjustPrecision, prelude::*};
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backend::AvianSpatialQuery,
+    character_controller::{
+        step_character, CharacterController, CharacterControllerConfig, CharacterControllerSet,
+        CustomMovement, SlideContext, SlopeSpeed, SurfaceMaterials, WalkableSurfaces,
+    },
+    schedule::{CustomFirst, CustomPostUpdate, CustomPreUpdate},
+    snapshot::CharacterSnapshot,
+};
+
+pub struct CharacterReplicationPlugin;
+
+impl Plugin for CharacterReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.replicate::<ControlledBy>()
+            .replicate::<ServerCharacterState>()
+            .add_client_event::<CharacterInput>(ChannelKind::Ordered)
+            .add_systems(
+                CustomFirst,
+                reconcile_predicted_characters.run_if(client_connected),
+            )
+            .add_systems(
+                CustomPreUpdate,
+                (buffer_client_inputs, apply_client_inputs)
+                    .chain()
+                    .run_if(server_running)
+                    .before(CharacterControllerSet),
+            )
+            .add_systems(
+                CustomPostUpdate,
+                update_server_states
+                    .run_if(server_running)
+                    .after(CharacterControllerSet),
+            );
+    }
+}
+
+/// The client that sends the inputs for this character.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ControlledBy(pub ClientId);
+
+/// The input for one step of a client's character. Sequence numbers increase by one every step.
+#[derive(Event, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CharacterInput {
+    pub sequence: u32,
+    pub velocity: Vec3,
+}
+
+/// The character's state on the server, along with the last input that was applied to reach it.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ServerCharacterState {
+    pub snapshot: CharacterSnapshot,
+    pub last_input: Option<u32>,
+}
+
+/// Inputs received by the server that haven't been applied yet.
+#[derive(Component, Default)]
+pub struct ServerInputBuffer {
+    pub inputs: VecDeque<CharacterInput>,
+    pub last_input: Option<u32>,
+}
+
+/// Predicts a client's own character ahead of the server.
+#[derive(Component, Default)]
+pub struct PredictedCharacter {
+    /// Inputs that have been applied locally but not acknowledged by the server yet.
+    pub pending_inputs: VecDeque<CharacterInput>,
+    next_sequence: u32,
+}
+
+impl PredictedCharacter {
+    /// Applies `velocity` to the character immediately and returns the input to send to the
+    /// server for this step.
+    pub fn predict(
+        &mut self,
+        character_controller: &mut CharacterController,
+        velocity: Vec3,
+    ) -> CharacterInput {
+        let input = CharacterInput {
+            sequence: self.next_sequence,
+            velocity,
+        };
+
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.pending_inputs.push_back(input);
+        character_controller.velocity = velocity;
+
+        input
+    }
+}
+
+fn buffer_client_inputs(
+    mut inputs: EventReader<FromClient<CharacterInput>>,
+    mut characters: Query<(&ControlledBy, &mut ServerInputBuffer)>,
+) {
+    for FromClient { client_id, event } in inputs.read() {
+        for (controlled_by, mut buffer) in &mut characters {
+            if controlled_by.0 == *client_id {
+                buffer.inputs.push_back(*event);
+            }
+        }
+    }
+}
+
+/// Applies one buffered input per step, so the server steps the character with the same inputs
+/// the client predicted with. When the buffer runs dry, the last input is kept.
+fn apply_client_inputs(mut characters: Query<(&mut ServerInputBuffer, &mut CharacterController)>) {
+    for (mut buffer, mut character_controller) in &mut characters {
+        if let Some(input) = buffer.inputs.pop_front() {
+            character_controller.velocity = input.velocity;
+            buffer.last_input = Some(input.sequence);
+        }
+    }
+}
+
+fn update_server_states(
+    mut commands: Commands,
+    characters: Query<(
+        Entity,
+        &CharacterController,
+        &Position,
+        &Transform,
+        Option<&ServerInputBuffer>,
+    )>,
+) {
+    for (entity, character_controller, position, transform, buffer) in &characters {
+        commands.entity(entity).insert(ServerCharacterState {
+            snapshot: CharacterSnapshot::save(character_controller, position, transform),
+            last_input: buffer.and_then(|buffer| buffer.last_input),
+        });
+    }
+}
+
+/// Resets predicted characters to newly received server states and replays the inputs the
+/// server hasn't applied yet.
+#[allow(clippy::type_complexity)]
+fn reconcile_predicted_characters(
+    mut characters: Query<
+        (
+            Entity,
+            &ServerCharacterState,
+            &mut PredictedCharacter,
+            &mut CharacterController,
+            &mut Position,
+            &mut Transform,
+            &Collider,
+            Option<&CharacterControllerConfig>,
+            Option<&SlopeSpeed>,
+            Option<&mut CustomMovement>,
+        ),
+        Changed<ServerCharacterState>,
+    >,
+    surface_materials: SurfaceMaterials,
+    walkable_surfaces: WalkableSurfaces,
+    spatial_query: AvianSpatialQuery,
+    time: Res<Time>,
+) {
+    let default_config = CharacterControllerConfig::default();
+
+    for (
+        entity,
+        server_state,
+        mut predicted,
+        mut character_controller,
+        mut position,
+        mut transform,
+        collider,
+        config,
+        slope_speed,
+        mut custom_movement,
+    ) in &mut characters
+    {
+        if let Some(last_input) = server_state.last_input {
+            while predicted
+                .pending_inputs
+                .front()
+                .is_some_and(|input| input.sequence.wrapping_sub(last_input) as i32 <= 0)
+            {
+                predicted.pending_inputs.pop_front();
+            }
+        }
+
+        server_state
+            .snapshot
+            .load(&mut character_controller, &mut position, &mut transform);
+
+        let config = config.unwrap_or(&default_config);
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        for input in &predicted.pending_inputs {
+            let context = SlideContext {
+                spatial_query: &spatial_query,
+                collider,
+                rotation: transform.rotation.adjust_precision(),
+                filter: &filter,
+                up: character_controller.up_direction,
+                max_slope_angle: config.max_slope_angle,
+                fallback: config.solver_fallback,
+                walkable_overrides: Some(&walkable_surfaces),
+            };
+
+            character_controller.velocity = input.velocity;
+            let gravity = character_controller.gravity;
+
+            step_character(
+                &mut character_controller,
+                &mut position.0,
+                &context,
+                config,
+                slope_speed,
+                custom_movement.as_deref_mut(),
+                &surface_materials,
+                gravity,
+                time.delta_seconds(),
+            );
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Sends characters back to a respawn point when they fall out of the level.
+
+use avian3d::{math::AdjustPrecision, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet, ControllerDisabled},
+    schedule::CustomPostUpdate,
+};
+
+pub struct RespawnPlugin;
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillPlane>()
+            .init_resource::<RespawnPoint>()
+            .add_systems(
+                CustomPostUpdate,
+                (activate_checkpoints, respawn_characters)
+                    .chain()
+                    .after(CharacterControllerSet),
+            );
+    }
+}
+
+/// Characters below this height are respawned.
+#[derive(Resource)]
+pub struct KillPlane {
+    pub height: f32,
+}
+
+impl Default for KillPlane {
+    fn default() -> Self {
+        Self { height: -20.0 }
+    }
+}
+
+/// Where characters are teleported to when they're killed.
+#[derive(Resource, Default)]
+pub struct RespawnPoint(pub Vec3);
+
+impl RespawnPoint {
+    /// Teleports the character to the respawn point.
+    pub fn respawn(&self, character_controller: &mut CharacterController) {
+        character_controller.teleport(self.0.adjust_precision());
+    }
+}
+
+/// A sensor volume that moves the [`RespawnPoint`] to `spawn_point` when a character enters it.
+///
+/// Needs a [`Sensor`] collider and [`CollidingEntities`] on the same entity.
+#[derive(Component)]
+pub struct Checkpoint {
+    pub spawn_point: Vec3,
+}
+
+fn activate_checkpoints(
+    checkpoints: Query<(&Checkpoint, &CollidingEntities)>,
+    characters: Query<(), With<CharacterController>>,
+    mut respawn_point: ResMut<RespawnPoint>,
+) {
+    for (checkpoint, colliding_entities) in &checkpoints {
+        if respawn_point.0 != checkpoint.spawn_point
+            && colliding_entities
+                .iter()
+                .any(|&entity| characters.contains(entity))
+        {
+            respawn_point.0 = checkpoint.spawn_point;
+        }
+    }
+}
+
+fn respawn_characters(
+    mut characters: Query<(&mut CharacterController, &Position), Without<ControllerDisabled>>,
+    kill_plane: Res<KillPlane>,
+    respawn_point: Res<RespawnPoint>,
+) {
+    // The solved world-space position, since the transform of a parented character is local to
+    // its parent and isn't synced from the position until the next physics step.
+    for (mut character_controller, position) in &mut characters {
+        if position.y < kill_plane.height.adjust_precision() {
+            respawn_point.respawn(&mut character_controller);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! RON-driven reproduction cases for the character controller, run headless on a [`TestWorld`].
+//!
+//! ```ron
+//! (
+//!     blocks: [
+//!         (translation: (0.0, -0.5, 0.0), size: (20.0, 1.0, 20.0)),
+//!         (translation: (5.0, 2.0, 0.0), size: (1.0, 4.0, 20.0)),
+//!     ],
+//!     character: (translation: (0.0, 1.01, 0.0)),
+//!     input: [(frames: 64, velocity: (15.0, 0.0, 0.0))],
+//!     expect: (translation: Some((3.995, 1.005, 0.0)), grounded: Some(true)),
+//! )
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{character_controller::CharacterController, testing::TestWorld};
+
+#[derive(Deserialize, Debug)]
+pub struct Scenario {
+    #[serde(default)]
+    pub blocks: Vec<Block>,
+    pub character: CharacterStart,
+    pub input: Vec<InputSegment>,
+    pub expect: Expectation,
+}
+
+/// A static cuboid. Ramps are blocks with a rotation.
+#[derive(Deserialize, Debug)]
+pub struct Block {
+    pub translation: Vec3,
+    pub size: Vec3,
+    /// Euler angles in degrees, applied in XYZ order.
+    #[serde(default)]
+    pub rotation: Vec3,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CharacterStart {
+    pub translation: Vec3,
+}
+
+/// Holds `velocity` as the character's input for `frames` steps.
+#[derive(Deserialize, Debug)]
+pub struct InputSegment {
+    pub frames: usize,
+    pub velocity: Vec3,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Expectation {
+    #[serde(default)]
+    pub translation: Option<Vec3>,
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f32,
+    #[serde(default)]
+    pub grounded: Option<bool>,
+}
+
+fn default_tolerance() -> f32 {
+    0.01
+}
+
+#[derive(Debug)]
+pub struct ScenarioOutcome {
+    pub translation: Vec3,
+    pub grounded: bool,
+}
+
+impl Scenario {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        ron::de::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn run(&self) -> ScenarioOutcome {
+        let mut world = TestWorld::new();
+
+        for block in &self.blocks {
+            let rotation = Quat::from_euler(
+                EulerRot::XYZ,
+                block.rotation.x.to_radians(),
+                block.rotation.y.to_radians(),
+                block.rotation.z.to_radians(),
+            );
+
+            world.spawn_box(block.translation, rotation, block.size);
+        }
+
+        let character = world.spawn_character(self.character.translation, Vec3::ZERO);
+
+        for segment in &self.input {
+            world
+                .world_mut()
+                .get_mut::<CharacterController>(character)
+                .unwrap()
+                .velocity = segment.velocity;
+
+            world.step(segment.frames);
+        }
+
+        ScenarioOutcome {
+            translation: world.translation(character),
+            grounded: world.controller(character).is_grounded(),
+        }
+    }
+
+    /// Runs the scenario and describes every way the outcome differs from what was expected.
+    pub fn check(&self) -> Result<ScenarioOutcome, String> {
+        let outcome = self.run();
+        let mut failures = Vec::new();
+
+        if let Some(translation) = self.expect.translation {
+            if !outcome
+                .translation
+                .abs_diff_eq(translation, self.expect.tolerance)
+            {
+                failures.push(format!(
+                    "expected translation {translation} but ended at {}",
+                    outcome.translation
+                ));
+            }
+        }
+
+        if let Some(grounded) = self.expect.grounded {
+            if outcome.grounded != grounded {
+                failures.push(format!(
+                    "expected grounded to be {grounded} but it was {}",
+                    outcome.grounded
+                ));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(outcome)
+        } else {
+            Err(failures.join(", "))
+        }
+    }
+}
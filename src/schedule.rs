@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::{
     app::{MainScheduleOrder, RunFixedMainLoop},
-    ecs::schedule::ScheduleLabel,
+    ecs::{schedule::ScheduleLabel, system::RunSystemOnce},
     gizmos::{
         clear_gizmo_context, collect_requested_gizmos, end_gizmo_context,
         gizmos::{GizmoStorage, Swap},
@@ -11,7 +11,8 @@ use bevy::{
     prelude::*,
 };
 
-use crate::CustomStepping;
+/// The fixed timestep of the custom schedule.
+pub const TIMESTEP: Duration = Duration::from_micros(15625);
 
 pub struct SchedulePlugin;
 
@@ -24,11 +25,18 @@ impl Plugin for SchedulePlugin {
         app.add_plugins(ClearCustomGizmoContextPlugin)
             .init_schedule(RunCustomSchedule)
             .init_resource::<Time<CustomTime>>()
+            .init_resource::<CustomStepping>()
             .add_systems(CustomMain, run_custom_main)
             .add_systems(RunCustomSchedule, run_custom_schedule);
     }
 }
 
+/// When enabled, the custom schedule only runs through [`step_custom_schedule`].
+#[derive(Resource, Default)]
+pub struct CustomStepping {
+    pub enabled: bool,
+}
+
 #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RunCustomSchedule;
 
@@ -71,11 +79,10 @@ fn run_custom_main(world: &mut World) {
 }
 
 fn expend_custom(a: &mut Time<CustomTime>) -> bool {
-    let timestep = Duration::from_micros(15625);
-    if let Some(new_value) = a.context_mut().overstep.checked_sub(timestep) {
+    if let Some(new_value) = a.context_mut().overstep.checked_sub(TIMESTEP) {
         // reduce accumulated and increase elapsed by period
         a.context_mut().overstep = new_value;
-        a.advance_by(timestep);
+        a.advance_by(TIMESTEP);
         true
     } else {
         // no more periods left in accumulated
@@ -107,12 +114,25 @@ pub fn run_custom_schedule(world: &mut World) {
     *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
 }
 
+/// Runs one step of the custom schedule, e.g. from an exclusive system while
+/// [`CustomStepping`] is enabled. Gizmos drawn in the step are kept until the next step, however
+/// many frames that takes, so the last step's casts can be studied while paused.
 pub fn step_custom_schedule(world: &mut World) {
+    world
+        .resource_mut::<Time<CustomTime>>()
+        .advance_by(TIMESTEP);
+
+    // Like `run_custom_schedule`, which runs inside the gizmo context, so that gizmos drawn
+    // earlier in the frame aren't collected into the step's.
+    world.run_system_once(start_gizmo_context::<DefaultGizmoConfigGroup, CustomGizmoContext>);
+
     let _ = world.try_schedule_scope(CustomMain, |world, schedule| {
         *world.resource_mut::<Time>() = world.resource::<Time<CustomTime>>().as_generic();
         schedule.run(world);
     });
 
+    world.run_system_once(end_gizmo_context::<DefaultGizmoConfigGroup, CustomGizmoContext>);
+
     *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
 }
 
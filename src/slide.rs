@@ -0,0 +1,39 @@
+//! The parts of collide-and-slide that don't depend on the number of dimensions, shared by the 3D
+//! controller and the 2D one in `kcc2d`.
+
+use std::ops::{Add, Mul, Sub};
+
+use avian3d::math::{Scalar, Vector};
+
+/// A vector the solver can slide along planes (or lines, in 2D).
+pub trait SlideVector:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Scalar, Output = Self>
+{
+    fn dot(self, other: Self) -> Scalar;
+}
+
+impl SlideVector for Vector {
+    fn dot(self, other: Self) -> Scalar {
+        Vector::dot(self, other)
+    }
+}
+
+/// Removes the part of `velocity` that goes into the surface with `normal`.
+pub fn clip_velocity<V: SlideVector>(velocity: V, normal: V) -> V {
+    velocity - normal * velocity.dot(normal)
+}
+
+/// Nudges `velocity` away from the surface with `normal` if it's nearly parallel to one of
+/// `planes`, the normals already slid along this move, so the character doesn't get wedged
+/// between them.
+pub fn nudge_off_planes<V: SlideVector>(velocity: V, normal: V, planes: &[V]) -> V {
+    let mut velocity = velocity;
+
+    for plane in planes {
+        if normal.dot(*plane) > 0.99 {
+            velocity = velocity + normal * 0.01;
+        }
+    }
+
+    velocity
+}
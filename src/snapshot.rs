@@ -0,0 +1,212 @@
+//! Saving and restoring character state, for rollback netcode like GGRS.
+//!
+//! Rollback frameworks that snapshot components directly can register [`CharacterController`],
+//! [`Position`] and [`Transform`], since they're all [`Clone`]. [`CharacterSnapshot`] is for ones
+//! that need the state as plain data, and provides a checksum for detecting desyncs.
+
+use avian3d::{
+    math::{Scalar, Vector},
+    prelude::*,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::character_controller::{
+    BufferedJump, CharacterController, GroundInfo, MovementMode, Sliding,
+};
+
+/// Everything that affects how a character moves in its next step.
+///
+/// The solver output of the last step isn't included, because it doesn't affect the next step.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CharacterSnapshot {
+    pub translation: Vector,
+    pub rotation: Quat,
+    pub velocity: Vec3,
+    pub move_velocity: Vec3,
+    pub vertical_velocity: f32,
+    pub external_velocity: Vec3,
+    pub up_direction: Dir3,
+    pub ground: Option<GroundInfo>,
+    pub sliding: Option<Sliding>,
+    /// Missing from snapshots saved before movement modes, which were all walking or falling.
+    #[serde(default)]
+    pub mode: MovementMode,
+    pub time_since_grounded: f32,
+    pub air_jumps_used: u32,
+    pub teleport: Option<Vector>,
+    pub root_motion: Option<Vec3>,
+    pub jump: Option<BufferedJump>,
+    /// The rotation the character was last resolved at, which a rotation that can't be
+    /// depenetrated backs off towards. Missing from snapshots saved before it existed.
+    #[serde(default)]
+    pub solved_rotation: Option<Quat>,
+}
+
+impl CharacterSnapshot {
+    pub fn save(
+        character_controller: &CharacterController,
+        position: &Position,
+        transform: &Transform,
+    ) -> Self {
+        Self {
+            translation: position.0,
+            rotation: transform.rotation,
+            velocity: character_controller.velocity,
+            move_velocity: character_controller.move_velocity,
+            vertical_velocity: character_controller.vertical_velocity,
+            external_velocity: character_controller.external_velocity,
+            up_direction: character_controller.up_direction,
+            ground: character_controller.ground,
+            sliding: character_controller.sliding,
+            mode: character_controller.mode,
+            time_since_grounded: character_controller.time_since_grounded,
+            air_jumps_used: character_controller.air_jumps_used,
+            teleport: character_controller.teleport,
+            root_motion: character_controller.root_motion,
+            jump: character_controller.jump,
+            solved_rotation: character_controller.solved_rotation,
+        }
+    }
+
+    pub fn load(
+        &self,
+        character_controller: &mut CharacterController,
+        position: &mut Position,
+        transform: &mut Transform,
+    ) {
+        position.0 = self.translation;
+        transform.rotation = self.rotation;
+        character_controller.velocity = self.velocity;
+        character_controller.move_velocity = self.move_velocity;
+        character_controller.vertical_velocity = self.vertical_velocity;
+        character_controller.external_velocity = self.external_velocity;
+        character_controller.up_direction = self.up_direction;
+        character_controller.ground = self.ground;
+        character_controller.sliding = self.sliding;
+        character_controller.mode = self.mode;
+        character_controller.stepped_mode = self.mode;
+        character_controller.time_since_grounded = self.time_since_grounded;
+        character_controller.air_jumps_used = self.air_jumps_used;
+        character_controller.teleport = self.teleport;
+        character_controller.root_motion = self.root_motion;
+        character_controller.jump = self.jump;
+        character_controller.solved_rotation = self.solved_rotation;
+    }
+
+    /// A hash of the snapshot's exact bits, which is the same on every machine and build.
+    pub fn checksum(&self) -> u64 {
+        let mut checksum = Checksum::default();
+
+        checksum.vector(self.translation);
+        checksum.floats(&self.rotation.to_array());
+        checksum.vec3(self.velocity);
+        checksum.vec3(self.move_velocity);
+        checksum.floats(&[self.vertical_velocity]);
+        checksum.vec3(self.external_velocity);
+        checksum.vec3(self.up_direction.as_vec3());
+
+        match &self.ground {
+            Some(ground) => {
+                checksum.bytes(&[1]);
+                checksum.bytes(&ground.entity.to_bits().to_le_bytes());
+                checksum.vector(ground.point);
+                checksum.vector(ground.normal);
+                checksum.scalars(&[ground.distance]);
+                checksum.floats(&[
+                    ground.material.traction,
+                    ground.material.speed,
+                    ground.traction,
+                ]);
+                checksum.vec3(ground.velocity);
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        match &self.sliding {
+            Some(sliding) => {
+                checksum.bytes(&[1]);
+                checksum.bytes(&sliding.entity.to_bits().to_le_bytes());
+                checksum.vector(sliding.normal);
+                checksum.vec3(sliding.velocity);
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        checksum.bytes(&[self.mode as u8]);
+        checksum.floats(&[self.time_since_grounded]);
+        checksum.bytes(&self.air_jumps_used.to_le_bytes());
+
+        match self.teleport {
+            Some(teleport) => {
+                checksum.bytes(&[1]);
+                checksum.vector(teleport);
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        match self.root_motion {
+            Some(root_motion) => {
+                checksum.bytes(&[1]);
+                checksum.vec3(root_motion);
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        match self.jump {
+            Some(jump) => {
+                checksum.bytes(&[1]);
+                checksum.floats(&[jump.speed, jump.age]);
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        match self.solved_rotation {
+            Some(solved_rotation) => {
+                checksum.bytes(&[1]);
+                checksum.floats(&solved_rotation.to_array());
+            }
+            None => checksum.bytes(&[0]),
+        }
+
+        checksum.0
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hasher is stable across Rust versions.
+pub(crate) struct Checksum(pub(crate) u64);
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Checksum {
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub(crate) fn floats(&mut self, floats: &[f32]) {
+        for float in floats {
+            self.bytes(&float.to_bits().to_le_bytes());
+        }
+    }
+
+    pub(crate) fn vec3(&mut self, vector: Vec3) {
+        self.floats(&vector.to_array());
+    }
+
+    pub(crate) fn scalars(&mut self, scalars: &[Scalar]) {
+        for scalar in scalars {
+            self.bytes(&scalar.to_bits().to_le_bytes());
+        }
+    }
+
+    pub(crate) fn vector(&mut self, vector: Vector) {
+        self.scalars(&vector.to_array());
+    }
+}
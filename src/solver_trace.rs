@@ -0,0 +1,145 @@
+//! Traces of the solver's internals for bug reports. Add a [`SolverTrace`] to a character to record
+//! every bounce of its steps in a range of frames, with the direction it cast in, what it hit, the
+//! movement it projected onto the surface, and the planes it had slid along so far. Once the range
+//! ends, the trace is saved as RON to the [`ReplayStore`], so a report can include exactly what
+//! the solver did rather than a video of it.
+//!
+//! Frames are the steps of the custom schedule, counted from 1 like the frames of the demo's
+//! recordings, so the frames a recording diverged at or misbehaved in can be traced by playing it
+//! back.
+
+use std::ops::Range;
+
+use avian3d::math::{AdjustPrecision, Scalar, Vector};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    character_controller::{
+        CharacterController, ControllerDisabled, KccSet, MoveAndSlideOutput, SlideHit,
+    },
+    replay::{to_canonical_ron, ReplayStore},
+    schedule::{CustomPostUpdate, TIMESTEP},
+};
+
+pub struct SolverTracePlugin;
+
+impl Plugin for SolverTracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayStore>().add_systems(
+            CustomPostUpdate,
+            record_solver_traces.in_set(KccSet::PostMove),
+        );
+    }
+}
+
+/// Records the solver internals of this character's steps in `frames`, and saves them as `name`
+/// once the frames have passed, removing the trace. Nothing is saved if the app exits first.
+#[derive(Component, Clone, Debug)]
+pub struct SolverTrace {
+    pub name: String,
+    pub frames: Range<u32>,
+    steps: Vec<TraceStep>,
+}
+
+impl SolverTrace {
+    pub fn new(name: impl Into<String>, frames: Range<u32>) -> Self {
+        Self {
+            name: name.into(),
+            frames,
+            steps: Vec::new(),
+        }
+    }
+}
+
+/// The bounces of one step of a [`SolverTrace`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub frame: u32,
+    /// The bounces of the step's movement.
+    pub moved: Vec<TraceBounce>,
+    /// The bounces of the step's gravity pass.
+    pub fell: Vec<TraceBounce>,
+}
+
+/// One iteration of the bounce loop in [`crate::character_controller::move_and_slide`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceBounce {
+    pub origin: Vector,
+    /// The direction the bounce cast in.
+    pub direction: Dir3,
+    pub distance: Scalar,
+    pub hit: Option<SlideHit>,
+    /// The movement left after sliding along the hit surface, which the next bounce casts. `None`
+    /// if the slide stopped here.
+    pub projected: Option<Vector>,
+    /// The normals of the surfaces slid along so far, including this bounce's.
+    pub planes: Vec<Vector>,
+}
+
+impl TraceBounce {
+    /// Rebuilds the bounce loop's state from its output. Every bounce that's followed by another,
+    /// or that ran out of bounces, slid along the surface it hit, and the movement it projected
+    /// onto it is what the next bounce cast, or what was left.
+    pub fn from_output(output: &MoveAndSlideOutput) -> Vec<Self> {
+        let bounces = output.bounces();
+        let mut planes = Vec::new();
+
+        bounces
+            .iter()
+            .enumerate()
+            .map(|(index, bounce)| {
+                let projected = match bounces.get(index + 1) {
+                    Some(next) => Some(next.direction.as_vec3().adjust_precision() * next.distance),
+                    None => output.exhausted.map(|exhausted| exhausted.remaining),
+                };
+
+                if let (Some(hit), Some(_)) = (bounce.hit, projected) {
+                    planes.push(hit.normal);
+                }
+
+                Self {
+                    origin: bounce.origin,
+                    direction: bounce.direction,
+                    distance: bounce.distance,
+                    hit: bounce.hit,
+                    projected,
+                    planes: planes.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn record_solver_traces(
+    mut commands: Commands,
+    mut query: Query<(Entity, &CharacterController, &mut SolverTrace), Without<ControllerDisabled>>,
+    replay_store: Res<ReplayStore>,
+    time: Res<Time>,
+) {
+    let frame = (time.elapsed().as_nanos() / TIMESTEP.as_nanos()) as u32;
+
+    for (entity, character_controller, mut trace) in &mut query {
+        if trace.frames.contains(&frame) {
+            trace.steps.push(TraceStep {
+                frame,
+                moved: TraceBounce::from_output(&character_controller.last_move),
+                fell: TraceBounce::from_output(&character_controller.last_fall),
+            });
+        }
+
+        if frame < trace.frames.end {
+            continue;
+        }
+
+        let saved = to_canonical_ron(&trace.steps)
+            .and_then(|contents| replay_store.0.save(&trace.name, &contents));
+
+        match saved {
+            Ok(()) => info!("saved a solver trace of {entity} to {}", trace.name),
+            Err(error) => warn!("couldn't save a solver trace of {entity}: {error}"),
+        }
+
+        commands.entity(entity).remove::<SolverTrace>();
+    }
+}
@@ -0,0 +1,167 @@
+//! Rolling statistics of how hard the solver works, for tuning configs and catching levels with
+//! geometry that makes characters bounce around, get stuck, or push out of walls. The
+//! [`SolverTelemetry`] covers the last `window` steps of every character.
+
+use std::collections::VecDeque;
+
+use avian3d::math::AsF32;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, ControllerDisabled, KccSet, SolverFallback},
+    schedule::CustomPostUpdate,
+};
+
+pub struct SolverTelemetryPlugin;
+
+impl Plugin for SolverTelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolverTelemetry>().add_systems(
+            CustomPostUpdate,
+            record_solver_telemetry.in_set(KccSet::PostMove),
+        );
+    }
+}
+
+/// Statistics of every character's steps, added by the [`SolverTelemetryPlugin`].
+#[derive(Resource, Clone, Debug)]
+pub struct SolverTelemetry {
+    /// How many steps the statistics cover.
+    pub window: usize,
+    /// Bounces per character per step, of both its movement and its gravity pass.
+    pub bounces: RollingStat,
+    /// How far characters were pushed out of geometry per step, by resolving their rotation or
+    /// by [`SolverFallback::Depenetrate`].
+    pub depenetration: RollingStat,
+    /// 1 for each character step that ran out of bounces, and 0 otherwise, so the average is the
+    /// fraction of steps and the total is how many.
+    pub exhausted: RollingStat,
+    /// How long each character's step took to solve, in seconds.
+    pub step_time: RollingStat,
+}
+
+impl Default for SolverTelemetry {
+    fn default() -> Self {
+        Self {
+            window: 256,
+            bounces: default(),
+            depenetration: default(),
+            exhausted: default(),
+            step_time: default(),
+        }
+    }
+}
+
+/// A statistic over the last steps, each of which can have a sample from any number of characters.
+#[derive(Clone, Debug, Default)]
+pub struct RollingStat {
+    steps: VecDeque<StepSamples>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct StepSamples {
+    sum: f32,
+    max: f32,
+    count: u32,
+}
+
+impl RollingStat {
+    /// The average of every sample in the window, or 0 if there are none.
+    pub fn average(&self) -> f32 {
+        let count: u32 = self.steps.iter().map(|step| step.count).sum();
+
+        if count == 0 {
+            return 0.0;
+        }
+
+        self.total() / count as f32
+    }
+
+    /// The largest sample in the window, or 0 if there are none.
+    pub fn max(&self) -> f32 {
+        self.steps.iter().map(|step| step.max).fold(0.0, f32::max)
+    }
+
+    /// The sum of every sample in the window.
+    pub fn total(&self) -> f32 {
+        self.steps.iter().map(|step| step.sum).sum()
+    }
+
+    /// Adds a step's samples, dropping the oldest steps past `window`.
+    pub fn push(&mut self, samples: impl IntoIterator<Item = f32>, window: usize) {
+        let step = samples
+            .into_iter()
+            .fold(StepSamples::default(), |step, sample| StepSamples {
+                sum: step.sum + sample,
+                max: step.max.max(sample),
+                count: step.count + 1,
+            });
+
+        self.steps.push_back(step);
+
+        while self.steps.len() > window {
+            self.steps.pop_front();
+        }
+    }
+}
+
+fn record_solver_telemetry(
+    characters: Query<&CharacterController, Without<ControllerDisabled>>,
+    mut telemetry: ResMut<SolverTelemetry>,
+) {
+    let window = telemetry.window;
+    let passes = |character_controller: &CharacterController| {
+        [
+            character_controller.last_move,
+            character_controller.last_fall,
+        ]
+    };
+
+    telemetry.bounces.push(
+        characters.iter().map(|character_controller| {
+            passes(character_controller)
+                .iter()
+                .map(|output| output.bounce_count as f32)
+                .sum::<f32>()
+        }),
+        window,
+    );
+    telemetry.depenetration.push(
+        characters.iter().map(|character_controller| {
+            let fallback = passes(character_controller)
+                .iter()
+                .filter_map(|output| output.exhausted)
+                .filter(|exhausted| exhausted.fallback == SolverFallback::Depenetrate)
+                .map(|exhausted| {
+                    exhausted
+                        .resolved_translation
+                        .distance(exhausted.translation)
+                        .f32()
+                })
+                .sum::<f32>();
+
+            character_controller.depenetration.f32() + fallback
+        }),
+        window,
+    );
+    telemetry.exhausted.push(
+        characters.iter().map(|character_controller| {
+            let exhausted = passes(character_controller)
+                .iter()
+                .any(|output| output.exhausted.is_some());
+
+            if exhausted {
+                1.0
+            } else {
+                0.0
+            }
+        }),
+        window,
+    );
+    telemetry.step_time.push(
+        characters
+            .iter()
+            .map(|character_controller| character_controller.step_time.as_secs_f32()),
+        window,
+    );
+}
@@ -0,0 +1,153 @@
+//! A headless app for exercising the character controller in tests and benchmarks.
+
+use avian3d::{
+    math::{AdjustPrecision, AsF32},
+    prelude::*,
+};
+use bevy::{ecs::system::SystemState, prelude::*, scene::ScenePlugin};
+
+use crate::{
+    backend::AvianSpatialQuery,
+    character_controller::{CharacterController, CharacterControllerPlugin},
+    schedule::{step_custom_schedule, CustomStepping, CustomUpdate, SchedulePlugin},
+};
+
+/// The character collider used by the demo.
+pub fn character_collider() -> Collider {
+    Collider::cylinder(0.5, 2.0)
+}
+
+/// An [`App`] with [`MinimalPlugins`], avian, and the character controller, stepped manually.
+pub struct TestWorld {
+    app: App,
+    ready: bool,
+    spatial_query: Option<SystemState<AvianSpatialQuery<'static, 'static>>>,
+}
+
+impl Default for TestWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestWorld {
+    pub fn new() -> Self {
+        let mut app = App::new();
+
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+            bevy::gizmos::GizmoPlugin,
+            PhysicsPlugins::new(CustomUpdate),
+            SchedulePlugin,
+            CharacterControllerPlugin,
+        ))
+        .init_asset::<Mesh>()
+        .insert_resource(CustomStepping { enabled: true });
+
+        Self {
+            app,
+            ready: false,
+            spatial_query: None,
+        }
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Spawns a static box with the given full extents.
+    pub fn spawn_box(&mut self, translation: Vec3, rotation: Quat, size: Vec3) -> Entity {
+        let extents = size.adjust_precision();
+
+        self.world_mut()
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(extents.x, extents.y, extents.z),
+                TransformBundle::from_transform(
+                    Transform::from_translation(translation).with_rotation(rotation),
+                ),
+            ))
+            .id()
+    }
+
+    /// Spawns a character with the demo's collider.
+    pub fn spawn_character(&mut self, translation: Vec3, velocity: Vec3) -> Entity {
+        self.world_mut()
+            .spawn((
+                CharacterController {
+                    velocity,
+                    ..default()
+                },
+                RigidBody::Kinematic,
+                character_collider(),
+                TransformBundle::from_transform(Transform::from_translation(translation)),
+            ))
+            .id()
+    }
+
+    /// Runs the custom schedule `steps` times.
+    pub fn step(&mut self, steps: usize) {
+        self.prepare();
+
+        for _ in 0..steps {
+            step_custom_schedule(self.world_mut());
+        }
+    }
+
+    /// Finishes building the app and runs startup. Called by [`TestWorld::step`], but needed
+    /// before querying the world if the app hasn't been stepped yet.
+    pub fn prepare(&mut self) {
+        if self.ready {
+            return;
+        }
+
+        self.ready = true;
+        self.app.finish();
+        self.app.cleanup();
+        self.app.update();
+    }
+
+    /// Runs a single step so spawned colliders are added to the spatial query pipeline.
+    pub fn sync_colliders(&mut self) {
+        self.step(1);
+    }
+
+    pub fn spatial_query_pipeline(&self) -> &SpatialQueryPipeline {
+        self.world().resource::<SpatialQueryPipeline>()
+    }
+
+    /// The spatial queries the character controller makes, for running the solver directly.
+    pub fn spatial_query(&mut self) -> AvianSpatialQuery<'_, '_> {
+        let state = self
+            .spatial_query
+            .get_or_insert_with(|| SystemState::new(self.app.world_mut()));
+
+        state.get(self.app.world())
+    }
+
+    /// The entity's [`Position`], which is where the character controller moves characters to.
+    /// Their [`Transform`] isn't updated until the next physics step.
+    pub fn translation(&self, entity: Entity) -> Vec3 {
+        self.world().get::<Position>(entity).unwrap().0.f32()
+    }
+
+    pub fn controller(&self, entity: Entity) -> &CharacterController {
+        self.world().get::<CharacterController>(entity).unwrap()
+    }
+}
@@ -0,0 +1,99 @@
+//! Volumes that slow down or speed up time for characters inside them, like bullet time bubbles.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{CharacterController, CharacterControllerSet, CharacterTimeScale},
+    schedule::CustomPostUpdate,
+};
+
+pub struct TimeDilationPlugin;
+
+impl Plugin for TimeDilationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            apply_time_dilation_zones.before(CharacterControllerSet),
+        );
+    }
+}
+
+/// A sensor volume that scales time for characters inside it, through their
+/// [`CharacterTimeScale`]. Overlapping zones multiply, so a bubble inside a bubble is slower still,
+/// and they multiply onto whatever time scale the character already had, which it gets back once
+/// it leaves every zone.
+///
+/// Needs a [`Sensor`] collider and [`CollidingEntities`] on the same entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct TimeDilationZone {
+    pub time_scale: f32,
+}
+
+/// The [`CharacterTimeScale`] a character had before a [`TimeDilationZone`] changed it, to multiply
+/// zones onto and to put back once it leaves every zone.
+#[derive(Component, Clone, Copy)]
+struct ZoneTimeScaleApplied {
+    time_scale: Option<f32>,
+}
+
+fn apply_time_dilation_zones(
+    mut commands: Commands,
+    zones: Query<(&TimeDilationZone, &CollidingEntities)>,
+    mut characters: Query<
+        (
+            Entity,
+            Option<&mut CharacterTimeScale>,
+            Option<&ZoneTimeScaleApplied>,
+        ),
+        With<CharacterController>,
+    >,
+) {
+    for (entity, character_time_scale, applied) in &mut characters {
+        let mut zone_time_scale = None;
+
+        for (zone, colliding_entities) in &zones {
+            if colliding_entities.contains(&entity) {
+                zone_time_scale = Some(zone_time_scale.unwrap_or(1.0) * zone.time_scale);
+            }
+        }
+
+        let Some(zone_time_scale) = zone_time_scale else {
+            if let Some(applied) = applied {
+                match applied.time_scale {
+                    Some(previous) => {
+                        commands
+                            .entity(entity)
+                            .insert(CharacterTimeScale(previous))
+                            .remove::<ZoneTimeScaleApplied>();
+                    }
+                    None => {
+                        commands
+                            .entity(entity)
+                            .remove::<(CharacterTimeScale, ZoneTimeScaleApplied)>();
+                    }
+                }
+            }
+
+            continue;
+        };
+
+        let applied = applied.copied().unwrap_or_else(|| {
+            let applied = ZoneTimeScaleApplied {
+                time_scale: character_time_scale.as_ref().map(|time_scale| time_scale.0),
+            };
+            commands.entity(entity).insert(applied);
+            applied
+        });
+        let time_scale = applied.time_scale.unwrap_or(1.0) * zone_time_scale;
+
+        match character_time_scale {
+            Some(mut character_time_scale) => character_time_scale.0 = time_scale,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(CharacterTimeScale(time_scale));
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! An egui window for tuning the config of debugged characters while playing, with the `tuning`
+//! feature.
+
+use std::ops::RangeInclusive;
+
+use avian3d::prelude::*;
+use bevy::{asset::AssetPath, prelude::*};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::{
+    character_controller::{
+        CharacterControllerConfig, DebugCharacterController, SolverFallback, SKIN_WIDTH,
+    },
+    config_asset::KccConfigHandle,
+};
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+
+        app.add_systems(Update, tuning_panel);
+    }
+}
+
+/// Shows sliders for avian's [`Gravity`] and every field of the [`CharacterControllerConfig`] of
+/// characters with [`DebugCharacterController`]. Configs are only written when a value changes.
+///
+/// Configs that came from a [`KccConfigHandle`] can be saved back to their file.
+fn tuning_panel(
+    mut contexts: EguiContexts,
+    mut gravity: ResMut<Gravity>,
+    mut query: Query<
+        (&mut CharacterControllerConfig, Option<&KccConfigHandle>),
+        With<DebugCharacterController>,
+    >,
+    asset_server: Res<AssetServer>,
+    mut save_result: Local<Option<Result<String, String>>>,
+) {
+    egui::Window::new("Controller tuning").show(contexts.ctx_mut(), |ui| {
+        let mut gravity_y = gravity.0.y;
+
+        ui.add(egui::Slider::new(&mut gravity_y, -30.0..=30.0).text("gravity"));
+
+        if gravity_y != gravity.0.y {
+            gravity.0.y = gravity_y;
+        }
+
+        ui.label(format!("skin width: {SKIN_WIDTH} (a constant)"));
+
+        for (mut config, handle) in &mut query {
+            ui.separator();
+
+            let mut edited = *config;
+
+            ui.horizontal(|ui| {
+                for (name, preset) in [
+                    ("default", CharacterControllerConfig::default()),
+                    ("platformer", CharacterControllerConfig::platformer()),
+                    ("shooter", CharacterControllerConfig::shooter()),
+                    ("source-like", CharacterControllerConfig::source_like()),
+                ] {
+                    if ui.button(name).clicked() {
+                        edited = preset;
+                    }
+                }
+            });
+
+            config_sliders(ui, &mut edited);
+
+            if edited != *config {
+                *config = edited;
+            }
+
+            let Some(path) = handle.and_then(|handle| asset_server.get_path(&handle.0)) else {
+                continue;
+            };
+
+            if ui.button(format!("save to {path}")).clicked() {
+                *save_result = Some(save(&path, &config).map(|()| format!("saved {path}")));
+            }
+        }
+
+        match &*save_result {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => {}
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save(path: &AssetPath, config: &CharacterControllerConfig) -> Result<(), String> {
+    use crate::config_asset::{save_kcc_config, KccConfig};
+
+    save_kcc_config(path, &KccConfig(*config))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save(_path: &AssetPath, _config: &CharacterControllerConfig) -> Result<(), String> {
+    Err("configs can't be saved on the web".to_string())
+}
+
+fn config_sliders(ui: &mut egui::Ui, config: &mut CharacterControllerConfig) {
+    ui.add(egui::Slider::new(&mut config.gravity_scale, 0.0..=4.0).text("gravity scale"));
+    infinite_slider(ui, "acceleration", &mut config.acceleration, 0.0..=100.0);
+    infinite_slider(ui, "air control", &mut config.air_control, 0.0..=100.0);
+    ui.add(egui::Slider::new(&mut config.max_step_height, 0.0..=1.0).text("max step height"));
+    ui.add(egui::Slider::new(&mut config.min_step_depth, 0.0..=0.5).text("min step depth"));
+
+    let mut max_slope_degrees = config.max_slope_angle.to_degrees();
+    ui.add(egui::Slider::new(&mut max_slope_degrees, 0.0..=90.0).text("max slope angle (°)"));
+
+    if max_slope_degrees != config.max_slope_angle.to_degrees() {
+        config.max_slope_angle = max_slope_degrees.to_radians();
+    }
+
+    ui.add(egui::Slider::new(&mut config.slide_acceleration, 0.0..=4.0).text("slide acceleration"));
+    ui.add(egui::Slider::new(&mut config.slide_control, 0.0..=1.0).text("slide control"));
+    infinite_slider(
+        ui,
+        "max horizontal speed",
+        &mut config.max_horizontal_speed,
+        0.0..=100.0,
+    );
+    infinite_slider(
+        ui,
+        "terminal velocity",
+        &mut config.terminal_velocity,
+        0.0..=100.0,
+    );
+    ui.add(egui::Slider::new(&mut config.jump_buffer, 0.0..=0.5).text("jump buffer (s)"));
+    ui.add(egui::Slider::new(&mut config.coyote_time, 0.0..=0.5).text("coyote time (s)"));
+    ui.add(egui::Slider::new(&mut config.swim_speed, 0.0..=2.0).text("swim speed"));
+    infinite_slider(ui, "swim drag", &mut config.swim_drag, 0.0..=20.0);
+    infinite_slider(ui, "thrust", &mut config.thrust, 0.0..=100.0);
+    infinite_slider(
+        ui,
+        "external ground damping",
+        &mut config.external_ground_damping,
+        0.0..=20.0,
+    );
+    infinite_slider(
+        ui,
+        "external air damping",
+        &mut config.external_air_damping,
+        0.0..=20.0,
+    );
+    ui.add(egui::Slider::new(&mut config.vertical_damping, 0.0..=10.0).text("vertical damping"));
+    ui.add(egui::Slider::new(&mut config.air_jumps, 0..=5).text("air jumps"));
+    ui.checkbox(&mut config.preserve_momentum, "preserve momentum");
+
+    ui.horizontal(|ui| {
+        ui.label("solver fallback");
+
+        for (fallback, text) in [
+            (SolverFallback::Halt, "halt"),
+            (SolverFallback::Depenetrate, "depenetrate"),
+            (SolverFallback::SlideAlongLastPlane, "slide"),
+        ] {
+            ui.radio_value(&mut config.solver_fallback, fallback, text);
+        }
+    });
+}
+
+/// A slider for values that are often infinite, like instant acceleration, with a checkbox that
+/// switches between infinity and the top of the slider's range.
+fn infinite_slider(ui: &mut egui::Ui, text: &str, value: &mut f32, range: RangeInclusive<f32>) {
+    ui.horizontal(|ui| {
+        let mut infinite = value.is_infinite();
+
+        if ui.checkbox(&mut infinite, "∞").changed() {
+            *value = if infinite {
+                f32::INFINITY
+            } else {
+                *range.end()
+            };
+        }
+
+        if infinite {
+            ui.label(text);
+        } else {
+            ui.add(egui::Slider::new(value, range).text(text));
+        }
+    });
+}
@@ -0,0 +1,168 @@
+//! Water volumes that [`Swimmer`]s float in. A swimmer wades through water shallower than its
+//! `swim_depth`, walking on the bottom as usual, and starts swimming once it's submerged deeper
+//! than that. Swimming characters are pushed up in proportion to how much of them is under water,
+//! so they bob towards floating with their `float_depth` submerged, and keep their head above the
+//! waterline. They keep swimming until they stand on the bottom in shallow water, or leave the
+//! water.
+//!
+//! Swimmers that go all the way under, by diving in or swimming down, switch to
+//! [`MovementMode::Swimming`] and move in 3D until they surface again.
+
+use avian3d::{math::AsF32, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{
+        apply_gravity, CharacterController, CharacterControllerConfig, ControllerDisabled,
+        MovementMode, VelocityStage,
+    },
+    schedule::CustomPostUpdate,
+};
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            float_in_water
+                .after(apply_gravity)
+                .in_set(VelocityStage::Gravity),
+        );
+    }
+}
+
+/// A sensor volume of water, whose surface is the top of its AABB.
+///
+/// Needs a [`Sensor`] collider and [`CollidingEntities`] on the same entity.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Water;
+
+/// Lets a character swim in [`Water`]. Depths are the fraction of the character's height that's
+/// under water.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Swimmer {
+    /// How deep the character floats while swimming.
+    pub float_depth: f32,
+    /// How deep the character starts swimming, rather than wading.
+    pub swim_depth: f32,
+    /// How quickly bobbing on the surface dies down.
+    pub damping: f32,
+}
+
+impl Default for Swimmer {
+    fn default() -> Self {
+        Self {
+            float_depth: 0.75,
+            swim_depth: 0.6,
+            damping: 4.0,
+        }
+    }
+}
+
+/// How deep in [`Water`] a [`Swimmer`] is. Added and removed by the [`WaterPlugin`] as the
+/// character enters and leaves water.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Submerged {
+    /// The fraction of the character's height that's under water.
+    pub fraction: f32,
+    /// The height of the water's surface.
+    pub surface: f32,
+    /// Whether the character is swimming rather than wading.
+    pub swimming: bool,
+}
+
+fn float_in_water(
+    mut commands: Commands,
+    water: Query<(&ColliderAabb, &CollidingEntities), With<Water>>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut CharacterController,
+            &Swimmer,
+            &ColliderAabb,
+            Option<&mut Submerged>,
+            Option<&CharacterControllerConfig>,
+        ),
+        Without<ControllerDisabled>,
+    >,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+    let default_config = CharacterControllerConfig::default();
+
+    for (entity, mut character_controller, swimmer, aabb, submerged, config) in &mut characters {
+        // The highest surface of the water the character is in.
+        let surface = water
+            .iter()
+            .filter(|(_, colliding_entities)| colliding_entities.contains(&entity))
+            .map(|(water_aabb, _)| water_aabb.max.y.f32())
+            .reduce(f32::max);
+
+        let Some(surface) = surface else {
+            if submerged.is_some() {
+                leave_water(&mut character_controller);
+                commands.entity(entity).remove::<Submerged>();
+            }
+
+            continue;
+        };
+
+        let bottom = aabb.min.y.f32();
+        let height = (aabb.max.y.f32() - bottom).max(f32::EPSILON);
+        let fraction = ((surface - bottom) / height).clamp(0.0, 1.0);
+
+        let was_swimming = submerged
+            .as_ref()
+            .is_some_and(|submerged| submerged.swimming);
+        let swimming = if was_swimming {
+            fraction > 0.0 && !(character_controller.is_grounded() && fraction < swimmer.swim_depth)
+        } else {
+            fraction >= swimmer.swim_depth
+        };
+
+        let state = Submerged {
+            fraction,
+            surface,
+            swimming,
+        };
+
+        match submerged {
+            Some(mut submerged) => *submerged = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+
+        // Under water once the swimmer's head goes under. Characters that are flying or climbing
+        // keep doing that.
+        if swimming && fraction >= 1.0 {
+            if character_controller.mode.follows_ground() {
+                character_controller.mode = MovementMode::Swimming;
+            }
+        } else {
+            leave_water(&mut character_controller);
+        }
+
+        if !swimming || character_controller.mode == MovementMode::Swimming {
+            continue;
+        }
+
+        // Cancels gravity exactly when floating at `float_depth`, like buoyancy cancels a body's
+        // weight once it displaces as much water as it weighs.
+        let config = config.unwrap_or(&default_config);
+        let gravity = character_controller.gravity * config.gravity_scale;
+        let buoyancy =
+            -gravity.dot(*character_controller.up_direction) * fraction / swimmer.float_depth;
+
+        character_controller.vertical_velocity += buoyancy * delta_seconds;
+        character_controller.vertical_velocity *= (-swimmer.damping * delta_seconds).exp();
+    }
+}
+
+/// Hands a character that's surfacing or leaving the water back to the controller.
+fn leave_water(character_controller: &mut CharacterController) {
+    if character_controller.mode == MovementMode::Swimming {
+        character_controller.mode = MovementMode::Falling;
+    }
+}
@@ -0,0 +1,53 @@
+//! Volumes that push characters inside them, like wind tunnels and river currents.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    character_controller::{
+        CharacterController, CharacterTimeScale, ControllerDisabled, VelocityStage,
+    },
+    schedule::CustomPostUpdate,
+};
+
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            CustomPostUpdate,
+            blow_characters.in_set(VelocityStage::Wind),
+        );
+    }
+}
+
+/// A sensor volume that accelerates characters inside it, through their
+/// [`CharacterController::external_velocity`]. On the ground, that velocity dies down, so a
+/// current settles at a steady speed instead of accelerating characters forever.
+///
+/// Needs a [`Sensor`] collider and [`CollidingEntities`] on the same entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Wind {
+    pub acceleration: Vec3,
+}
+
+fn blow_characters(
+    volumes: Query<(&Wind, &CollidingEntities)>,
+    mut characters: Query<
+        (&mut CharacterController, Option<&CharacterTimeScale>),
+        Without<ControllerDisabled>,
+    >,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    for (wind, colliding_entities) in &volumes {
+        for &entity in colliding_entities.iter() {
+            if let Ok((mut character_controller, time_scale)) = characters.get_mut(entity) {
+                let time_scale = time_scale.map_or(1.0, |time_scale| time_scale.0);
+
+                character_controller.apply_impulse(wind.acceleration * delta_seconds * time_scale);
+            }
+        }
+    }
+}
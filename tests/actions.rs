@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use souls::{
+    actions::{ActionKind, CharacterAction, CharacterActionPlugin, StepActions},
+    character_controller::{JumpKind, SKIN_WIDTH},
+    testing::TestWorld,
+};
+
+fn world_with_character() -> (TestWorld, Entity) {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(CharacterActionPlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    let character = world.spawn_character(Vec3::new(0.0, 1.0 + SKIN_WIDTH, 0.0), Vec3::ZERO);
+    world.step(1);
+    (world, character)
+}
+
+fn send(world: &mut TestWorld, character: Entity, kind: ActionKind) {
+    world
+        .world_mut()
+        .send_event(CharacterAction { character, kind });
+}
+
+#[test]
+fn actions_survive_frames_without_steps() {
+    let (mut world, character) = world_with_character();
+
+    send(&mut world, character, ActionKind::Jump { speed: 5.0 });
+    for _ in 0..4 {
+        world.app_mut().update();
+    }
+
+    world.step(1);
+
+    assert_eq!(
+        world.controller(character).last_jump,
+        Some(JumpKind::Ground)
+    );
+}
+
+#[test]
+fn actions_are_handled_in_exactly_one_step() {
+    let (mut world, character) = world_with_character();
+
+    send(&mut world, character, ActionKind::Interact);
+
+    world.step(1);
+    let interactions = world
+        .world()
+        .resource::<StepActions>()
+        .for_character(character)
+        .count();
+    assert_eq!(interactions, 1);
+
+    world.step(1);
+    assert!(world.world().resource::<StepActions>().0.is_empty());
+}
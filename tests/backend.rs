@@ -0,0 +1,159 @@
+use avian3d::{
+    math::{Quaternion, Scalar, Vector},
+    prelude::*,
+};
+use bevy::prelude::*;
+use souls::{
+    backend::SpatialQueryBackend,
+    character_controller::{move_and_slide, SlideContext, SolverFallback, MAX_SLOPE_ANGLE},
+    testing::{character_collider, TestWorld},
+};
+
+/// A backend for a world with nothing in it.
+struct EmptyBackend;
+
+impl SpatialQueryBackend for EmptyBackend {
+    fn cast_shape(
+        &self,
+        _shape: &Collider,
+        _origin: Vector,
+        _rotation: Quaternion,
+        _direction: Dir3,
+        _max_time_of_impact: Scalar,
+        _filter: &SpatialQueryFilter,
+    ) -> Option<ShapeHitData> {
+        None
+    }
+
+    fn cast_ray(
+        &self,
+        _origin: Vector,
+        _direction: Dir3,
+        _max_time_of_impact: Scalar,
+        _filter: &SpatialQueryFilter,
+    ) -> Option<RayHitData> {
+        None
+    }
+
+    fn intersects_aabb(&self, _aabb: ColliderAabb, _filter: &SpatialQueryFilter) -> bool {
+        false
+    }
+
+    fn shape_intersections(
+        &self,
+        _shape: &Collider,
+        _origin: Vector,
+        _rotation: Quaternion,
+        _filter: &SpatialQueryFilter,
+    ) -> Vec<Entity> {
+        Vec::new()
+    }
+
+    fn contact(
+        &self,
+        _shape: &Collider,
+        _origin: Vector,
+        _rotation: Quaternion,
+        _entity: Entity,
+    ) -> Option<ContactData> {
+        None
+    }
+}
+
+#[test]
+fn solver_queries_through_the_backend() {
+    let mut world = TestWorld::new();
+    world.spawn_box(
+        Vec3::new(2.0, 0.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 4.0),
+    );
+    world.sync_colliders();
+
+    let collider = character_collider();
+    let filter = SpatialQueryFilter::default();
+    let displacement = Vector::X * 4.0;
+
+    let blocked = move_and_slide(
+        &SlideContext {
+            spatial_query: &world.spatial_query(),
+            collider: &collider,
+            rotation: Quaternion::IDENTITY,
+            filter: &filter,
+            up: Dir3::Y,
+            max_slope_angle: MAX_SLOPE_ANGLE,
+            fallback: SolverFallback::Halt,
+            walkable_overrides: None,
+        },
+        Vector::ZERO,
+        displacement,
+        None,
+        false,
+    );
+
+    let unblocked = move_and_slide(
+        &SlideContext {
+            spatial_query: &EmptyBackend,
+            collider: &collider,
+            rotation: Quaternion::IDENTITY,
+            filter: &filter,
+            up: Dir3::Y,
+            max_slope_angle: MAX_SLOPE_ANGLE,
+            fallback: SolverFallback::Halt,
+            walkable_overrides: None,
+        },
+        Vector::ZERO,
+        displacement,
+        None,
+        false,
+    );
+
+    assert!(blocked.translation.x < 1.0, "{}", blocked.translation);
+    assert_eq!(unblocked.translation, displacement);
+}
+
+#[test]
+fn avian_backend_finds_overlaps_and_their_contacts() {
+    let mut world = TestWorld::new();
+    let wall = world.spawn_box(
+        Vec3::new(0.8, 0.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 4.0),
+    );
+    world.sync_colliders();
+
+    let collider = character_collider();
+    let spatial_query = world.spatial_query();
+
+    let overlapping = spatial_query.shape_intersections(
+        &collider,
+        Vector::ZERO,
+        Quaternion::IDENTITY,
+        &SpatialQueryFilter::default(),
+    );
+    assert_eq!(overlapping, vec![wall]);
+
+    // The wall's face is at x = 0.3, and the cylinder reaches to x = 0.5.
+    let contact = spatial_query
+        .contact(&collider, Vector::ZERO, Quaternion::IDENTITY, wall)
+        .unwrap();
+    assert!((contact.penetration - 0.2).abs() < 1e-3, "{contact:?}");
+}
+
+#[test]
+fn avian_backend_ignores_colliders_the_filter_masks_out() {
+    let mut world = TestWorld::new();
+    let block = world.spawn_box(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE);
+    world
+        .world_mut()
+        .entity_mut(block)
+        .insert(CollisionLayers::new(LayerMask(0b10), LayerMask::ALL));
+    world.sync_colliders();
+
+    let aabb = ColliderAabb::new(Vector::ZERO, Vector::ONE);
+    let spatial_query = world.spatial_query();
+
+    assert!(spatial_query.intersects_aabb(aabb, &SpatialQueryFilter::default()));
+    assert!(spatial_query.intersects_aabb(aabb, &SpatialQueryFilter::from_mask(LayerMask(0b10))));
+    assert!(!spatial_query.intersects_aabb(aabb, &SpatialQueryFilter::from_mask(LayerMask(0b01))));
+}
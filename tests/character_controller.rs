@@ -0,0 +1,2027 @@
+use avian3d::{math::AsF32, prelude::*};
+use bevy::prelude::*;
+use souls::{
+    animation::{AnimationState, CharacterAnimationPlugin, CharacterAnimationState},
+    character_controller::{
+        surface_kind, AirJump, AlwaysWalkable, CharacterController, CharacterControllerConfig,
+        CharacterGravity, CharacterTimeScale, ControllerDisabled, CustomMovement,
+        CustomMovementContext, CustomMovementMode, FaceMovement, Jumped, KccSet, Landed,
+        MovementMode, MovementModeChanged, NeverWalkable, NotWalkable, SlopeSpeed, SurfaceKind,
+        SurfaceMaterial, Traction, VelocityStage, EXTERNAL_VELOCITY_DAMPING, MAX_SLOPE_ANGLE,
+        SKIN_WIDTH,
+    },
+    footsteps::{Footstep, FootstepPlugin, Footsteps},
+    gravity_zones::{GravityZone, GravityZonePlugin, ZoneGravity},
+    launch_pads::{LaunchPad, LaunchPadPlugin, Launched},
+    planet::{GravitySource, PlanetPlugin},
+    platforms::{PlatformAttached, PlatformDetached, PlatformPlugin, StandingOn},
+    respawn::{Checkpoint, KillPlane, RespawnPlugin, RespawnPoint},
+    schedule::{CustomPostUpdate, TIMESTEP},
+    testing::TestWorld,
+    time_dilation::{TimeDilationPlugin, TimeDilationZone},
+    wind::{Wind, WindPlugin},
+};
+
+/// A floor whose top is at y = 0.
+fn world_with_floor() -> TestWorld {
+    let mut world = TestWorld::new();
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world
+}
+
+/// Where the demo character's center rests on [`world_with_floor`].
+const STANDING_Y: f32 = 1.0 + SKIN_WIDTH;
+
+/// A wall on [`world_with_floor`] whose -X face is at x = 4.5.
+fn world_with_wall() -> TestWorld {
+    let mut world = world_with_floor();
+    world.spawn_box(
+        Vec3::new(5.0, 1.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+    world
+}
+
+/// Where the demo character's center rests against [`world_with_wall`].
+const WALL_CONTACT_X: f32 = 4.5 - 0.5 - SKIN_WIDTH;
+
+#[test]
+fn moves_freely_on_open_ground() {
+    let mut world = world_with_floor();
+    let character =
+        world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(0.0, 0.0, -4.0));
+
+    world.step(64);
+
+    assert!(world
+        .translation(character)
+        .abs_diff_eq(Vec3::new(0.0, STANDING_Y, -4.0), 1e-3));
+    assert!(world.controller(character).is_grounded());
+}
+
+#[test]
+fn falls_onto_ground() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 5.0, 0.0), Vec3::ZERO);
+
+    world.step(64);
+
+    let translation = world.translation(character);
+
+    assert!((translation.y - STANDING_Y).abs() < 1e-3, "{translation}");
+    assert!(world.controller(character).is_grounded());
+    assert_eq!(world.controller(character).vertical_velocity, 0.0);
+}
+
+#[test]
+fn stops_at_wall() {
+    let mut world = world_with_wall();
+    let character =
+        world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(15.0, 0.0, 0.0));
+
+    world.step(64);
+
+    let translation = world.translation(character);
+
+    assert!(
+        (translation.x - WALL_CONTACT_X).abs() < 1e-3,
+        "{translation}"
+    );
+    assert!(translation.z.abs() < 1e-3, "{translation}");
+}
+
+#[test]
+fn slides_along_wall() {
+    let mut world = world_with_wall();
+    let character =
+        world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(10.0, 0.0, 10.0));
+
+    world.step(64);
+
+    let translation = world.translation(character);
+
+    assert!(
+        (translation.x - WALL_CONTACT_X).abs() < 1e-3,
+        "{translation}"
+    );
+    assert!(translation.z > 5.0, "{translation}");
+}
+
+#[test]
+fn keeps_skin_width_while_pushing_into_wall() {
+    let mut world = world_with_wall();
+    let character =
+        world.spawn_character(Vec3::new(WALL_CONTACT_X, STANDING_Y, 0.0), Vec3::X * 15.0);
+
+    world.step(1);
+    let first = world.translation(character);
+
+    world.step(100);
+    let last = world.translation(character);
+
+    assert!((first.x - WALL_CONTACT_X).abs() < 1e-3, "{first}");
+    assert!(
+        (last.x - first.x).abs() < 1e-4,
+        "crept from {first} to {last}"
+    );
+}
+
+#[test]
+fn rotating_into_a_wall_pushes_box_characters_out() {
+    let mut world = world_with_wall();
+    let character = world.spawn_character(Vec3::new(4.2, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(Collider::cuboid(0.4, 2.0, 2.0));
+    world.step(1);
+
+    // Turning swings the long side of the box into the wall.
+    world
+        .world_mut()
+        .get_mut::<Transform>(character)
+        .unwrap()
+        .rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    world.step(2);
+
+    let translation = world.translation(character);
+    assert!(translation.x <= 4.5 - 1.0 + 1e-3, "{translation}");
+    assert!(translation.x > 4.5 - 1.0 - 0.05, "{translation}");
+}
+
+#[test]
+fn characters_spawned_inside_geometry_are_pushed_out_on_their_first_step() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 0.5, 0.0), Vec3::ZERO);
+
+    world.step(1);
+
+    let translation = world.translation(character);
+    assert!((translation.y - STANDING_Y).abs() < 1e-2, "{translation}");
+
+    world.step(16);
+
+    assert!(world.controller(character).is_grounded());
+    assert!((world.translation(character).y - STANDING_Y).abs() < 1e-3);
+}
+
+#[test]
+fn turning_in_a_narrow_corridor_backs_off_the_rotation() {
+    // A corridor running along Z whose walls are at x = ±0.5.
+    let mut world = world_with_floor();
+    for x in [-1.0, 1.0] {
+        world.spawn_box(
+            Vec3::new(x, 1.0, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(1.0, 4.0, 40.0),
+        );
+    }
+    let collider = Collider::cuboid(0.4, 2.0, 2.0);
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(collider.clone());
+    world.step(1);
+
+    world
+        .world_mut()
+        .get_mut::<Transform>(character)
+        .unwrap()
+        .rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    world.step(2);
+
+    let rotation = world.world().get::<Transform>(character).unwrap().rotation;
+    let angle = rotation.angle_between(Quat::IDENTITY);
+    assert!(
+        angle > 0.1 && angle < std::f32::consts::FRAC_PI_4,
+        "{angle}"
+    );
+
+    let translation = world.translation(character);
+    let filter = SpatialQueryFilter::from_excluded_entities([character]);
+    assert!(world
+        .world()
+        .resource::<SpatialQueryPipeline>()
+        .shape_intersections(&collider, translation, rotation, filter)
+        .is_empty());
+}
+
+#[test]
+fn scaled_characters_stand_on_their_scaled_collider() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 4.0, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .get_mut::<Transform>(character)
+        .unwrap()
+        .scale = Vec3::splat(2.0);
+
+    world.step(128);
+
+    let translation = world.translation(character);
+    assert!(world.controller(character).is_grounded());
+    assert!(
+        (translation.y - (2.0 + SKIN_WIDTH)).abs() < 1e-3,
+        "{translation}"
+    );
+}
+
+#[test]
+fn parented_characters_move_and_face_in_world_space() {
+    let mut world = world_with_floor();
+    let parent_rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    let parent = world
+        .world_mut()
+        .spawn(TransformBundle::from_transform(
+            Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(parent_rotation),
+        ))
+        .id();
+    let character =
+        world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(0.0, 0.0, -4.0));
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(FaceMovement { turn_rate: 100.0 })
+        .set_parent(parent);
+
+    world.step(64);
+
+    assert!(
+        world
+            .translation(character)
+            .abs_diff_eq(Vec3::new(10.0, STANDING_Y, -4.0), 1e-2),
+        "{}",
+        world.translation(character)
+    );
+    let local_rotation = world.world().get::<Transform>(character).unwrap().rotation;
+    assert!((parent_rotation * local_rotation).angle_between(Quat::IDENTITY) < 1e-3);
+}
+
+#[test]
+fn disabled_characters_keep_their_state_until_reenabled() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 5.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+    world.step(10);
+
+    let translation = world.translation(character);
+    let vertical_velocity = world.controller(character).vertical_velocity;
+    let time_since_grounded = world.controller(character).time_since_grounded;
+    assert!(vertical_velocity < 0.0);
+
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(ControllerDisabled);
+    world.step(32);
+
+    assert_eq!(world.translation(character), translation);
+    assert_eq!(
+        world.controller(character).vertical_velocity,
+        vertical_velocity
+    );
+    assert_eq!(
+        world.controller(character).time_since_grounded,
+        time_since_grounded
+    );
+
+    world
+        .world_mut()
+        .entity_mut(character)
+        .remove::<ControllerDisabled>();
+    world.step(1);
+
+    let resumed = world.translation(character);
+    assert!(resumed.y < translation.y, "{resumed}");
+    assert!(resumed.x > translation.x, "{resumed}");
+}
+
+#[test]
+fn flying_characters_thrust_and_drift_without_gravity() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::Y * 5.0);
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .mode = MovementMode::Flying;
+
+    // 20 m/s² of thrust reaches 5 m/s in a quarter second.
+    world.step(64);
+    let y = world.translation(character).y;
+    assert!((y - (STANDING_Y + 5.0 - 0.625)).abs() < 0.1, "{y}");
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .velocity = Vec3::ZERO;
+    world.step(64);
+
+    let drifted = world.translation(character).y - y;
+    assert!((drifted - 5.0).abs() < 0.05, "{drifted}");
+    assert!(!world.controller(character).is_grounded());
+}
+
+#[test]
+fn gravity_scale_lets_characters_float_or_fall_slower() {
+    let mut world = world_with_floor();
+    let ghost = world.spawn_character(Vec3::new(-4.0, 10.0, 0.0), Vec3::ZERO);
+    let drone = world.spawn_character(Vec3::new(0.0, 10.0, 0.0), Vec3::ZERO);
+    let character = world.spawn_character(Vec3::new(4.0, 10.0, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(ghost)
+        .insert(GravityScale(0.0));
+    world
+        .world_mut()
+        .entity_mut(drone)
+        .insert(GravityScale(0.5));
+
+    world.step(32);
+
+    let fallen = |world: &TestWorld, entity| 10.0 - world.translation(entity).y;
+    assert_eq!(fallen(&world, ghost), 0.0);
+    assert!(
+        (fallen(&world, drone) - fallen(&world, character) / 2.0).abs() < 1e-3,
+        "{} {}",
+        fallen(&world, drone),
+        fallen(&world, character)
+    );
+    assert_eq!(world.controller(drone).gravity, Vec3::NEG_Y * 9.81 * 0.5);
+}
+
+#[test]
+fn movement_modes_change_with_the_ground_and_when_set() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 3.0, 0.0), Vec3::ZERO);
+    let drain_changes = |world: &mut TestWorld| -> Vec<(MovementMode, MovementMode)> {
+        world
+            .world_mut()
+            .resource_mut::<Events<MovementModeChanged>>()
+            .drain()
+            .map(|event| (event.from, event.to))
+            .collect()
+    };
+
+    world.step(128);
+    assert_eq!(world.controller(character).mode, MovementMode::Walking);
+    assert_eq!(
+        drain_changes(&mut world),
+        [(MovementMode::Falling, MovementMode::Walking)]
+    );
+
+    // Climbing ignores gravity and isn't left by the controller.
+    let mut character_controller = world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap();
+    character_controller.mode = MovementMode::Climbing;
+    character_controller.velocity = Vec3::Y;
+    world.step(64);
+
+    assert!((world.translation(character).y - (STANDING_Y + 1.0)).abs() < 1e-3);
+    assert_eq!(world.controller(character).mode, MovementMode::Climbing);
+    assert_eq!(
+        drain_changes(&mut world),
+        [(MovementMode::Walking, MovementMode::Climbing)]
+    );
+
+    let mut character_controller = world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap();
+    character_controller.mode = MovementMode::Falling;
+    character_controller.velocity = Vec3::ZERO;
+    world.step(128);
+
+    assert_eq!(world.controller(character).mode, MovementMode::Walking);
+    assert_eq!(
+        drain_changes(&mut world),
+        [
+            (MovementMode::Climbing, MovementMode::Falling),
+            (MovementMode::Falling, MovementMode::Walking)
+        ]
+    );
+}
+
+/// Glides forward at a constant speed, sinking slowly.
+struct Glide;
+
+impl CustomMovementMode for Glide {
+    fn compute_velocity(&mut self, ctx: &CustomMovementContext) -> Vec3 {
+        let sink = (ctx.velocity.y + ctx.gravity.y * ctx.delta_seconds).max(-1.0);
+
+        Vec3::new(5.0, sink, 0.0)
+    }
+}
+
+#[test]
+fn custom_movement_modes_move_through_the_solver() {
+    let mut world = world_with_wall();
+    let character = world.spawn_character(Vec3::new(0.0, 2.5, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CustomMovement::new(Glide))
+        .get_mut::<CharacterController>()
+        .unwrap()
+        .mode = MovementMode::Custom;
+
+    world.step(64);
+
+    // Glides into the wall at x = 4.5 after 0.8 seconds, and sinks along it.
+    let translation = world.translation(character);
+    assert!(
+        (translation.x - WALL_CONTACT_X).abs() < 1e-3,
+        "{translation}"
+    );
+    assert!(translation.y < 1.6 && translation.y > 1.5, "{translation}");
+    assert_eq!(world.controller(character).mode, MovementMode::Custom);
+}
+
+/// The actual speed of every character at the end of each step.
+#[derive(Resource, Default)]
+struct SeenSpeeds(Vec<f32>);
+
+#[test]
+fn hook_sets_run_around_the_move() {
+    let mut world = world_with_floor();
+    world.app_mut().init_resource::<SeenSpeeds>().add_systems(
+        CustomPostUpdate,
+        (
+            (|mut characters: Query<&mut CharacterController>| {
+                for mut character_controller in &mut characters {
+                    character_controller.velocity = Vec3::X * 4.0;
+                }
+            })
+            .in_set(KccSet::PrepareVelocity),
+            (|characters: Query<&CharacterController>, mut seen: ResMut<SeenSpeeds>| {
+                seen.0
+                    .extend(characters.iter().map(|c| c.actual_velocity.length()));
+            })
+            .in_set(KccSet::PostMove),
+        ),
+    );
+    world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+
+    world.step(4);
+
+    let seen = &world.world().resource::<SeenSpeeds>().0;
+    assert_eq!(seen.len(), 4);
+    assert!(
+        seen.iter().all(|speed| (speed - 4.0).abs() < 1e-3),
+        "{seen:?}"
+    );
+}
+
+/// The order systems ran in within a step.
+#[derive(Resource, Default)]
+struct RunOrder(Vec<&'static str>);
+
+fn log_run(name: &'static str) -> impl FnMut(ResMut<RunOrder>) + Send + Sync + 'static {
+    move |mut order: ResMut<RunOrder>| order.0.push(name)
+}
+
+#[test]
+fn velocity_stages_run_in_order_before_the_move() {
+    let mut world = world_with_floor();
+    world.app_mut().init_resource::<RunOrder>().add_systems(
+        CustomPostUpdate,
+        (
+            log_run("post move").in_set(KccSet::PostMove),
+            log_run("wind").in_set(VelocityStage::Wind),
+            log_run("external").in_set(VelocityStage::External),
+            log_run("platform").in_set(VelocityStage::Platform),
+            log_run("gravity").in_set(VelocityStage::Gravity),
+            log_run("input").in_set(VelocityStage::Input),
+        ),
+    );
+
+    world.step(1);
+
+    assert_eq!(
+        world.world().resource::<RunOrder>().0,
+        [
+            "input",
+            "gravity",
+            "platform",
+            "external",
+            "wind",
+            "post move"
+        ]
+    );
+}
+
+#[test]
+fn respawns_below_kill_plane() {
+    let mut world = TestWorld::new();
+    world
+        .app_mut()
+        .add_plugins(RespawnPlugin)
+        .insert_resource(KillPlane { height: -5.0 })
+        .insert_resource(RespawnPoint(Vec3::new(0.0, 10.0, 0.0)));
+
+    let character = world.spawn_character(Vec3::ZERO, Vec3::ZERO);
+
+    // Falling 5 meters takes a little over a second.
+    world.step(80);
+
+    let translation = world.translation(character);
+
+    assert!(translation.y > 5.0, "{translation}");
+}
+
+#[test]
+fn teleporting_resets_the_movement_mode() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 5.0, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .mode = MovementMode::Flying;
+    world.step(4);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .teleport(Vec3::new(5.0, STANDING_Y, 0.0));
+    world.step(1);
+
+    assert_eq!(world.controller(character).mode, MovementMode::Walking);
+    assert!(world.controller(character).is_grounded());
+}
+
+#[test]
+fn checkpoint_moves_respawn_point() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(RespawnPlugin);
+
+    let spawn_point = Vec3::new(0.0, STANDING_Y, -5.0);
+
+    world.world_mut().spawn((
+        Checkpoint { spawn_point },
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(4.0, 3.0, 1.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 1.5, -5.0)),
+    ));
+
+    world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(0.0, 0.0, -4.0));
+
+    world.step(128);
+
+    assert_eq!(world.world().resource::<RespawnPoint>().0, spawn_point);
+}
+
+#[test]
+fn slope_speed_depends_on_incline_along_movement() {
+    let slope_speed = SlopeSpeed {
+        uphill: 0.5,
+        downhill: 1.5,
+    };
+    let normal = Quat::from_rotation_x(MAX_SLOPE_ANGLE) * Vec3::Y;
+    let uphill = Vec3::NEG_Z;
+
+    let multiplier = |direction, normal| slope_speed.multiplier(direction, normal, MAX_SLOPE_ANGLE);
+
+    assert_eq!(multiplier(uphill, Vec3::Y), 1.0);
+    assert!((multiplier(uphill, normal) - 0.5).abs() < 1e-4);
+    assert!((multiplier(-uphill, normal) - 1.5).abs() < 1e-4);
+    assert!((multiplier(Vec3::X, normal) - 1.0).abs() < 1e-4);
+
+    // The full multiplier applies at the character's own slope limit.
+    let steep_limit = 60f32.to_radians();
+    let steep_normal = Quat::from_rotation_x(steep_limit) * Vec3::Y;
+    assert!((slope_speed.multiplier(uphill, normal, steep_limit) - 0.5).abs() > 0.05);
+    assert!((slope_speed.multiplier(uphill, steep_normal, steep_limit) - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn keeps_sliding_on_ice() {
+    let mut world = TestWorld::new();
+    let ice = world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world
+        .world_mut()
+        .entity_mut(ice)
+        .insert(SurfaceMaterial::ICE);
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 5.0);
+
+    world.step(64);
+
+    assert_eq!(
+        world
+            .controller(character)
+            .ground
+            .map(|ground| ground.material),
+        Some(SurfaceMaterial::ICE)
+    );
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .velocity = Vec3::ZERO;
+
+    let stopped_at = world.translation(character);
+    world.step(16);
+
+    assert!(world.translation(character).x > stopped_at.x + 0.5);
+}
+
+#[test]
+fn traction_multiplies_how_quickly_characters_accelerate() {
+    let mut world = TestWorld::new();
+    let mut spawn_ground = |z: f32| {
+        world.spawn_box(
+            Vec3::new(0.0, -0.5, z),
+            Quat::IDENTITY,
+            Vec3::new(40.0, 1.0, 10.0),
+        )
+    };
+    let ice = spawn_ground(-10.0);
+    let slick_ice = spawn_ground(0.0);
+    let slick_floor = spawn_ground(10.0);
+    world
+        .world_mut()
+        .entity_mut(ice)
+        .insert(SurfaceMaterial::ICE);
+    world
+        .world_mut()
+        .entity_mut(slick_ice)
+        .insert((SurfaceMaterial::ICE, Traction(0.2)));
+    world
+        .world_mut()
+        .entity_mut(slick_floor)
+        .insert(Traction(0.2));
+
+    let on_ice = world.spawn_character(Vec3::new(0.0, STANDING_Y, -10.0), Vec3::X * 5.0);
+    let on_slick_ice = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 5.0);
+    let on_slick_floor = world.spawn_character(Vec3::new(0.0, STANDING_Y, 10.0), Vec3::X * 5.0);
+
+    world.step(16);
+
+    let ground_traction = |world: &TestWorld, character| {
+        world
+            .controller(character)
+            .ground
+            .map(|ground| ground.traction)
+    };
+    assert_eq!(ground_traction(&world, on_ice), Some(1.0));
+    assert_eq!(ground_traction(&world, on_slick_ice), Some(0.2));
+
+    let speed = |world: &TestWorld, character| world.controller(character).move_velocity.x;
+    assert!(speed(&world, on_ice) > 1.0, "{}", speed(&world, on_ice));
+    assert!(
+        speed(&world, on_slick_ice) < 0.5,
+        "{}",
+        speed(&world, on_slick_ice)
+    );
+
+    // Instant acceleration stays instant.
+    assert_eq!(speed(&world, on_slick_floor), 5.0);
+}
+
+#[test]
+fn zero_traction_keeps_instant_acceleration_finite() {
+    let mut world = TestWorld::new();
+    let floor = world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.world_mut().entity_mut(floor).insert(Traction(0.0));
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 5.0);
+
+    world.step(16);
+
+    let controller = world.controller(character);
+    assert_eq!(controller.ground.map(|ground| ground.traction), Some(0.0));
+    assert_eq!(controller.move_velocity.x, 5.0);
+    assert!(controller.external_velocity.is_finite());
+    assert!(world.translation(character).is_finite());
+    assert!(world.translation(character).x > 1.0);
+}
+
+#[test]
+fn sends_footstep_every_stride() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(FootstepPlugin);
+
+    let character =
+        world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(0.0, 0.0, -4.5));
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(Footsteps::new(1.0));
+
+    world.step(64);
+
+    let footsteps = world.world().resource::<Events<Footstep>>();
+
+    assert_eq!(footsteps.iter_current_update_events().count(), 4);
+}
+
+#[test]
+fn animation_state_follows_movement() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(CharacterAnimationPlugin);
+
+    let character = world.spawn_character(Vec3::new(0.0, 3.0, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterAnimationState::default());
+
+    let state = |world: &TestWorld| {
+        world
+            .world()
+            .get::<CharacterAnimationState>(character)
+            .unwrap()
+            .state
+    };
+
+    world.step(4);
+    assert_eq!(state(&world), AnimationState::Fall);
+
+    // Falling 2 meters takes about 0.64 seconds.
+    let mut landed = false;
+
+    for _ in 0..64 {
+        world.step(1);
+        landed |= state(&world) == AnimationState::Land;
+    }
+
+    assert!(landed);
+    assert_eq!(state(&world), AnimationState::Idle);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .velocity = Vec3::X * 4.0;
+    world.step(4);
+
+    assert_eq!(state(&world), AnimationState::Walk);
+}
+
+#[test]
+fn turns_to_face_movement_at_turn_rate() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 2.0);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(FaceMovement { turn_rate: 1.0 });
+
+    world.step(1);
+
+    let rotation = world.world().get::<Transform>(character).unwrap().rotation;
+    assert!((rotation.angle_between(Quat::IDENTITY) - 1.0 / 64.0).abs() < 1e-4);
+
+    world.step(128);
+
+    let forward = world.world().get::<Transform>(character).unwrap().forward();
+    assert!(forward.abs_diff_eq(Vec3::X, 1e-4), "{forward}");
+}
+
+#[test]
+fn root_motion_replaces_velocity_for_one_step() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+
+    world.step(1);
+
+    let mut controller = world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap();
+    controller.velocity = Vec3::Z * 100.0;
+    controller.add_root_motion(Vec3::X * 0.25);
+    controller.add_root_motion(Vec3::X * 0.25);
+
+    world.step(1);
+
+    let translation = world.translation(character);
+
+    assert!((translation.x - 0.5).abs() < 1e-4, "{translation}");
+    assert!(translation.z.abs() < 1e-4, "{translation}");
+}
+
+#[test]
+fn steps_up_ledges_lower_than_max_step_height() {
+    let mut world = world_with_floor();
+    world.spawn_box(
+        Vec3::new(13.0, 0.1, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 0.2, 40.0),
+    );
+
+    let blocked = world.spawn_character(Vec3::new(0.0, STANDING_Y, -5.0), Vec3::X * 5.0);
+    let stepping = world.spawn_character(Vec3::new(0.0, STANDING_Y, 5.0), Vec3::X * 5.0);
+    world
+        .world_mut()
+        .entity_mut(stepping)
+        .insert(CharacterControllerConfig::platformer());
+
+    world.step(64);
+
+    let blocked = world.translation(blocked);
+    let stepping = world.translation(stepping);
+
+    assert!((blocked.x - (2.5 - SKIN_WIDTH)).abs() < 1e-3, "{blocked}");
+    assert!(stepping.x > 4.0, "{stepping}");
+    assert!((stepping.y - (STANDING_Y + 0.2)).abs() < 1e-3, "{stepping}");
+}
+
+#[test]
+fn does_not_step_onto_lips_thinner_than_min_step_depth() {
+    let mut world = world_with_floor();
+    // A lip 0.2 high and 0.05 deep whose -X face is at x = 3.
+    world.spawn_box(
+        Vec3::new(3.025, 0.1, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(0.05, 0.2, 40.0),
+    );
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 5.0);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterControllerConfig::platformer());
+
+    world.step(64);
+
+    let translation = world.translation(character);
+    assert!(
+        (translation.x - (2.5 - SKIN_WIDTH)).abs() < 1e-3,
+        "{translation}"
+    );
+    assert!((translation.y - STANDING_Y).abs() < 1e-3, "{translation}");
+}
+
+#[test]
+fn air_control_limits_acceleration_in_the_air() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 10.0, 0.0), Vec3::X * 10.0);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterControllerConfig {
+            air_control: 2.0,
+            ..default()
+        });
+
+    world.step(1);
+
+    let move_velocity = world.controller(character).move_velocity;
+    let expected = 10.0 * (1.0 - (-2.0 * TIMESTEP.as_secs_f32()).exp());
+
+    assert!((move_velocity.x - expected).abs() < 1e-4, "{move_velocity}");
+}
+
+#[test]
+fn stands_on_walls_with_sideways_up_direction() {
+    let mut world = world_with_wall();
+    world.world_mut().insert_resource(Gravity(Vec3::X * 9.81));
+
+    let character = world.spawn_character(Vec3::new(2.0, 3.0, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .up_direction = Dir3::NEG_X;
+
+    world.step(64);
+
+    assert!(world.controller(character).is_grounded());
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .velocity = Vec3::Z * 5.0;
+
+    world.step(32);
+
+    let translation = world.translation(character);
+
+    assert!(world.controller(character).is_grounded());
+    assert!(
+        (translation.x - WALL_CONTACT_X).abs() < 1e-3,
+        "{translation}"
+    );
+    assert!((translation.z - 2.5).abs() < 0.1, "{translation}");
+    assert!((translation.y - 3.0).abs() < 1e-3, "{translation}");
+}
+
+#[test]
+fn stands_on_the_side_of_a_planet() {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(PlanetPlugin);
+    world.world_mut().spawn((
+        RigidBody::Static,
+        Collider::sphere(10.0),
+        GravitySource {
+            strength: 9.81,
+            radius: 30.0,
+        },
+        TransformBundle::default(),
+    ));
+
+    let character = world.spawn_character(Vec3::new(12.0, 0.0, 0.0), Vec3::ZERO);
+
+    world.step(64);
+
+    let controller = world.controller(character);
+    let translation = world.translation(character);
+    let up = world.world().get::<Transform>(character).unwrap().up();
+
+    assert!(controller.is_grounded());
+    assert!(controller.up_direction.abs_diff_eq(Vec3::X, 1e-3));
+    assert!(up.abs_diff_eq(Vec3::X, 1e-3), "{up:?}");
+    assert!(
+        (translation.x - (11.0 + SKIN_WIDTH)).abs() < 1e-2,
+        "{translation}"
+    );
+}
+
+#[test]
+fn sends_platform_events_when_ground_changes() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(PlatformPlugin);
+    let floor = world
+        .world_mut()
+        .query_filtered::<Entity, With<Collider>>()
+        .single(world.world());
+    let platform = world.spawn_box(
+        Vec3::new(5.0, 0.1, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(4.0, 0.2, 4.0),
+    );
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(StandingOn::default());
+
+    world.step(1);
+
+    assert_eq!(
+        world.world().get::<StandingOn>(character),
+        Some(&StandingOn(Some(floor)))
+    );
+
+    // Put the character on the platform.
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .teleport(Vec3::new(5.0, STANDING_Y + 0.2, 0.0));
+    world.step(1);
+
+    assert_eq!(
+        world.world().get::<StandingOn>(character),
+        Some(&StandingOn(Some(platform)))
+    );
+
+    let attached: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<PlatformAttached>>()
+        .drain()
+        .collect();
+    let detached: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<PlatformDetached>>()
+        .drain()
+        .collect();
+
+    assert_eq!(
+        attached,
+        [
+            PlatformAttached {
+                character,
+                platform: floor
+            },
+            PlatformAttached {
+                character,
+                platform
+            },
+        ]
+    );
+    assert_eq!(
+        detached,
+        [PlatformDetached {
+            character,
+            platform: floor
+        }]
+    );
+}
+
+#[test]
+fn stays_grounded_on_moving_elevators() {
+    for speed in [-20.0, 5.0] {
+        let mut world = TestWorld::new();
+        let elevator = world.spawn_box(
+            Vec3::new(0.0, -0.5, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(10.0, 1.0, 10.0),
+        );
+        world
+            .world_mut()
+            .entity_mut(elevator)
+            .insert((RigidBody::Kinematic, LinearVelocity(Vec3::Y * speed)));
+
+        let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+        world.step(1);
+
+        for _ in 0..64 {
+            world.step(1);
+
+            assert!(world.controller(character).is_grounded(), "speed {speed}");
+        }
+
+        let elevator_y = world.translation(elevator).y;
+        let translation = world.translation(character);
+        assert!(
+            (translation.y - (elevator_y + 0.5 + STANDING_Y)).abs() < 0.05,
+            "speed {speed}: {translation}, elevator at {elevator_y}"
+        );
+    }
+}
+
+#[test]
+fn stays_grounded_over_ramp_crests() {
+    let mut world = TestWorld::new();
+    // A plateau whose top is at y = 0 up to x = 0, and a 30° slope falling away from its edge.
+    world.spawn_box(
+        Vec3::new(-10.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 1.0, 10.0),
+    );
+    let slope = Quat::from_rotation_z(-30f32.to_radians());
+    world.spawn_box(
+        slope * Vec3::new(10.0, -0.5, 0.0),
+        slope,
+        Vec3::new(20.0, 1.0, 10.0),
+    );
+
+    let character = world.spawn_character(Vec3::new(-5.0, STANDING_Y, 0.0), Vec3::X * 40.0);
+
+    for _ in 0..24 {
+        world.step(1);
+
+        let translation = world.translation(character);
+        assert!(
+            world.controller(character).is_grounded(),
+            "launched at {translation}"
+        );
+    }
+
+    assert!(world.translation(character).x > 5.0);
+}
+
+#[test]
+fn slides_down_slopes_steeper_than_max_slope_angle() {
+    let mut world = world_with_floor();
+    let slope = Quat::from_rotation_z(-60f32.to_radians());
+    // A 60° slope rising towards -X from the floor at x = 0.
+    world.spawn_box(
+        slope * Vec3::new(-10.0, -0.5, 0.0),
+        slope,
+        Vec3::new(20.0, 1.0, 10.0),
+    );
+
+    let slow = world.spawn_character(Vec3::new(-4.0, 8.9, -2.0), Vec3::ZERO);
+    let fast = world.spawn_character(Vec3::new(-4.0, 8.9, 2.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(fast)
+        .insert(CharacterControllerConfig {
+            slide_acceleration: 2.0,
+            ..default()
+        });
+
+    world.step(16);
+
+    assert!(world.controller(slow).is_sliding());
+    assert!(!world.controller(slow).is_grounded());
+
+    let slide_speed = |world: &TestWorld, character| {
+        world
+            .controller(character)
+            .sliding
+            .unwrap()
+            .velocity
+            .length()
+    };
+    assert!(slide_speed(&world, fast) > slide_speed(&world, slow));
+
+    world.step(128);
+
+    for character in [slow, fast] {
+        let controller = world.controller(character);
+        assert!(controller.is_grounded() && !controller.is_sliding());
+        assert!(world.translation(character).x > 0.0);
+    }
+}
+
+#[test]
+fn cannot_climb_steep_slopes_at_shallow_angles() {
+    let mut world = world_with_floor();
+    let slope = Quat::from_rotation_z(-60f32.to_radians());
+    // A 60° slope rising towards -X from the floor at x = 0.
+    world.spawn_box(
+        slope * Vec3::new(-10.0, -0.5, 0.0),
+        slope,
+        Vec3::new(20.0, 1.0, 40.0),
+    );
+
+    let character =
+        world.spawn_character(Vec3::new(1.0, STANDING_Y, -10.0), Vec3::new(-2.0, 0.0, 8.0));
+
+    for _ in 0..128 {
+        world.step(1);
+
+        let translation = world.translation(character);
+        assert!(translation.y < STANDING_Y + 0.05, "{translation}");
+    }
+
+    assert!(world.translation(character).z > 5.0);
+}
+
+#[test]
+fn slide_control_limits_movement_across_steep_slopes() {
+    let mut world = world_with_floor();
+    let slope = Quat::from_rotation_z(-60f32.to_radians());
+    // A 60° slope rising towards -X from the floor at x = 0.
+    world.spawn_box(
+        slope * Vec3::new(-10.0, -0.5, 0.0),
+        slope,
+        Vec3::new(20.0, 1.0, 40.0),
+    );
+
+    let mut spawn = |z: f32, slide_control: f32| {
+        let character = world.spawn_character(Vec3::new(-4.0, 8.9, z), Vec3::Z * 4.0);
+        world
+            .world_mut()
+            .entity_mut(character)
+            .insert(CharacterControllerConfig {
+                slide_control,
+                ..default()
+            });
+        character
+    };
+    let fixed = spawn(-10.0, 0.0);
+    let steering = spawn(0.0, 0.5);
+
+    world.step(16);
+
+    let mut start = Vec::new();
+
+    for character in [fixed, steering] {
+        assert!(world.controller(character).is_sliding());
+        world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap()
+            .velocity = Vec3::Z * 4.0;
+        start.push(world.translation(character).z);
+    }
+
+    world.step(8);
+
+    let fixed = world.translation(fixed).z - start[0];
+    let steering = world.translation(steering).z - start[1];
+    let full_control = 8.0 * TIMESTEP.as_secs_f32() * 4.0;
+
+    assert!(fixed.abs() < 1e-3, "{fixed}");
+    assert!((steering - full_control * 0.5).abs() < 1e-2, "{steering}");
+}
+
+#[test]
+fn impulses_move_characters_alongside_their_input() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::Z * 2.0);
+    world.step(1);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .apply_impulse(Vec3::new(10.0, 5.0, 0.0));
+    world.step(1);
+
+    let controller = world.controller(character);
+    assert!(!controller.is_grounded());
+    assert!(controller.vertical_velocity > 4.0);
+    assert_eq!(controller.external_velocity, Vec3::new(10.0, 0.0, 0.0));
+
+    world.step(256);
+
+    let translation = world.translation(character);
+    let controller = world.controller(character);
+    assert!(controller.is_grounded());
+    assert!(controller.external_velocity.length() < 1e-3);
+    assert!(translation.x > 5.0, "{translation}");
+    assert!((translation.z - 2.0 * 258.0 * TIMESTEP.as_secs_f32()).abs() < 1e-2);
+}
+
+#[test]
+fn knockback_survives_input_and_slides_further_on_ice() {
+    let mut world = world_with_floor();
+    let ice = world.spawn_box(
+        Vec3::new(0.0, -0.5, 40.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 20.0),
+    );
+    world
+        .world_mut()
+        .entity_mut(ice)
+        .insert(SurfaceMaterial::ICE);
+    world.step(1);
+
+    let on_ground = world.spawn_character(Vec3::new(-10.0, STANDING_Y, -10.0), Vec3::ZERO);
+    let on_ice = world.spawn_character(Vec3::new(-10.0, STANDING_Y, 40.0), Vec3::ZERO);
+    world.step(2);
+
+    for character in [on_ground, on_ice] {
+        world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap()
+            .apply_impulse(Vec3::X * 6.0);
+    }
+
+    // Input is replaced every step without wiping out the knockback.
+    for _ in 0..64 {
+        for character in [on_ground, on_ice] {
+            world
+                .world_mut()
+                .get_mut::<CharacterController>(character)
+                .unwrap()
+                .velocity = Vec3::ZERO;
+        }
+        world.step(1);
+    }
+
+    let slid = |world: &TestWorld, character| world.translation(character).x + 10.0;
+    let on_ground = slid(&world, on_ground);
+    let on_ice = slid(&world, on_ice);
+
+    // 6 m/s dies down over 1 / 6 s on the ground, and 1 / 1.5 s on ice.
+    assert!((on_ground - 1.0).abs() < 0.1, "{on_ground}");
+    assert!(on_ice > 2.5, "{on_ice}");
+}
+
+#[test]
+fn vertical_damping_floats_falls_without_slowing_running() {
+    let mut world = world_with_floor();
+    let falling = world.spawn_character(Vec3::new(-4.0, 200.0, 0.0), Vec3::X * 4.0);
+    let floating = world.spawn_character(Vec3::new(4.0, 200.0, 0.0), Vec3::X * 4.0);
+    world
+        .world_mut()
+        .entity_mut(floating)
+        .insert(CharacterControllerConfig {
+            vertical_damping: 2.0,
+            ..default()
+        });
+
+    world.step(192);
+
+    // Falls level off where the damping cancels gravity, about 9.81 / 2 m/s.
+    let fall_speed = -world.controller(floating).vertical_velocity;
+    assert!((fall_speed - 4.9).abs() < 0.15, "{fall_speed}");
+    assert!(world.controller(falling).vertical_velocity < -25.0);
+
+    let ran = |world: &TestWorld, character| world.translation(character).x;
+    assert!((ran(&world, floating) - 4.0 - (ran(&world, falling) + 4.0)).abs() < 1e-3);
+}
+
+#[test]
+fn knockback_persists_in_the_air_unless_air_damping_is_set() {
+    let mut world = world_with_floor();
+    let keeps = world.spawn_character(Vec3::new(-4.0, 20.0, 0.0), Vec3::Z * 3.0);
+    let damped = world.spawn_character(Vec3::new(4.0, 20.0, 0.0), Vec3::Z * 3.0);
+    world
+        .world_mut()
+        .entity_mut(damped)
+        .insert(CharacterControllerConfig {
+            external_air_damping: 2.0,
+            ..default()
+        });
+
+    for character in [keeps, damped] {
+        world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap()
+            .apply_impulse(Vec3::X * 10.0);
+    }
+    world.step(32);
+
+    assert_eq!(world.controller(keeps).external_velocity, Vec3::X * 10.0);
+
+    let damped_speed = world.controller(damped).external_velocity.x;
+    assert!(
+        (damped_speed - 10.0 * (-1.0_f32).exp()).abs() < 1e-3,
+        "{damped_speed}"
+    );
+
+    // The input velocity isn't damped.
+    let keeps_z = world.translation(keeps).z;
+    assert!((world.translation(damped).z - keeps_z).abs() < 1e-4);
+    assert!((keeps_z - 1.5).abs() < 1e-3, "{keeps_z}");
+}
+
+#[test]
+fn buffered_jumps_preserve_momentum_on_landing() {
+    let mut world = world_with_floor();
+
+    let mut spawn = |z: f32, preserve_momentum: bool| {
+        let character = world.spawn_character(Vec3::new(0.0, 1.5, z), Vec3::ZERO);
+        world
+            .world_mut()
+            .entity_mut(character)
+            .insert(CharacterControllerConfig {
+                acceleration: 5.0,
+                air_control: 0.0,
+                jump_buffer: 0.5,
+                preserve_momentum,
+                ..default()
+            });
+        let mut controller = world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap();
+        controller.move_velocity = Vec3::X * 10.0;
+        controller.jump(5.0);
+        character
+    };
+    let slowed = spawn(-5.0, false);
+    let hopping = spawn(5.0, true);
+
+    // Land, jump right away with the buffered jump, and take off.
+    let mut jumped = [false; 2];
+
+    for _ in 0..32 {
+        world.step(1);
+
+        for (jumped, character) in jumped.iter_mut().zip([slowed, hopping]) {
+            *jumped |= world.controller(character).vertical_velocity > 4.0;
+        }
+    }
+
+    assert_eq!(jumped, [true, true]);
+
+    let slowed = world.controller(slowed).move_velocity.x;
+    let hopping = world.controller(hopping).move_velocity.x;
+
+    assert!(hopping > slowed, "{hopping} <= {slowed}");
+    assert!((hopping - 10.0).abs() < 1e-4, "{hopping}");
+}
+
+#[test]
+fn speed_caps_apply_after_impulses_and_gravity() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, 50.0, 0.0), Vec3::X * 8.0);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterControllerConfig {
+            max_horizontal_speed: 10.0,
+            terminal_velocity: 15.0,
+            ..default()
+        });
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .apply_impulse(Vec3::X * 20.0);
+
+    world.step(128);
+
+    let controller = world.controller(character);
+    assert_eq!(controller.vertical_velocity, -15.0);
+
+    let horizontal_speed = controller.last_move.displacement().f32().x / TIMESTEP.as_secs_f32();
+    assert!((horizontal_speed - 10.0).abs() < 1e-3, "{horizontal_speed}");
+}
+
+#[test]
+fn jumps_within_coyote_time_after_walking_off_ledges() {
+    let mut world = TestWorld::new();
+    // A ledge whose top is at y = 0 and edge is at x = 1.
+    world.spawn_box(
+        Vec3::new(-9.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 1.0, 40.0),
+    );
+
+    let mut spawn = |z: f32, coyote_time: f32| {
+        let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, z), Vec3::X * 8.0);
+        world
+            .world_mut()
+            .entity_mut(character)
+            .insert(CharacterControllerConfig {
+                coyote_time,
+                jump_buffer: 0.0,
+                ..default()
+            });
+        character
+    };
+    let late = spawn(-5.0, 0.0);
+    let coyote = spawn(5.0, 0.1);
+
+    while world.controller(coyote).is_grounded() || world.controller(late).is_grounded() {
+        world.step(1);
+    }
+
+    world.step(2);
+
+    for character in [late, coyote] {
+        world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap()
+            .jump(5.0);
+    }
+
+    world.step(1);
+
+    assert!(world.controller(late).vertical_velocity < 0.0);
+    assert!(world.controller(coyote).vertical_velocity > 4.0);
+}
+
+#[test]
+fn air_jumps_reset_on_landing_and_send_their_own_events() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterControllerConfig {
+            air_jumps: 1,
+            jump_buffer: 0.0,
+            ..default()
+        });
+    world.step(1);
+
+    let mut jump = |world: &mut TestWorld| {
+        world
+            .world_mut()
+            .get_mut::<CharacterController>(character)
+            .unwrap()
+            .jump(5.0);
+        world.step(8);
+    };
+
+    // A ground jump, an air jump, and one more that has none left.
+    jump(&mut world);
+    jump(&mut world);
+    jump(&mut world);
+
+    assert_eq!(world.controller(character).air_jumps_used, 1);
+
+    world.step(128);
+    assert!(world.controller(character).is_grounded());
+
+    jump(&mut world);
+    jump(&mut world);
+
+    let jumped: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<Jumped>>()
+        .drain()
+        .collect();
+    let air_jumps: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<AirJump>>()
+        .drain()
+        .collect();
+
+    assert_eq!(jumped, [Jumped { character }; 2]);
+    assert_eq!(
+        air_jumps,
+        [AirJump {
+            character,
+            count: 1
+        }; 2]
+    );
+}
+
+#[test]
+fn fast_characters_stop_at_thin_walls() {
+    let mut world = world_with_floor();
+    world.spawn_box(
+        Vec3::new(0.0, 2.0, -15.0),
+        Quat::IDENTITY,
+        Vec3::new(6.0, 4.0, 0.005),
+    );
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world.step(1);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .apply_impulse(Vec3::new(0.0, 0.0, -1000.0));
+    world.step(16);
+
+    assert!(world.translation(character).z > -15.0);
+}
+
+#[test]
+fn non_finite_inputs_leave_characters_standing_still() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(
+        Vec3::new(0.0, STANDING_Y, 0.0),
+        Vec3::new(f32::NAN, 0.0, 1.0),
+    );
+    world.step(4);
+
+    assert_eq!(world.controller(character).velocity, Vec3::ZERO);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .apply_impulse(Vec3::new(f32::INFINITY, 0.0, 0.0));
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterTimeScale(f32::NAN));
+    world.step(4);
+
+    assert!(world
+        .translation(character)
+        .abs_diff_eq(Vec3::new(0.0, STANDING_Y, 0.0), 1e-3));
+    assert!(world.controller(character).is_grounded());
+}
+
+#[test]
+fn landing_sends_one_event_with_the_falling_speed() {
+    let mut world = world_with_floor();
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y + 5.0, 0.0), Vec3::ZERO);
+
+    world.step(128);
+    assert!(world.controller(character).is_grounded());
+
+    let landed: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<Landed>>()
+        .drain()
+        .collect();
+
+    assert_eq!(landed.len(), 1);
+    assert_eq!(landed[0].character, character);
+    assert!(landed[0].speed > 5.0);
+    assert_eq!(world.controller(character).last_landing, None);
+}
+
+#[test]
+fn launch_pads_launch_characters_once_per_landing() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(LaunchPadPlugin);
+    let pad = world.spawn_box(
+        Vec3::new(0.0, 0.05, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(4.0, 0.1, 4.0),
+    );
+    world
+        .world_mut()
+        .entity_mut(pad)
+        .insert(LaunchPad(Vec3::new(0.0, 8.0, 2.0)));
+
+    let character = world.spawn_character(Vec3::new(0.0, 3.0, 0.0), Vec3::ZERO);
+
+    let mut launched_at = None;
+
+    for step in 0..96 {
+        world.step(1);
+
+        if world.controller(character).vertical_velocity > 5.0 {
+            launched_at.get_or_insert(step);
+        }
+    }
+
+    assert!(launched_at.is_some());
+    assert!(world.translation(character).z > 1.0);
+
+    let launched: Vec<_> = world
+        .world_mut()
+        .resource_mut::<Events<Launched>>()
+        .drain()
+        .collect();
+
+    assert_eq!(
+        launched,
+        [Launched {
+            character,
+            pad,
+            velocity: Vec3::new(0.0, 8.0, 2.0),
+        }]
+    );
+}
+
+#[test]
+fn wind_pushes_characters_to_a_steady_speed_on_the_ground() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(WindPlugin);
+    world.world_mut().spawn((
+        Wind {
+            acceleration: Vec3::X * 12.0,
+        },
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(40.0, 4.0, 40.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 2.0, 0.0)),
+    ));
+
+    let character = world.spawn_character(Vec3::new(-10.0, STANDING_Y, 0.0), Vec3::ZERO);
+
+    world.step(256);
+
+    let speed = world.controller(character).external_velocity.x;
+    let steady_speed = 12.0 / EXTERNAL_VELOCITY_DAMPING;
+
+    assert!((speed - steady_speed).abs() < 0.2, "{speed}");
+    assert!(world.translation(character).x > -5.0);
+}
+
+#[test]
+fn gravity_zones_stack_by_priority() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(GravityZonePlugin);
+
+    let mut spawn_zone = |gravity: ZoneGravity, priority: i32| {
+        world.world_mut().spawn((
+            GravityZone { gravity, priority },
+            RigidBody::Static,
+            Sensor,
+            Collider::cuboid(10.0, 10.0, 10.0),
+            CollidingEntities::default(),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 5.0, 0.0)),
+        ));
+    };
+    spawn_zone(ZoneGravity::Scale(0.5), 1);
+    spawn_zone(ZoneGravity::Replace(Vec3::Y * 9.81), 0);
+
+    // A ceiling at y = 10 for the character to fall up onto.
+    world.spawn_box(
+        Vec3::new(0.0, 10.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(10.0, 1.0, 10.0),
+    );
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+
+    world.step(128);
+
+    assert_eq!(
+        world.world().get::<CharacterGravity>(character),
+        Some(&CharacterGravity(Vec3::Y * 9.81 * 0.5))
+    );
+    assert_eq!(world.controller(character).up_direction, Dir3::NEG_Y);
+    assert!(world.controller(character).is_grounded());
+    assert!(world.translation(character).y > 8.0);
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .teleport(Vec3::new(15.0, STANDING_Y, 0.0));
+    world.step(2);
+
+    assert!(world.world().get::<CharacterGravity>(character).is_none());
+    assert_eq!(world.controller(character).up_direction, Dir3::Y);
+}
+
+#[test]
+fn gravity_zones_put_back_the_gravity_characters_had() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(GravityZonePlugin);
+    world.world_mut().spawn((
+        GravityZone {
+            gravity: ZoneGravity::Scale(0.5),
+            priority: 0,
+        },
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(10.0, 10.0, 10.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 5.0, 0.0)),
+    ));
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterGravity(Vec3::NEG_Y * 20.0));
+
+    world.step(8);
+
+    assert_eq!(
+        world.world().get::<CharacterGravity>(character),
+        Some(&CharacterGravity(Vec3::NEG_Y * 10.0))
+    );
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .teleport(Vec3::new(15.0, STANDING_Y, 0.0));
+    world.step(2);
+
+    assert_eq!(
+        world.world().get::<CharacterGravity>(character),
+        Some(&CharacterGravity(Vec3::NEG_Y * 20.0))
+    );
+    assert_eq!(world.controller(character).up_direction, Dir3::Y);
+}
+
+#[test]
+fn gravity_zones_stack_on_gravity_sources() {
+    let mut world = TestWorld::new();
+    world
+        .app_mut()
+        .add_plugins((PlanetPlugin, GravityZonePlugin));
+    world.world_mut().spawn((
+        RigidBody::Static,
+        Collider::sphere(10.0),
+        GravitySource {
+            strength: 9.81,
+            radius: 30.0,
+        },
+        TransformBundle::default(),
+    ));
+    let zone = world
+        .world_mut()
+        .spawn((
+            GravityZone {
+                gravity: ZoneGravity::Scale(0.5),
+                priority: 0,
+            },
+            RigidBody::Static,
+            Sensor,
+            Collider::cuboid(4.0, 4.0, 4.0),
+            CollidingEntities::default(),
+            TransformBundle::from_transform(Transform::from_xyz(12.0, 0.0, 0.0)),
+        ))
+        .id();
+
+    let character = world.spawn_character(Vec3::new(12.0, 0.0, 0.0), Vec3::ZERO);
+
+    world.step(64);
+
+    let gravity = |world: &TestWorld| world.world().get::<CharacterGravity>(character).unwrap().0;
+    assert!(
+        gravity(&world).abs_diff_eq(Vec3::NEG_X * 9.81 * 0.5, 1e-2),
+        "{}",
+        gravity(&world)
+    );
+    assert!(world
+        .controller(character)
+        .up_direction
+        .abs_diff_eq(Vec3::X, 1e-3));
+
+    // Leaving the zone while still on the planet keeps the planet's gravity.
+    world.world_mut().despawn(zone);
+    world.step(2);
+
+    assert!(
+        gravity(&world).abs_diff_eq(Vec3::NEG_X * 9.81, 1e-2),
+        "{}",
+        gravity(&world)
+    );
+    assert!(world
+        .controller(character)
+        .up_direction
+        .abs_diff_eq(Vec3::X, 1e-3));
+}
+
+#[test]
+fn coyote_time_runs_on_dilated_time() {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(TimeDilationPlugin);
+    // A ledge whose top is at y = 0 and edge is at x = 1.
+    world.spawn_box(
+        Vec3::new(-9.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 1.0, 40.0),
+    );
+    world.world_mut().spawn((
+        TimeDilationZone { time_scale: 0.25 },
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(10.0, 10.0, 6.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 0.0, 5.0)),
+    ));
+
+    let normal = world.spawn_character(Vec3::new(0.0, STANDING_Y, -5.0), Vec3::X * 8.0);
+    let dilated = world.spawn_character(Vec3::new(0.0, STANDING_Y, 5.0), Vec3::X * 8.0);
+
+    // Both jump 10 steps after leaving the ledge, which is past the default coyote time in normal
+    // time but not at a quarter of it.
+    let mut left_ledge = [None, None];
+    let mut vertical_velocities = [None, None];
+
+    for step in 0..256 {
+        world.step(1);
+
+        for (i, character) in [normal, dilated].into_iter().enumerate() {
+            if left_ledge[i].is_none() && !world.controller(character).is_grounded() {
+                left_ledge[i] = Some(step);
+            }
+
+            if left_ledge[i].map(|left| left + 10) == Some(step) {
+                world
+                    .world_mut()
+                    .get_mut::<CharacterController>(character)
+                    .unwrap()
+                    .jump(5.0);
+            }
+
+            if left_ledge[i].map(|left| left + 11) == Some(step) {
+                vertical_velocities[i] = Some(world.controller(character).vertical_velocity);
+            }
+        }
+    }
+
+    let [Some(normal_velocity), Some(dilated_velocity)] = vertical_velocities else {
+        panic!("{vertical_velocities:?}");
+    };
+
+    assert!(normal_velocity < 0.0, "{normal_velocity}");
+    assert!(dilated_velocity > 4.0, "{dilated_velocity}");
+    assert_eq!(
+        world.world().get::<CharacterTimeScale>(dilated),
+        Some(&CharacterTimeScale(0.25))
+    );
+}
+
+#[test]
+fn time_dilation_zones_multiply_and_restore_the_time_scale() {
+    let mut world = world_with_floor();
+    world.app_mut().add_plugins(TimeDilationPlugin);
+    world.world_mut().spawn((
+        TimeDilationZone { time_scale: 0.25 },
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(10.0, 10.0, 10.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 5.0, 0.0)),
+    ));
+
+    let character = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(CharacterTimeScale(0.5));
+
+    world.step(8);
+
+    assert_eq!(
+        world.world().get::<CharacterTimeScale>(character),
+        Some(&CharacterTimeScale(0.125))
+    );
+
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .teleport(Vec3::new(15.0, STANDING_Y, 0.0));
+    world.step(2);
+
+    assert_eq!(
+        world.world().get::<CharacterTimeScale>(character),
+        Some(&CharacterTimeScale(0.5))
+    );
+}
+
+#[test]
+fn actual_velocity_is_what_the_character_moved() {
+    let mut world = world_with_floor();
+    world.spawn_box(
+        Vec3::new(3.0, 1.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+
+    let open = world.spawn_character(Vec3::new(0.0, STANDING_Y, 10.0), Vec3::NEG_X * 10.0);
+    let blocked = world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::X * 10.0);
+
+    world.step(32);
+
+    let open_velocity = world.controller(open).actual_velocity;
+    let blocked_velocity = world.controller(blocked).actual_velocity;
+
+    assert!(
+        open_velocity.abs_diff_eq(Vec3::NEG_X * 10.0, 1e-3),
+        "{open_velocity}"
+    );
+    assert!(blocked_velocity.length() < 1e-3, "{blocked_velocity}");
+}
+
+#[test]
+fn classifies_surfaces_by_angle() {
+    let classify = |normal: Vec3| surface_kind(normal.normalize(), Dir3::Y, MAX_SLOPE_ANGLE);
+
+    assert_eq!(classify(Vec3::new(0.5, 1.0, 0.0)), SurfaceKind::Walkable);
+    assert_eq!(classify(Vec3::new(2.0, 1.0, 0.0)), SurfaceKind::Steep);
+    assert_eq!(classify(Vec3::X), SurfaceKind::Wall);
+    assert_eq!(classify(Vec3::new(1.0, -1.0, 0.0)), SurfaceKind::Ceiling);
+}
+
+#[test]
+fn walkable_overrides_change_which_slopes_characters_stand_on() {
+    let mut world = world_with_floor();
+    // A 60° slope and a 30° roof, both rising towards -X from the floor at x = 0.
+    let ladder_slope = Quat::from_rotation_z(-60f32.to_radians());
+    let ladder = world.spawn_box(
+        ladder_slope * Vec3::new(-10.0, -0.5, 0.0),
+        ladder_slope,
+        Vec3::new(20.0, 1.0, 10.0),
+    );
+    let roof_slope = Quat::from_rotation_z(-30f32.to_radians());
+    let roof = world.spawn_box(
+        roof_slope * Vec3::new(-10.0, -0.5, 0.0) + Vec3::Z * 12.0,
+        roof_slope,
+        Vec3::new(20.0, 1.0, 10.0),
+    );
+    world.world_mut().entity_mut(ladder).insert(AlwaysWalkable);
+    world.world_mut().entity_mut(roof).insert(NeverWalkable);
+
+    let climber = world.spawn_character(Vec3::new(-4.0, 8.9, 0.0), Vec3::ZERO);
+    let slider = world.spawn_character(Vec3::new(-4.0, 3.7, 12.0), Vec3::ZERO);
+
+    world.step(16);
+
+    assert!(world.controller(climber).is_grounded());
+    assert!(!world.controller(climber).is_sliding());
+    assert!(world.controller(slider).is_sliding());
+    assert!(!world.controller(slider).is_grounded());
+
+    world.step(48);
+
+    let translation = world.translation(climber);
+    assert!((translation.x + 4.0).abs() < 0.1, "{translation}");
+    assert!(world.translation(slider).x > -3.0);
+}
+
+#[test]
+fn characters_cannot_stand_on_or_step_onto_not_walkable_surfaces() {
+    let mut world = world_with_floor();
+    world.spawn_box(
+        Vec3::new(13.0, 0.1, -10.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 0.2, 20.0),
+    );
+    let boundary = world.spawn_box(
+        Vec3::new(13.0, 0.1, 10.0),
+        Quat::IDENTITY,
+        Vec3::new(20.0, 0.2, 20.0),
+    );
+    world.world_mut().entity_mut(boundary).insert(NotWalkable);
+
+    let stepping = world.spawn_character(Vec3::new(0.0, STANDING_Y, -10.0), Vec3::X * 5.0);
+    let blocked = world.spawn_character(Vec3::new(0.0, STANDING_Y, 10.0), Vec3::X * 5.0);
+    let perched = world.spawn_character(Vec3::new(13.0, 3.0, 10.0), Vec3::ZERO);
+
+    for character in [stepping, blocked] {
+        world
+            .world_mut()
+            .entity_mut(character)
+            .insert(CharacterControllerConfig::platformer());
+    }
+
+    world.step(64);
+
+    let stepping = world.translation(stepping);
+    let blocked = world.translation(blocked);
+
+    assert!(stepping.x > 4.0, "{stepping}");
+    assert!((blocked.x - (2.5 - SKIN_WIDTH)).abs() < 1e-3, "{blocked}");
+    assert!((blocked.y - STANDING_Y).abs() < 1e-3, "{blocked}");
+
+    let perched = world.controller(perched);
+    assert!(!perched.is_grounded());
+    assert!(!perched.is_sliding());
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use souls::{
+    character_controller::CharacterControllerConfig,
+    config_asset::{KccConfig, KccConfigHandle, KccConfigPlugin},
+};
+
+#[test]
+fn config_files_only_need_changed_fields() {
+    let config: KccConfig = ron::from_str("(coyote_time: 0.25, acceleration: inf)").unwrap();
+
+    assert_eq!(
+        config.0,
+        CharacterControllerConfig {
+            coyote_time: 0.25,
+            acceleration: f32::INFINITY,
+            ..default()
+        }
+    );
+}
+
+#[test]
+fn shipped_player_config_parses() {
+    let contents = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/configs/player.kcc.ron"
+    ))
+    .unwrap();
+
+    ron::from_str::<KccConfig>(&contents).unwrap();
+}
+
+#[test]
+fn characters_follow_changes_to_their_config_asset() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), KccConfigPlugin));
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<KccConfig>>()
+        .add(KccConfig(CharacterControllerConfig::platformer()));
+    let character = app.world_mut().spawn(KccConfigHandle(handle.clone())).id();
+
+    app.update();
+
+    assert_eq!(
+        app.world().get::<CharacterControllerConfig>(character),
+        Some(&CharacterControllerConfig::platformer())
+    );
+
+    app.world_mut()
+        .resource_mut::<Assets<KccConfig>>()
+        .get_mut(&handle)
+        .unwrap()
+        .0
+        .air_jumps = 3;
+
+    // The asset event for the change can arrive a frame later.
+    app.update();
+    app.update();
+
+    assert_eq!(
+        app.world()
+            .get::<CharacterControllerConfig>(character)
+            .unwrap()
+            .air_jumps,
+        3
+    );
+}
+
+#[test]
+fn saved_configs_load_back_the_same() {
+    let config = KccConfig(CharacterControllerConfig::source_like());
+
+    assert_eq!(
+        ron::from_str::<KccConfig>(&config.to_ron()).unwrap(),
+        config
+    );
+}
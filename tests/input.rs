@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use souls::{
+    input::{FixedInput, FixedInputPlugin},
+    schedule::CustomPreUpdate,
+    testing::TestWorld,
+};
+
+/// How many steps saw Space just pressed and pressed.
+#[derive(Resource, Default)]
+struct Presses {
+    just_pressed: u32,
+    pressed: u32,
+}
+
+fn count_presses(input: Res<FixedInput<KeyCode>>, mut presses: ResMut<Presses>) {
+    presses.just_pressed += u32::from(input.just_pressed(KeyCode::Space));
+    presses.pressed += u32::from(input.pressed(KeyCode::Space));
+}
+
+fn world_with_input() -> TestWorld {
+    let mut world = TestWorld::new();
+    world
+        .app_mut()
+        .add_plugins(FixedInputPlugin)
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<Presses>()
+        .add_systems(CustomPreUpdate, count_presses);
+    world.prepare();
+    world
+}
+
+/// Runs a rendered frame without any fixed steps, then clears the frame's presses like Bevy's
+/// input plugin would.
+fn frame_without_steps(world: &mut TestWorld) {
+    world.app_mut().update();
+    world
+        .world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .clear();
+}
+
+#[test]
+fn presses_in_frames_without_steps_reach_the_next_step_once() {
+    let mut world = world_with_input();
+
+    world
+        .world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    frame_without_steps(&mut world);
+    frame_without_steps(&mut world);
+
+    world.step(2);
+
+    assert_eq!(world.world().resource::<Presses>().just_pressed, 1);
+    assert_eq!(world.world().resource::<Presses>().pressed, 2);
+}
+
+#[test]
+fn taps_between_steps_are_held_for_one_step() {
+    let mut world = world_with_input();
+
+    let mut input = world.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    input.press(KeyCode::Space);
+    input.release(KeyCode::Space);
+    frame_without_steps(&mut world);
+
+    world.step(2);
+
+    assert_eq!(world.world().resource::<Presses>().just_pressed, 1);
+    assert_eq!(world.world().resource::<Presses>().pressed, 1);
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "kcc2d")]
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use souls::{
+    character_controller::SKIN_WIDTH,
+    kcc2d::{CharacterController2d, CharacterController2dPlugin},
+    schedule::{step_custom_schedule, CustomStepping, CustomUpdate, SchedulePlugin},
+};
+
+fn app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        HierarchyPlugin,
+        PhysicsPlugins::new(CustomUpdate),
+        SchedulePlugin,
+        CharacterController2dPlugin,
+    ))
+    .insert_resource(CustomStepping { enabled: true });
+
+    app.finish();
+    app.cleanup();
+    app.update();
+
+    app
+}
+
+fn spawn_box(app: &mut App, translation: Vec2, size: Vec2) {
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::rectangle(size.x, size.y),
+        TransformBundle::from_transform(Transform::from_translation(translation.extend(0.0))),
+    ));
+}
+
+fn spawn_character(app: &mut App, translation: Vec2, velocity: f32) -> Entity {
+    app.world_mut()
+        .spawn((
+            CharacterController2d {
+                velocity,
+                ..default()
+            },
+            RigidBody::Kinematic,
+            Collider::rectangle(1.0, 2.0),
+            TransformBundle::from_transform(Transform::from_translation(translation.extend(0.0))),
+        ))
+        .id()
+}
+
+fn step(app: &mut App, steps: usize) {
+    for _ in 0..steps {
+        step_custom_schedule(app.world_mut());
+    }
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world().get::<Position>(entity).unwrap().0
+}
+
+#[test]
+fn falls_onto_ground() {
+    let mut app = app();
+    spawn_box(&mut app, Vec2::new(0.0, -0.5), Vec2::new(40.0, 1.0));
+    let character = spawn_character(&mut app, Vec2::new(0.0, 3.0), 0.0);
+
+    step(&mut app, 128);
+
+    let position = position(&app, character);
+    assert!((position.y - (1.0 + SKIN_WIDTH)).abs() < 1e-3, "{position}");
+    assert!(app
+        .world()
+        .get::<CharacterController2d>(character)
+        .unwrap()
+        .is_grounded());
+}
+
+#[test]
+fn is_blocked_by_walls() {
+    let mut app = app();
+    spawn_box(&mut app, Vec2::new(0.0, -0.5), Vec2::new(40.0, 1.0));
+    spawn_box(&mut app, Vec2::new(5.0, 2.0), Vec2::new(1.0, 4.0));
+    let character = spawn_character(&mut app, Vec2::new(0.0, 1.0 + SKIN_WIDTH), 10.0);
+
+    step(&mut app, 64);
+
+    let position = position(&app, character);
+    assert!((position.x - (4.0 - SKIN_WIDTH)).abs() < 1e-3, "{position}");
+}
+
+#[test]
+fn steps_up_low_ledges() {
+    let mut app = app();
+    spawn_box(&mut app, Vec2::new(0.0, -0.5), Vec2::new(40.0, 1.0));
+    spawn_box(&mut app, Vec2::new(10.0, 0.1), Vec2::new(10.0, 0.2));
+    let character = spawn_character(&mut app, Vec2::new(0.0, 1.0 + SKIN_WIDTH), 5.0);
+
+    step(&mut app, 128);
+
+    let position = position(&app, character);
+    assert!(position.x > 6.0, "{position}");
+    assert!((position.y - (1.2 + SKIN_WIDTH)).abs() < 1e-2, "{position}");
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use souls::{
+    character_controller::{ControllerDisabled, SKIN_WIDTH},
+    mounts::{Mount, MountExitBlocked, MountExited, MountPlugin, MountRequest, Riding},
+    testing::TestWorld,
+};
+
+const STANDING_Y: f32 = 1.0 + SKIN_WIDTH;
+
+/// A 2 m wide car on a floor, with exits on its left and right.
+fn world_with_car() -> (TestWorld, Entity, Entity) {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(MountPlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    let car = world.spawn_box(
+        Vec3::new(0.0, 0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(2.0, 1.0, 4.0),
+    );
+    world.world_mut().entity_mut(car).insert(Mount {
+        seat: Vec3::new(0.0, 1.5, 0.0),
+        exits: vec![
+            Vec3::new(-2.0, STANDING_Y - 0.5, 0.0),
+            Vec3::new(2.0, STANDING_Y - 0.5, 0.0),
+        ],
+    });
+    let character = world.spawn_character(Vec3::new(4.0, STANDING_Y, 0.0), Vec3::ZERO);
+    world.step(1);
+
+    world.world_mut().send_event(MountRequest::Enter {
+        character,
+        mount: car,
+    });
+    world.step(1);
+
+    (world, car, character)
+}
+
+#[test]
+fn entering_seats_and_disables_the_character() {
+    let (world, car, character) = world_with_car();
+
+    let entity = world.world().entity(character);
+    assert_eq!(entity.get::<Riding>(), Some(&Riding { mount: car }));
+    assert!(entity.contains::<ControllerDisabled>());
+    assert_eq!(entity.get::<Parent>().map(Parent::get), Some(car));
+}
+
+#[test]
+fn exiting_skips_blocked_exits() {
+    let (mut world, _, character) = world_with_car();
+    world.spawn_box(
+        Vec3::new(-2.0, 1.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 4.0),
+    );
+    world.step(1);
+
+    world
+        .world_mut()
+        .send_event(MountRequest::Exit { character });
+    world.step(2);
+
+    let entity = world.world().entity(character);
+    assert!(!entity.contains::<Riding>());
+    assert!(!entity.contains::<ControllerDisabled>());
+    assert!(!entity.contains::<Parent>());
+    assert!(world
+        .translation(character)
+        .abs_diff_eq(Vec3::new(2.0, STANDING_Y, 0.0), 1e-2));
+    assert_eq!(world.world().resource::<Events<MountExited>>().len(), 1);
+}
+
+#[test]
+fn exiting_with_every_exit_blocked_stays_seated() {
+    let (mut world, _, character) = world_with_car();
+    for x in [-2.0, 2.0] {
+        world.spawn_box(
+            Vec3::new(x, 1.0, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(1.0, 4.0, 4.0),
+        );
+    }
+    world.step(1);
+
+    world
+        .world_mut()
+        .send_event(MountRequest::Exit { character });
+    world.step(1);
+
+    assert!(world.world().entity(character).contains::<Riding>());
+    assert_eq!(
+        world.world().resource::<Events<MountExitBlocked>>().len(),
+        1
+    );
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "navigation")]
+
+use bevy::prelude::*;
+use souls::{
+    character_controller::SKIN_WIDTH,
+    navigation::{DesiredVelocity, NavigationAgent, NavigationPath, NavigationPlugin},
+    testing::TestWorld,
+};
+
+#[test]
+fn agent_slows_down_and_stops_at_destination() {
+    let agent = NavigationAgent {
+        destination: Some(Vec3::new(10.0, 0.0, 0.0)),
+        max_speed: 4.0,
+        stopping_distance: 0.5,
+        slowdown_distance: 2.0,
+    };
+    let desired = Vec3::X * 100.0;
+
+    assert_eq!(agent.steer(Vec3::ZERO, desired), Vec3::X * 4.0);
+    assert_eq!(
+        agent.steer(Vec3::new(9.0, 0.0, 0.0), desired),
+        Vec3::X * 2.0
+    );
+    assert_eq!(agent.steer(Vec3::new(9.6, 0.0, 0.0), desired), Vec3::ZERO);
+}
+
+#[test]
+fn agent_follows_path_to_destination() {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(NavigationPlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+
+    let standing_y = 1.0 + SKIN_WIDTH;
+    let character = world.spawn_character(Vec3::new(0.0, standing_y, 0.0), Vec3::ZERO);
+    world.world_mut().entity_mut(character).insert((
+        NavigationAgent::default(),
+        DesiredVelocity::default(),
+        NavigationPath {
+            waypoints: vec![Vec3::new(5.0, 0.0, 0.0), Vec3::new(5.0, 0.0, -5.0)],
+            waypoint_radius: 0.5,
+        },
+    ));
+
+    world.step(256);
+
+    let translation = world.translation(character);
+
+    assert!(
+        translation.with_y(0.0).distance(Vec3::new(5.0, 0.0, -5.0))
+            <= NavigationAgent::default().stopping_distance + 0.05,
+        "{translation}"
+    );
+    assert!(world
+        .world()
+        .get::<NavigationAgent>(character)
+        .unwrap()
+        .destination
+        .is_none());
+}
@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use avian3d::{math::AsF32, prelude::*};
+use bevy::prelude::*;
+use souls::{
+    character_controller::{
+        CharacterController, CharacterControllerConfig, SolverFallback, SKIN_WIDTH,
+    },
+    replay::{
+        config_checksum, frames_to_csv, read_replay_stream, to_canonical_ron, Divergence,
+        FileStorage, FrameRecord, ReplayMetadata, ReplayRng, ReplayStorage, ReplayStream,
+        CSV_HEADER,
+    },
+    schedule::TIMESTEP,
+    snapshot::CharacterSnapshot,
+    testing::TestWorld,
+};
+
+fn save(world: &TestWorld, character: Entity) -> CharacterSnapshot {
+    CharacterSnapshot::save(
+        world.controller(character),
+        world.world().get::<Position>(character).unwrap(),
+        world.world().get::<Transform>(character).unwrap(),
+    )
+}
+
+/// Walks a character into a wall for `steps`, and records the last step.
+fn record_last_step(steps: usize, velocity: Vec3) -> FrameRecord {
+    let mut world = TestWorld::new();
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.spawn_box(
+        Vec3::new(3.0, 1.0, 0.0),
+        Quat::from_rotation_y(0.3),
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+
+    let character = world.spawn_character(Vec3::new(0.0, 1.0 + SKIN_WIDTH, 0.0), velocity);
+
+    world.step(steps - 1);
+    let input = save(&world, character);
+    world.step(1);
+
+    FrameRecord::new(input, save(&world, character), world.controller(character))
+}
+
+#[test]
+fn file_storage_round_trips() {
+    let name = concat!(env!("CARGO_TARGET_TMPDIR"), "/replay_round_trip.ron");
+
+    FileStorage.save(name, "(frames: 1)").unwrap();
+    FileStorage.save(name, "(frames: 2)").unwrap();
+
+    assert_eq!(FileStorage.load(name).unwrap(), "(frames: 2)");
+    assert!(FileStorage.load("missing.ron").is_err());
+}
+
+#[test]
+fn metadata_reports_each_mismatch() {
+    let config = CharacterControllerConfig::default();
+    let recorded = ReplayMetadata::new("corridors", TIMESTEP, &config, 7);
+
+    let serialized = ron::to_string(&recorded).unwrap();
+    assert_eq!(
+        ron::from_str::<ReplayMetadata>(&serialized).unwrap(),
+        recorded
+    );
+
+    assert!(recorded
+        .mismatches(&ReplayMetadata::new("corridors", TIMESTEP, &config, 8))
+        .is_empty());
+
+    let mismatches = recorded.mismatches(&ReplayMetadata::new(
+        "sandbox",
+        TIMESTEP * 2,
+        &CharacterControllerConfig::platformer(),
+        7,
+    ));
+
+    assert_eq!(mismatches.len(), 3, "{mismatches:?}");
+    assert!(mismatches[0].contains("`corridors`") && mismatches[0].contains("`sandbox`"));
+    assert!(mismatches[1].starts_with("timestep"));
+    assert!(mismatches[2].starts_with("config"));
+}
+
+#[test]
+fn config_checksum_sees_every_field() {
+    let config = CharacterControllerConfig::default();
+
+    assert_eq!(
+        config_checksum(&config),
+        config_checksum(&CharacterControllerConfig::default())
+    );
+    assert_ne!(
+        config_checksum(&config),
+        config_checksum(&CharacterControllerConfig {
+            preserve_momentum: true,
+            ..config
+        })
+    );
+    assert_ne!(
+        config_checksum(&config),
+        config_checksum(&CharacterControllerConfig {
+            air_jumps: 1,
+            ..config
+        })
+    );
+    assert_ne!(
+        config_checksum(&config),
+        config_checksum(&CharacterControllerConfig {
+            solver_fallback: SolverFallback::SlideAlongLastPlane,
+            ..config
+        })
+    );
+}
+
+#[test]
+fn rng_replays_from_its_seed() {
+    let mut recorded = ReplayRng::from_time();
+    let mut replayed = ReplayRng::new(recorded.seed());
+
+    for _ in 0..100 {
+        let value = recorded.next_f32();
+
+        assert!((0.0..1.0).contains(&value));
+        assert_eq!(value, replayed.next_f32());
+    }
+
+    assert_ne!(ReplayRng::new(1).next_u64(), ReplayRng::new(2).next_u64());
+}
+
+#[test]
+fn metadata_without_a_seed_loads_as_zero() {
+    let metadata: ReplayMetadata = ron::from_str(
+        "(level: \"sandbox\", timestep: (secs: 0, nanos: 15625000), config_checksum: 1)",
+    )
+    .unwrap();
+
+    assert_eq!(metadata.seed, 0);
+}
+
+#[test]
+fn divergences_keep_both_versions_of_the_frame() {
+    let expected = record_last_step(32, Vec3::new(10.0, 0.0, 3.0));
+
+    assert!(!expected.moved.is_empty());
+    assert!(expected.moved.iter().any(|bounce| bounce.hit.is_some()));
+    assert!(Divergence::check(
+        32,
+        &expected,
+        &record_last_step(32, Vec3::new(10.0, 0.0, 3.0))
+    )
+    .is_none());
+
+    let actual = record_last_step(32, Vec3::new(10.0, 0.0, 3.001));
+    let divergence = Divergence::check(32, &expected, &actual).unwrap();
+
+    assert_eq!(divergence.frame, 32);
+    assert_eq!(divergence.expected, expected);
+    assert_eq!(divergence.actual, actual);
+
+    let serialized = ron::to_string(&divergence).unwrap();
+    assert_eq!(
+        ron::from_str::<Divergence>(&serialized).unwrap(),
+        divergence
+    );
+}
+
+#[test]
+fn csv_has_a_row_per_frame_in_order() {
+    let first = record_last_step(1, Vec3::X * 10.0);
+    let later = record_last_step(32, Vec3::X * 10.0);
+
+    let csv = frames_to_csv([(32, &later), (1, &first)]);
+    let lines: Vec<_> = csv.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], CSV_HEADER);
+    assert!(lines[1].starts_with("1,"));
+
+    let columns: Vec<_> = lines[2].split(',').collect();
+    assert_eq!(columns.len(), CSV_HEADER.split(',').count());
+    assert_eq!(columns[0], "32");
+    assert_eq!(
+        columns[1].parse::<f32>().unwrap(),
+        later.output.translation.f32().x
+    );
+    assert_eq!(columns[7], "true");
+    assert_eq!(columns[8], later.moved.len().to_string());
+}
+
+#[test]
+fn canonical_ron_is_sorted_and_reproducible() {
+    let mut velocities = BTreeMap::new();
+    for frame in [30, 2, 17] {
+        velocities.insert(frame, Vec3::new(0.1, -0.0, frame as f32 / 3.0));
+    }
+
+    let contents = to_canonical_ron(&velocities).unwrap();
+    let loaded: BTreeMap<u32, Vec3> = ron::from_str(&contents).unwrap();
+
+    assert!(!contents.contains('\r'));
+    assert!(contents.find("2:").unwrap() < contents.find("17:").unwrap());
+    assert!(contents.find("17:").unwrap() < contents.find("30:").unwrap());
+    assert_eq!(to_canonical_ron(&loaded).unwrap(), contents);
+}
+
+/// Keeps recordings in memory, and remembers which recordings were saved as finished.
+#[derive(Default)]
+struct MemoryStorage {
+    recordings: Mutex<BTreeMap<String, String>>,
+    saved: Mutex<Vec<String>>,
+}
+
+impl ReplayStorage for MemoryStorage {
+    fn load(&self, name: &str) -> Result<String, String> {
+        self.recordings
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("{name}: no such recording"))
+    }
+
+    fn save(&self, name: &str, contents: &str) -> Result<(), String> {
+        self.saved.lock().unwrap().push(name.to_string());
+        self.create(name, contents)
+    }
+
+    fn create(&self, name: &str, contents: &str) -> Result<(), String> {
+        self.recordings
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), contents.to_string());
+        Ok(())
+    }
+
+    fn append(&self, name: &str, contents: &str) -> Result<(), String> {
+        self.recordings
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default()
+            .push_str(contents);
+        Ok(())
+    }
+}
+
+#[test]
+fn streams_are_never_saved_as_finished() {
+    let storage = MemoryStorage::default();
+    let metadata = ReplayMetadata::new("sandbox", TIMESTEP, &default(), 7);
+
+    let mut stream = ReplayStream::create(&storage, "stream", &metadata, 2).unwrap();
+    for frame in 0..5_u32 {
+        stream.push(&storage, frame).unwrap();
+    }
+    stream.flush(&storage).unwrap();
+
+    let (_, items) = read_replay_stream::<u32>(&storage.load("stream").unwrap()).unwrap();
+    assert_eq!(items, (0..5).collect::<Vec<_>>());
+    assert!(storage.saved.lock().unwrap().is_empty());
+}
+
+#[test]
+fn streams_read_back_everything_pushed() {
+    let name = concat!(env!("CARGO_TARGET_TMPDIR"), "/replay_stream.stream");
+    let metadata = ReplayMetadata::new("sandbox", TIMESTEP, &default(), 7);
+
+    let mut stream = ReplayStream::create(&FileStorage, name, &metadata, 4).unwrap();
+    for frame in 0..10_u32 {
+        stream
+            .push(&FileStorage, (frame, Vec3::X * frame as f32))
+            .unwrap();
+    }
+
+    // Two full chunks have been appended, and the rest are waiting for a flush.
+    let (_, items) = read_replay_stream::<(u32, Vec3)>(&FileStorage.load(name).unwrap()).unwrap();
+    assert_eq!(items.len(), 8);
+
+    stream.flush(&FileStorage).unwrap();
+
+    let (loaded_metadata, items) =
+        read_replay_stream::<(u32, Vec3)>(&FileStorage.load(name).unwrap()).unwrap();
+    assert_eq!(loaded_metadata, metadata);
+    assert_eq!(
+        items,
+        (0..10_u32)
+            .map(|frame| (frame, Vec3::X * frame as f32))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn streams_cut_off_mid_chunk_keep_the_complete_chunks() {
+    let metadata = ReplayMetadata::new("sandbox", TIMESTEP, &default(), 0);
+    let contents = format!(
+        "{}\n[(1, 2.0), (2, 3.0)]\n[(3, 4.",
+        ron::to_string(&metadata).unwrap()
+    );
+
+    let (_, items) = read_replay_stream::<(u32, f32)>(&contents).unwrap();
+
+    assert_eq!(items, [(1, 2.0), (2, 3.0)]);
+    assert!(read_replay_stream::<(u32, f32)>("").is_err());
+}
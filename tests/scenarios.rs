@@ -0,0 +1,20 @@
+use std::fs;
+
+use souls::scenario::Scenario;
+
+#[test]
+fn scenarios() {
+    let mut failures = Vec::new();
+
+    for entry in fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/scenarios")).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            if let Err(error) = Scenario::load(&path).and_then(|scenario| scenario.check()) {
+                failures.push(format!("{}: {error}", path.display()));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
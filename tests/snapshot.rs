@@ -0,0 +1,84 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use souls::{
+    character_controller::{CharacterController, SKIN_WIDTH},
+    snapshot::CharacterSnapshot,
+    testing::TestWorld,
+};
+
+fn save(world: &TestWorld, character: Entity) -> CharacterSnapshot {
+    CharacterSnapshot::save(
+        world.controller(character),
+        world.world().get::<Position>(character).unwrap(),
+        world.world().get::<Transform>(character).unwrap(),
+    )
+}
+
+#[test]
+fn resimulating_from_snapshot_is_deterministic() {
+    let mut world = TestWorld::new();
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.spawn_box(
+        Vec3::new(5.0, 1.0, 0.0),
+        Quat::from_rotation_y(0.3),
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+
+    let character = world.spawn_character(
+        Vec3::new(0.0, 1.0 + SKIN_WIDTH, 0.0),
+        Vec3::new(10.0, 0.0, 3.0),
+    );
+
+    world.step(8);
+    let snapshot = save(&world, character);
+
+    world.step(32);
+    let expected = save(&world, character);
+
+    let mut entity = world.world_mut().entity_mut(character);
+    let mut position = *entity.get::<Position>().unwrap();
+    let mut transform = *entity.get::<Transform>().unwrap();
+    snapshot.load(
+        &mut entity.get_mut::<CharacterController>().unwrap(),
+        &mut position,
+        &mut transform,
+    );
+    entity.insert((position, transform));
+
+    assert_eq!(save(&world, character).checksum(), snapshot.checksum());
+
+    world.step(32);
+    let resimulated = save(&world, character);
+
+    assert_eq!(resimulated, expected);
+    assert_eq!(resimulated.checksum(), expected.checksum());
+}
+
+#[test]
+fn snapshots_restore_the_solved_rotation() {
+    let mut world = TestWorld::new();
+    let character = world.spawn_character(Vec3::ZERO, Vec3::ZERO);
+
+    world.step(1);
+    let snapshot = save(&world, character);
+    assert_eq!(snapshot.solved_rotation, Some(Quat::IDENTITY));
+
+    let mut entity = world.world_mut().entity_mut(character);
+    let mut position = *entity.get::<Position>().unwrap();
+    let mut transform = *entity.get::<Transform>().unwrap();
+    CharacterSnapshot {
+        solved_rotation: None,
+        ..snapshot
+    }
+    .load(
+        &mut entity.get_mut::<CharacterController>().unwrap(),
+        &mut position,
+        &mut transform,
+    );
+
+    assert_eq!(save(&world, character).solved_rotation, None);
+}
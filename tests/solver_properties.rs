@@ -0,0 +1,106 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use proptest::prelude::*;
+use souls::{
+    backend::SpatialQueryBackend,
+    character_controller::{
+        move_and_slide, SlideContext, SolverFallback, MAX_SLOPE_ANGLE, SKIN_WIDTH,
+        SLIDE_NUDGE_MARGIN,
+    },
+    testing::TestWorld,
+};
+
+const RADIUS: f32 = 0.5;
+const HEIGHT: f32 = 2.0;
+
+#[derive(Clone, Debug)]
+struct Block {
+    translation: Vec3,
+    size: Vec3,
+    yaw: f32,
+}
+
+fn block() -> impl Strategy<Value = Block> {
+    (
+        (-6.0f32..6.0, -3.0f32..3.0, -6.0f32..6.0),
+        (0.1f32..6.0, 0.1f32..6.0, 0.1f32..6.0),
+        0.0f32..std::f32::consts::TAU,
+    )
+        .prop_map(|((x, y, z), (width, height, depth), yaw)| Block {
+            translation: Vec3::new(x, y, z),
+            size: Vec3::new(width, height, depth),
+            yaw,
+        })
+}
+
+fn displacement() -> impl Strategy<Value = Vec3> {
+    (-5.0f32..5.0, -5.0f32..5.0, -5.0f32..5.0).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+}
+
+fn setup_world(blocks: &[Block]) -> TestWorld {
+    let mut world = TestWorld::new();
+
+    for block in blocks {
+        world.spawn_box(
+            block.translation,
+            Quat::from_rotation_y(block.yaw),
+            block.size,
+        );
+    }
+
+    world.sync_colliders();
+
+    world
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn move_and_slide_invariants(
+        blocks in prop::collection::vec(block(), 0..6),
+        displacement in displacement(),
+    ) {
+        let mut world = setup_world(&blocks);
+        let spatial_query = world.spatial_query();
+
+        let collider = Collider::cylinder(RADIUS, HEIGHT);
+        let filter = SpatialQueryFilter::default();
+
+        // Only start from positions that are clear of the level.
+        prop_assume!(spatial_query
+            .shape_intersections(&collider, Vec3::ZERO, Quat::IDENTITY, &filter)
+            .is_empty());
+
+        let context = SlideContext {
+            spatial_query: &spatial_query,
+            collider: &collider,
+            rotation: Quat::IDENTITY,
+            filter: &filter,
+            up: Dir3::Y,
+            max_slope_angle: MAX_SLOPE_ANGLE,
+            fallback: SolverFallback::Halt,
+            walkable_overrides: None,
+        };
+
+        let output = move_and_slide(&context, Vec3::ZERO, displacement, None, false);
+
+        // No NaN output for any finite input
+        prop_assert!(output.translation.is_finite());
+
+        // Displacement magnitude is at most the requested distance
+        prop_assert!(
+            output.translation.length() <= displacement.length() + SLIDE_NUDGE_MARGIN,
+            "moved {} but only {} was requested",
+            output.translation.length(),
+            displacement.length(),
+        );
+
+        // The final position never penetrates a collider beyond the skin width
+        let shrunk_collider = Collider::cylinder(RADIUS - SKIN_WIDTH, HEIGHT - 2.0 * SKIN_WIDTH);
+
+        prop_assert!(spatial_query
+            .shape_intersections(&shrunk_collider, output.translation, Quat::IDENTITY, &filter)
+            .is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use souls::{
+    character_controller::SKIN_WIDTH,
+    replay::{FileStorage, ReplayStorage},
+    solver_trace::{SolverTrace, SolverTracePlugin, TraceStep},
+    testing::TestWorld,
+};
+
+#[test]
+fn traces_the_bounces_of_the_chosen_frames() {
+    let name = concat!(env!("CARGO_TARGET_TMPDIR"), "/solver_trace.ron");
+
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(SolverTracePlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.spawn_box(
+        Vec3::new(3.0, 1.0, 0.0),
+        Quat::from_rotation_y(0.3),
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+    let character = world.spawn_character(Vec3::new(0.0, 1.0 + SKIN_WIDTH, 0.0), Vec3::X * 4.0);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(SolverTrace::new(name, 60..64));
+
+    world.step(63);
+    assert!(world.world().entity(character).contains::<SolverTrace>());
+
+    world.step(1);
+    assert!(!world.world().entity(character).contains::<SolverTrace>());
+
+    let steps: Vec<TraceStep> = ron::from_str(&FileStorage.load(name).unwrap()).unwrap();
+    assert_eq!(
+        steps.iter().map(|step| step.frame).collect::<Vec<_>>(),
+        [60, 61, 62, 63]
+    );
+
+    // Walking into the angled wall slides along it.
+    let moved = &steps[3].moved;
+    let wall = moved[0].hit.expect("the first bounce should hit the wall");
+    assert_eq!(moved[0].planes, [wall.normal]);
+    assert_eq!(
+        moved[0].projected,
+        Some(moved[1].direction.as_vec3() * moved[1].distance)
+    );
+    assert!(moved[0].projected.unwrap().dot(wall.normal).abs() < 1e-3);
+}
@@ -0,0 +1,77 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use souls::{
+    character_controller::SKIN_WIDTH,
+    telemetry::{SolverTelemetry, SolverTelemetryPlugin},
+    testing::TestWorld,
+};
+
+const STANDING_Y: f32 = 1.0 + SKIN_WIDTH;
+
+/// A floor whose top is at y = 0, with a wall whose -X face is at x = 4.5.
+fn world_with_wall() -> TestWorld {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(SolverTelemetryPlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.spawn_box(
+        Vec3::new(5.0, 1.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 4.0, 40.0),
+    );
+    world
+}
+
+#[test]
+fn counts_bounces_and_time_over_the_window() {
+    let mut world = world_with_wall();
+    world.spawn_character(Vec3::new(0.0, STANDING_Y, 0.0), Vec3::new(10.0, 0.0, 10.0));
+
+    world.step(64);
+
+    let telemetry = world.world().resource::<SolverTelemetry>();
+    assert!(telemetry.bounces.average() > 1.0, "{:?}", telemetry.bounces);
+    assert!(telemetry.bounces.max() >= 2.0);
+    assert_eq!(telemetry.exhausted.total(), 0.0);
+    assert!(telemetry.step_time.max() > 0.0);
+}
+
+#[test]
+fn measures_how_far_rotations_push_characters_out() {
+    let mut world = world_with_wall();
+    let character = world.spawn_character(Vec3::new(4.2, STANDING_Y, 0.0), Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(Collider::cuboid(0.4, 2.0, 2.0));
+    world.step(1);
+    assert_eq!(
+        world
+            .world()
+            .resource::<SolverTelemetry>()
+            .depenetration
+            .max(),
+        0.0
+    );
+
+    world
+        .world_mut()
+        .get_mut::<Transform>(character)
+        .unwrap()
+        .rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    world.step(2);
+
+    // The box's long side swings 0.7 m into the wall, and is pushed out to the skin width.
+    let depenetration = world
+        .world()
+        .resource::<SolverTelemetry>()
+        .depenetration
+        .max();
+    assert!(
+        (depenetration - (0.7 + SKIN_WIDTH)).abs() < 0.05,
+        "{depenetration}"
+    );
+}
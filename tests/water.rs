@@ -0,0 +1,90 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use souls::{
+    character_controller::{
+        CharacterController, CharacterControllerConfig, MovementMode, SKIN_WIDTH,
+    },
+    testing::TestWorld,
+    water::{Submerged, Swimmer, Water, WaterPlugin},
+};
+
+/// A pool with its bottom at y = -`depth` and its surface at y = 0.
+fn world_with_pool(depth: f32) -> TestWorld {
+    let mut world = TestWorld::new();
+    world.app_mut().add_plugins(WaterPlugin);
+    world.spawn_box(
+        Vec3::new(0.0, -depth - 0.5, 0.0),
+        Quat::IDENTITY,
+        Vec3::new(40.0, 1.0, 40.0),
+    );
+    world.world_mut().spawn((
+        Water,
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(40.0, depth, 40.0),
+        CollidingEntities::default(),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, -depth / 2.0, 0.0)),
+    ));
+    world
+}
+
+fn spawn_swimmer(world: &mut TestWorld, translation: Vec3) -> Entity {
+    let character = world.spawn_character(translation, Vec3::ZERO);
+    world
+        .world_mut()
+        .entity_mut(character)
+        .insert(Swimmer::default());
+    character
+}
+
+#[test]
+fn swimmers_float_with_their_head_above_water() {
+    let mut world = world_with_pool(10.0);
+    let character = spawn_swimmer(&mut world, Vec3::new(0.0, 3.0, 0.0));
+
+    world.step(640);
+
+    let submerged = *world.world().get::<Submerged>(character).unwrap();
+    assert!(submerged.swimming);
+    assert!(
+        (submerged.fraction - Swimmer::default().float_depth).abs() < 0.02,
+        "{submerged:?}"
+    );
+    assert!(world.controller(character).vertical_velocity.abs() < 0.05);
+    assert!(!world.controller(character).is_grounded());
+}
+
+#[test]
+fn swimmers_wade_through_shallow_water() {
+    let mut world = world_with_pool(0.5);
+    let character = spawn_swimmer(&mut world, Vec3::new(0.0, 1.0 - 0.5 + SKIN_WIDTH, 0.0));
+
+    world.step(64);
+
+    let submerged = *world.world().get::<Submerged>(character).unwrap();
+    assert!(!submerged.swimming);
+    assert!((submerged.fraction - 0.25).abs() < 0.01, "{submerged:?}");
+    assert!(world.controller(character).is_grounded());
+}
+
+#[test]
+fn underwater_swimmers_move_in_3d_without_sinking() {
+    let mut world = world_with_pool(10.0);
+    let character = spawn_swimmer(&mut world, Vec3::new(0.0, -5.0, 0.0));
+    world.step(2);
+    assert_eq!(world.controller(character).mode, MovementMode::Swimming);
+
+    let start = world.translation(character);
+    world
+        .world_mut()
+        .get_mut::<CharacterController>(character)
+        .unwrap()
+        .velocity = Vec3::new(0.0, -1.0, -1.0);
+    world.step(64);
+
+    let speed = CharacterControllerConfig::default().swim_speed;
+    let moved = world.translation(character) - start;
+    assert!(moved.y < -0.5 * speed && moved.y > -speed, "{moved}");
+    assert!(moved.z < -0.5 * speed && moved.z > -speed, "{moved}");
+    assert!(!world.controller(character).is_grounded());
+}